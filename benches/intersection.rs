@@ -0,0 +1,64 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use raytracing_in_one_weekend::geometry::{Hittable, Ray, Sphere};
+use raytracing_in_one_weekend::material::{Lambertian, Material};
+use raytracing_in_one_weekend::scenes::random_scene;
+use raytracing_in_one_weekend::utils::color::Color;
+use raytracing_in_one_weekend::utils::random;
+use raytracing_in_one_weekend::utils::vec3::Vec3;
+use raytracing_in_one_weekend::utils::Float;
+use std::rc::Rc;
+
+// A fan of rays from `origin` looking down -z, `n * n` rays in total.
+fn ray_fan(origin: Vec3, spread: Float, n: usize) -> Vec<Ray> {
+    (0..n * n)
+        .map(|i| {
+            let s = (i % n) as Float / (n - 1) as Float - 0.5;
+            let t = (i / n) as Float / (n - 1) as Float - 0.5;
+            Ray::new(&origin, &Vec3::new(spread * s, spread * t, -1.0))
+        })
+        .collect()
+}
+
+// Compare scalar and packed Vec3 with e.g.
+//   cargo bench --bench intersection
+//   cargo bench --bench intersection --features f32,simd
+fn sphere_hit(c: &mut Criterion) {
+    let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let sphere = Sphere::new(Vec3::new(0.0, 0.0, -1.0), 0.5, &mat);
+
+    // Roughly half of the rays hit the sphere.
+    let rays = ray_fan(Vec3::new(0.0, 0.0, 0.0), 1.2, 32);
+
+    let mut group = c.benchmark_group("sphere_hit");
+    group.throughput(Throughput::Elements(rays.len() as u64));
+    group.bench_function("fan_1024", |b| {
+        b.iter(|| {
+            rays.iter()
+                .filter(|r| black_box(&sphere).hit(r, 0.001, Float::INFINITY).is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn hittable_list_hit(c: &mut Criterion) {
+    random::seed(0);
+    let world = random_scene();
+
+    // Looking at the field of small spheres from the front.
+    let rays = ray_fan(Vec3::new(0.0, 2.0, 12.0), 1.5, 32);
+
+    let mut group = c.benchmark_group("hittable_list_hit");
+    group.throughput(Throughput::Elements(rays.len() as u64));
+    group.bench_function("random_scene_fan_1024", |b| {
+        b.iter(|| {
+            rays.iter()
+                .filter(|r| black_box(&world).hit(r, 0.001, Float::INFINITY).is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, sphere_hit, hittable_list_hit);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use raytracing_in_one_weekend::geometry::{HitRecord, Ray};
+use raytracing_in_one_weekend::material::{Dielectric, Lambertian, Material, Metal};
+use raytracing_in_one_weekend::utils::color::Color;
+use raytracing_in_one_weekend::utils::random;
+use raytracing_in_one_weekend::utils::vec3::Vec3;
+use std::rc::Rc;
+
+const SCATTERS: u64 = 1024;
+
+fn scatter(c: &mut Criterion) {
+    let materials: Vec<(&str, Rc<Box<dyn Material>>)> = vec![
+        (
+            "lambertian",
+            Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))),
+        ),
+        (
+            "metal",
+            Rc::new(Box::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.3))),
+        ),
+        ("dielectric", Rc::new(Box::new(Dielectric::new(1.5)))),
+    ];
+
+    let r_in = Ray::new(&Vec3::new(0.0, 1.0, 1.0), &Vec3::new(0.0, -1.0, -1.0));
+
+    let mut group = c.benchmark_group("scatter");
+    group.throughput(Throughput::Elements(SCATTERS));
+    for (name, mat) in materials.iter() {
+        let rec = HitRecord::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            1.0,
+            (0.0, 0.0),
+            &r_in,
+            &Vec3::new(0.0, 1.0, 0.0),
+            mat,
+        );
+
+        random::seed(0);
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                (0..SCATTERS)
+                    .filter(|_| black_box(mat).scatter(&r_in, &rec).is_some())
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scatter);
+criterion_main!(benches);
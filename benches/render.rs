@@ -0,0 +1,54 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use raytracing_in_one_weekend::camera::PerspectiveCamera;
+use raytracing_in_one_weekend::integrator::IntegratorKind;
+use raytracing_in_one_weekend::render::{render, Background, RenderSettings};
+use raytracing_in_one_weekend::sampler::SamplerKind;
+use raytracing_in_one_weekend::scenes::random_scene;
+use raytracing_in_one_weekend::utils::random;
+use raytracing_in_one_weekend::utils::vec3::Vec3;
+
+// A small, seeded version of the final render in `main`. Throughput is
+// reported in camera rays per second.
+fn render_random_scene(c: &mut Criterion) {
+    let settings = RenderSettings {
+        image_width: 60,
+        image_height: 40,
+        samples_per_pixel: 4,
+        max_depth: 50,
+        roulette_depth: 5,
+        background: Background::Sky,
+        crop: None,
+        sampler: SamplerKind::Independent,
+        integrator: IntegratorKind::Path,
+        lights: Vec::new(),
+    };
+
+    random::seed(0);
+    let world = random_scene();
+
+    let camera = PerspectiveCamera::new(
+        Vec3::new(13.0, 2.0, 3.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        settings.image_size(),
+        0.1,
+        10.0,
+    );
+
+    let rays = settings.image_width * settings.image_height * settings.samples_per_pixel as usize;
+
+    let mut group = c.benchmark_group("render");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(rays as u64));
+    group.bench_function("random_scene_60x40_4spp", |b| {
+        b.iter(|| {
+            random::seed(1);
+            render(&world, &camera, &settings)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, render_random_scene);
+criterion_main!(benches);
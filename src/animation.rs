@@ -0,0 +1,9 @@
+mod camera_path;
+mod sequence;
+mod track;
+mod transform_path;
+
+pub use camera_path::CameraPath;
+pub use sequence::{frame_path, render_sequence, Sequence};
+pub use track::{Interpolation, Keyframe, Track};
+pub use transform_path::TransformPath;
@@ -0,0 +1,103 @@
+use super::track::{Interpolation, Track};
+use crate::camera::{PerspectiveCamera, View};
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// A keyframed camera move: position, target, field of view and focus
+/// distance are each interpolated between keys. The field of view and focus
+/// distance stay within the range of their keys.
+pub struct CameraPath {
+    lookfrom: Track<Vec3>,
+    lookat: Track<Vec3>,
+    vfov: Track<Float>,
+    focus_dist: Track<Float>,
+    vup: Vec3,
+    image_size: (usize, usize),
+    aperture: Float,
+}
+
+impl CameraPath {
+    pub fn new(
+        interpolation: Interpolation,
+        vup: Vec3,
+        image_size: (usize, usize),
+        aperture: Float,
+    ) -> Self {
+        Self {
+            lookfrom: Track::new(interpolation),
+            lookat: Track::new(interpolation),
+            vfov: Track::new(interpolation),
+            focus_dist: Track::new(interpolation),
+            vup,
+            image_size,
+            aperture,
+        }
+    }
+
+    pub fn key(
+        mut self,
+        time: Float,
+        lookfrom: Vec3,
+        lookat: Vec3,
+        vfov: Float,
+        focus_dist: Float,
+    ) -> Self {
+        self.lookfrom.insert(time, lookfrom);
+        self.lookat.insert(time, lookat);
+        self.vfov.insert(time, vfov);
+        self.focus_dist.insert(time, focus_dist);
+        self
+    }
+
+    pub fn view_at(&self, time: Float) -> View {
+        View::new(
+            self.lookfrom.sample(time),
+            self.lookat.sample(time),
+            self.vup,
+            self.vfov.sample_clamped(time),
+            self.image_size,
+        )
+    }
+
+    pub fn camera_at(&self, time: Float) -> PerspectiveCamera {
+        self.view_at(time)
+            .perspective(self.aperture, self.focus_dist.sample_clamped(time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn interpolates_every_track() {
+        let path = CameraPath::new(
+            Interpolation::Linear,
+            Vec3::new(0.0, 1.0, 0.0),
+            (300, 200),
+            0.0,
+        )
+        .key(
+            0.0,
+            Vec3::new(0.0, 0.0, 10.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            20.0,
+            10.0,
+        )
+        .key(
+            2.0,
+            Vec3::new(10.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            40.0,
+            6.0,
+        );
+
+        let view = path.view_at(1.0);
+        assert!((view.lookfrom - Vec3::new(5.0, 0.0, 5.0)).length() < EPSILON);
+        assert!((view.lookat - Vec3::new(0.0, 1.0, 0.0)).length() < EPSILON);
+        assert!((view.vfov - 30.0).abs() < EPSILON);
+        assert!((path.view_at(5.0).vfov - 40.0).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::camera::Camera;
+use crate::geometry::Hittable;
+use crate::render::{render, RenderSettings};
+use crate::utils::image::Image;
+use crate::utils::Float;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// A numbered series of frames written to `directory`.
+pub struct Sequence {
+    pub frames: usize,
+    pub fps: Float,
+    pub directory: PathBuf,
+}
+
+/// Where frame `number`, counting from 1, is written: `frame_0001.png`, ...
+pub fn frame_path(directory: &Path, number: usize) -> PathBuf {
+    directory.join(format!("frame_{:04}.png", number))
+}
+
+// A frame is done if it was fully written and has the expected size.
+fn is_complete(path: &Path, settings: &RenderSettings) -> bool {
+    let crop = settings.crop_window();
+    File::open(path)
+        .and_then(|file| Image::read_png(BufReader::new(file)))
+        .map(|image| image.width() == crop.width() && image.height() == crop.height())
+        .unwrap_or(false)
+}
+
+/// Renders every frame of `sequence`, building the scene for each with
+/// `scene(time)`, where frame 1 is at time 0.
+///
+/// Frames that are already on disk are skipped, so an interrupted run can
+/// simply be started again. Each frame is written under a temporary name and
+/// renamed once complete, so a partial file is never mistaken for a finished
+/// frame. Returns the numbers of the frames rendered by this call.
+pub fn render_sequence<W, C, F>(
+    sequence: &Sequence,
+    settings: &RenderSettings,
+    mut scene: F,
+) -> io::Result<Vec<usize>>
+where
+    W: Hittable,
+    C: Camera,
+    F: FnMut(Float) -> (W, C),
+{
+    fs::create_dir_all(&sequence.directory)?;
+
+    let mut rendered = Vec::new();
+    for number in 1..=sequence.frames {
+        let path = frame_path(&sequence.directory, number);
+        if is_complete(&path, settings) {
+            continue;
+        }
+
+        let time = (number - 1) as Float / sequence.fps;
+        let (world, camera) = scene(time);
+        let image = render(&world, &camera, settings);
+
+        let partial = path.with_extension("png.partial");
+        image.write_png(BufWriter::new(File::create(&partial)?))?;
+        fs::rename(&partial, &path)?;
+        rendered.push(number);
+    }
+
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::PerspectiveCamera;
+    use crate::geometry::{HittableList, Sphere};
+    use crate::integrator::IntegratorKind;
+    use crate::material::{Lambertian, Material};
+    use crate::render::Background;
+    use crate::sampler::SamplerKind;
+    use crate::utils::color::Color;
+    use crate::utils::vec3::Vec3;
+    use std::rc::Rc;
+
+    fn scene(time: Float) -> (HittableList, PerspectiveCamera) {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        let mut world = HittableList::new();
+        world.add(Box::new(Sphere::new(Vec3::new(time, 0.0, -2.0), 0.5, &mat)));
+        let camera = PerspectiveCamera::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            60.0,
+            (8, 6),
+            0.0,
+            1.0,
+        );
+        (world, camera)
+    }
+
+    #[test]
+    fn resumes_partial_sequence() {
+        let directory = std::env::temp_dir().join(format!("sequence-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        let sequence = Sequence {
+            frames: 4,
+            fps: 24.0,
+            directory: directory.clone(),
+        };
+        let settings = RenderSettings {
+            image_width: 8,
+            image_height: 6,
+            samples_per_pixel: 1,
+            max_depth: 2,
+            roulette_depth: 2,
+            background: Background::Sky,
+            crop: None,
+            sampler: SamplerKind::Independent,
+            integrator: IntegratorKind::Path,
+            lights: Vec::new(),
+        };
+
+        assert_eq!(
+            render_sequence(&sequence, &settings, scene).unwrap(),
+            vec![1, 2, 3, 4]
+        );
+        assert!(frame_path(&directory, 4).ends_with("frame_0004.png"));
+
+        // Lose one frame and truncate another, as if interrupted.
+        fs::remove_file(frame_path(&directory, 2)).unwrap();
+        let data = fs::read(frame_path(&directory, 3)).unwrap();
+        fs::write(frame_path(&directory, 3), &data[..data.len() / 2]).unwrap();
+
+        assert_eq!(
+            render_sequence(&sequence, &settings, scene).unwrap(),
+            vec![2, 3]
+        );
+        assert!(render_sequence(&sequence, &settings, scene)
+            .unwrap()
+            .is_empty());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+use crate::utils::Float;
+use std::ops::{Add, Mul, Sub};
+
+/// How a `Track` moves between its keys.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Interpolation {
+    /// Straight lines, with sudden changes of speed at each key.
+    Linear,
+    /// A Catmull-Rom spline through the keys, with smooth changes of speed.
+    /// The slope at each key is taken over the time to its neighbors, so
+    /// the speed stays smooth however unevenly the keys are spaced.
+    CatmullRom,
+}
+
+/// Values that can be keyframed: anything that can be blended linearly.
+pub trait Keyframe:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<Float, Output = Self>
+{
+}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Float, Output = T>> Keyframe for T {}
+
+/// A value that changes over time, set by keys at given times. Before the
+/// first key and after the last one the value holds still.
+#[derive(Clone, Debug)]
+pub struct Track<T> {
+    // Sorted by time.
+    keys: Vec<(Float, T)>,
+    interpolation: Interpolation,
+}
+
+impl<T: Keyframe> Track<T> {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            keys: Vec::new(),
+            interpolation,
+        }
+    }
+
+    /// Adds a key, replacing any existing key at the same time.
+    pub fn key(mut self, time: Float, value: T) -> Self {
+        self.insert(time, value);
+        self
+    }
+
+    pub fn insert(&mut self, time: Float, value: T) {
+        match self.keys.iter().position(|&(t, _)| t >= time) {
+            Some(i) if self.keys[i].0 == time => self.keys[i].1 = value,
+            Some(i) => self.keys.insert(i, (time, value)),
+            None => self.keys.push((time, value)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The value at `time`. Panics if the track has no keys.
+    pub fn sample(&self, time: Float) -> T {
+        assert!(!self.keys.is_empty(), "cannot sample a track without keys");
+
+        let last = self.keys.len() - 1;
+        if time <= self.keys[0].0 {
+            return self.keys[0].1;
+        }
+        if time >= self.keys[last].0 {
+            return self.keys[last].1;
+        }
+
+        // The segment from key i to key i + 1 contains `time`.
+        let i = self.keys.partition_point(|&(t, _)| t <= time) - 1;
+        let (t1, p1) = self.keys[i];
+        let (t2, p2) = self.keys[i + 1];
+        let u = (time - t1) / (t2 - t1);
+
+        match self.interpolation {
+            Interpolation::Linear => p1 + (p2 - p1) * u,
+            Interpolation::CatmullRom => {
+                // A cubic Hermite segment, with the slopes at its ends
+                // scaled from per unit of time to per segment.
+                let m1 = self.slope(i) * (t2 - t1);
+                let m2 = self.slope(i + 1) * (t2 - t1);
+                let (u2, u3) = (u * u, u * u * u);
+                p1 * (2.0 * u3 - 3.0 * u2 + 1.0)
+                    + m1 * (u3 - 2.0 * u2 + u)
+                    + p2 * (-2.0 * u3 + 3.0 * u2)
+                    + m2 * (u3 - u2)
+            }
+        }
+    }
+
+    // The rate of change at key `i` per unit of time: the change between its
+    // neighbors over the time between them. A key at either end stands in
+    // for its missing neighbor, the same time away as the other one.
+    fn slope(&self, i: usize) -> T {
+        let last = self.keys.len() - 1;
+        let (t0, p0) = self.keys[i.saturating_sub(1)];
+        let (t2, p2) = self.keys[(i + 1).min(last)];
+        let span = if i == 0 || i == last {
+            2.0 * (t2 - t0)
+        } else {
+            t2 - t0
+        };
+        (p2 - p0) * (1.0 / span)
+    }
+}
+
+impl Track<Float> {
+    /// Like `sample`, kept between the smallest and largest key, so that a
+    /// spline overshooting its keys cannot reach a value none of them has,
+    /// such as a negative distance.
+    pub fn sample_clamped(&self, time: Float) -> Float {
+        let (min, max) = self.keys.iter().fold(
+            (Float::INFINITY, Float::NEG_INFINITY),
+            |(min, max), &(_, v)| (min.min(v), max.max(v)),
+        );
+        self.sample(time).clamp(min, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::vec3::Vec3;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn linear() {
+        let track = Track::new(Interpolation::Linear)
+            .key(2.0, 10.0)
+            .key(0.0, 0.0)
+            .key(3.0, 0.0);
+
+        assert!((track.sample(-1.0) - 0.0).abs() < EPSILON);
+        assert!((track.sample(1.0) - 5.0).abs() < EPSILON);
+        assert!((track.sample(2.5) - 5.0).abs() < EPSILON);
+        assert!((track.sample(9.0) - 0.0).abs() < EPSILON);
+
+        let track = track.key(2.0, 20.0);
+        assert_eq!(track.len(), 3);
+        assert!((track.sample(1.0) - 10.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_keys() {
+        let track = Track::new(Interpolation::CatmullRom)
+            .key(0.0, Vec3::new(0.0, 0.0, 0.0))
+            .key(1.0, Vec3::new(1.0, 2.0, 0.0))
+            .key(2.0, Vec3::new(2.0, 0.0, 0.0))
+            .key(3.0, Vec3::new(3.0, 2.0, 0.0));
+
+        for t in 0..4 {
+            let expected = [0.0, 2.0, 0.0, 2.0][t];
+            assert!((track.sample(t as Float).y() - expected).abs() < EPSILON);
+        }
+
+        // Evenly spaced keys on a line stay evenly spaced in between.
+        assert!((track.sample(1.5).x() - 1.5).abs() < EPSILON);
+        // The curve overshoots the middle segment's straight line.
+        assert!(track.sample(1.25).y() > 1.5);
+    }
+
+    #[test]
+    fn catmull_rom_speed_is_smooth_across_uneven_keys() {
+        // Keys on a line, a long gap then a short one: the value keeps
+        // moving at one speed rather than jumping at the middle key.
+        let track = Track::new(Interpolation::CatmullRom)
+            .key(0.0, 0.0)
+            .key(4.0, 4.0)
+            .key(5.0, 5.0);
+
+        let h = 1e-2;
+        let before = (track.sample(4.0) - track.sample(4.0 - h)) / h;
+        let after = (track.sample(4.0 + h) - track.sample(4.0)) / h;
+        assert!((before - 1.0).abs() < 0.01, "{}", before);
+        assert!((after - 1.0).abs() < 0.01, "{}", after);
+    }
+
+    #[test]
+    fn clamped_samples_stay_within_the_keys() {
+        // Falling steeply and then holding makes the spline dip below the
+        // lowest key.
+        let track = Track::new(Interpolation::CatmullRom)
+            .key(1.0, 10.0)
+            .key(2.0, 0.1)
+            .key(3.0, 0.1);
+
+        let lowest = (0..=100)
+            .map(|i| track.sample(1.0 + i as Float / 50.0))
+            .fold(Float::INFINITY, Float::min);
+        assert!(lowest < 0.0);
+        for i in 0..=100 {
+            let value = track.sample_clamped(1.0 + i as Float / 50.0);
+            assert!((0.1..=10.0).contains(&value), "{}", value);
+        }
+    }
+}
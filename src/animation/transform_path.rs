@@ -0,0 +1,79 @@
+use super::track::{Interpolation, Track};
+use crate::geometry::Transform;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// A keyframed object transform. Rotations are interpolated as Euler
+/// angles, so a turntable is simply a key at 0° and another at 360°. Scales
+/// are interpolated by their logarithm, which keeps them positive and makes
+/// growing tenfold take as long as shrinking tenfold.
+pub struct TransformPath {
+    translation: Track<Vec3>,
+    rotation: Track<Vec3>,
+    // The natural logarithm of the scale.
+    log_scale: Track<Float>,
+}
+
+impl TransformPath {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            translation: Track::new(interpolation),
+            rotation: Track::new(interpolation),
+            log_scale: Track::new(interpolation),
+        }
+    }
+
+    pub fn key(mut self, time: Float, transform: &Transform) -> Self {
+        self.translation.insert(time, *transform.translation());
+        self.rotation.insert(time, *transform.rotation());
+        self.log_scale.insert(time, transform.scale().ln());
+        self
+    }
+
+    pub fn at(&self, time: Float) -> Transform {
+        Transform::new(
+            self.translation.sample(time),
+            self.rotation.sample(time),
+            self.log_scale.sample(time).exp(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn turntable() {
+        let origin = Vec3::new(0.0, 0.0, 0.0);
+        let path = TransformPath::new(Interpolation::Linear)
+            .key(0.0, &Transform::new(origin, origin, 1.0))
+            .key(
+                4.0,
+                &Transform::new(origin, Vec3::new(0.0, 360.0, 0.0), 1.0),
+            );
+
+        // A quarter of the way round, +x has turned to -z.
+        let x = path.at(1.0).vector(&Vec3::new(1.0, 0.0, 0.0));
+        assert!((x - Vec3::new(0.0, 0.0, -1.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn scale_stays_positive() {
+        // Keys that make a spline through the scales themselves dip below
+        // zero.
+        let origin = Vec3::new(0.0, 0.0, 0.0);
+        let path = TransformPath::new(Interpolation::CatmullRom)
+            .key(1.0, &Transform::new(origin, origin, 10.0))
+            .key(2.0, &Transform::new(origin, origin, 0.1))
+            .key(3.0, &Transform::new(origin, origin, 0.1));
+
+        for i in 0..=100 {
+            let scale = path.at(1.0 + i as Float / 50.0).scale();
+            assert!(scale > 0.0);
+        }
+        assert!((path.at(2.0).scale() - 0.1).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::denoise::{denoise, DenoiseSettings};
+use crate::geometry::{Hittable, Ray};
+use crate::material::MaterialIds;
+use crate::sampler::Dimensions;
+use crate::utils::color::Color;
+use crate::utils::image::{write_exr, Image};
+use crate::utils::random;
+use crate::utils::Float;
+use std::io::{self, Seek, Write};
+
+/// An arbitrary output variable: something about the scene other than the
+/// finished image, which `render_with_aovs` renders alongside it for
+/// compositing or denoising. Most describe the first surface the camera sees.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Aov {
+    /// The color of the surface (`Material::albedo`), black where nothing
+    /// is hit.
+    Albedo,
+    /// The shading normal, facing the camera, as x, y and z.
+    Normal,
+    /// The distance along the camera ray to the surface, `HitRecord::t` for
+    /// a unit direction. Infinite where nothing is hit.
+    Depth,
+    /// The point where the camera ray hits, as x, y and z.
+    Position,
+    /// One more than the index of the object in the world's `HittableList`,
+    /// or 0 where nothing is hit.
+    ObjectId,
+    /// A number for the material, counting from 1 in the order the render
+    /// meets them, or 0 where nothing is hit.
+    MaterialId,
+    /// Light from emitters and the background that reaches the camera
+    /// directly or after one bounce, on the paths the integrator traced for
+    /// the image. Black for integrators that do not tell it apart; see
+    /// `Integrator::li_with_direct`.
+    Direct,
+    /// The rest of the light of the image, which bounces more than once, so
+    /// that the two add up to it. Black where `Direct` is.
+    Indirect,
+    /// How uncertain each pixel's color is: the variance of the mean of its
+    /// samples, for each channel, estimated from their spread. Light other
+    /// pixels splat onto it is not counted. Guides `denoise`.
+    Variance,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 9] = [
+        Aov::Albedo,
+        Aov::Normal,
+        Aov::Depth,
+        Aov::Position,
+        Aov::ObjectId,
+        Aov::MaterialId,
+        Aov::Direct,
+        Aov::Indirect,
+        Aov::Variance,
+    ];
+
+    /// The name of the AOV in files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Albedo => "albedo",
+            Aov::Normal => "normal",
+            Aov::Depth => "depth",
+            Aov::Position => "position",
+            Aov::ObjectId => "object_id",
+            Aov::MaterialId => "material_id",
+            Aov::Direct => "direct",
+            Aov::Indirect => "indirect",
+            Aov::Variance => "variance",
+        }
+    }
+
+    /// The names of the channels the AOV's image holds in red, green and
+    /// blue. Those with a single value repeat it in all three.
+    pub fn channels(&self) -> &'static [&'static str] {
+        match self {
+            Aov::Albedo | Aov::Direct | Aov::Indirect | Aov::Variance => &["R", "G", "B"],
+            Aov::Normal | Aov::Position => &["X", "Y", "Z"],
+            Aov::Depth => &["Z"],
+            Aov::ObjectId | Aov::MaterialId => &["id"],
+        }
+    }
+
+    /// Whether a pixel is worked out from all its samples. The others hold
+    /// its first sample, since depths across an edge or IDs do not average
+    /// to anything meaningful.
+    pub fn averaged(&self) -> bool {
+        matches!(
+            self,
+            Aov::Albedo | Aov::Normal | Aov::Direct | Aov::Indirect | Aov::Variance
+        )
+    }
+
+    // Whether the AOV is measured in light, and so scaled by the camera's
+    // exposure like the image.
+    fn is_light(&self) -> bool {
+        matches!(self, Aov::Direct | Aov::Indirect)
+    }
+
+    // Whether the AOV describes the first surface hit, which has to be
+    // found for it.
+    fn is_surface(&self) -> bool {
+        !matches!(self, Aov::Direct | Aov::Indirect | Aov::Variance)
+    }
+}
+
+/// A rendered image and the AOVs asked for, in the same order.
+pub struct Layers {
+    pub color: Image,
+    pub aovs: Vec<(Aov, Image)>,
+}
+
+impl Layers {
+    pub fn aov(&self, aov: Aov) -> Option<&Image> {
+        self.aovs
+            .iter()
+            .find(|(a, _)| *a == aov)
+            .map(|(_, image)| image)
+    }
+
+    /// The image with its noise removed by `denoise`, if `Aov::Albedo`,
+    /// `Aov::Normal` and `Aov::Variance` were rendered.
+    pub fn denoised(&self, settings: &DenoiseSettings) -> Option<Image> {
+        let albedo = self.aov(Aov::Albedo)?;
+        let normal = self.aov(Aov::Normal)?;
+        let variance = self.aov(Aov::Variance)?;
+        Some(denoise(&self.color, albedo, normal, variance, settings))
+    }
+
+    /// Writes the image and its AOVs as one OpenEXR file: the image as the
+    /// channels R, G and B, and each AOV as its channels prefixed by its
+    /// name, such as `normal.X`.
+    pub fn write_exr<T: Write + Seek>(&self, writer: T) -> io::Result<()> {
+        let mut layers: Vec<(&str, &Image, &[&str])> = vec![("", &self.color, &["R", "G", "B"])];
+        for (aov, image) in &self.aovs {
+            layers.push((aov.name(), image, aov.channels()));
+        }
+        write_exr(writer, &layers)
+    }
+}
+
+// The AOVs of the pixels being rendered, gathered sample by sample.
+pub(crate) struct AovBuffers {
+    aovs: Vec<Aov>,
+    // The sum or first sample of each AOV, for each pixel. For the variance,
+    // the sum of the squared colors.
+    values: Vec<Vec<Color>>,
+    // The sum of the colors of each pixel, if the variance is wanted.
+    sums: Vec<Color>,
+    material_ids: MaterialIds,
+}
+
+impl AovBuffers {
+    pub(crate) fn new(aovs: &[Aov], pixels: usize) -> Self {
+        Self {
+            aovs: aovs.to_vec(),
+            values: vec![vec![Color::black(); pixels]; aovs.len()],
+            sums: if aovs.contains(&Aov::Variance) {
+                vec![Color::black(); pixels]
+            } else {
+                Vec::new()
+            },
+            material_ids: MaterialIds::default(),
+        }
+    }
+
+    // Adds sample `index` of pixel `pixel`, taken along the camera ray `r`,
+    // where the integrator found the light `color`, of which `direct` is
+    // direct if it tells.
+    pub(crate) fn add_sample<T: Hittable + ?Sized>(
+        &mut self,
+        pixel: usize,
+        index: usize,
+        r: &Ray,
+        world: &T,
+        color: Color,
+        direct: Option<Color>,
+    ) {
+        if self.aovs.is_empty() {
+            return;
+        }
+
+        let first = index == 0;
+        let wanted = |aov: &Aov| first || aov.averaged();
+        // Materials are numbered on every sample, in the order the first
+        // hits meet them, as `MaterialIdIntegrator` numbers them.
+        let numbers_materials = self.aovs.contains(&Aov::MaterialId);
+        let hit =
+            if numbers_materials || self.aovs.iter().any(|aov| aov.is_surface() && wanted(aov)) {
+                random::start_dimensions(Dimensions::Bounce(0));
+                world.hit(r, 0.001, Float::INFINITY)
+            } else {
+                None
+            };
+        let (direct, indirect) = match direct {
+            Some(direct) => (direct, color - direct),
+            None => (Color::black(), Color::black()),
+        };
+        if let Some(sum) = self.sums.get_mut(pixel) {
+            *sum += color;
+        }
+        let material_id = match &hit {
+            Some(rec) if numbers_materials => self.material_ids.id(&rec.mat),
+            _ => 0,
+        };
+
+        for (aov, values) in self.aovs.iter().zip(self.values.iter_mut()) {
+            if !wanted(aov) {
+                continue;
+            }
+            let value = match (aov, &hit) {
+                (Aov::Direct, _) => direct,
+                (Aov::Indirect, _) => indirect,
+                (Aov::Variance, _) => color * color,
+                (Aov::Depth, None) => gray(Float::INFINITY),
+                (_, None) => Color::black(),
+                (Aov::Albedo, Some(rec)) => rec.mat.albedo(rec),
+                (Aov::Normal, Some(rec)) => {
+                    let n = rec.shading_normal;
+                    Color::new(n.x(), n.y(), n.z())
+                }
+                (Aov::Depth, Some(rec)) => gray(rec.t * r.direction().length()),
+                (Aov::Position, Some(rec)) => Color::new(rec.p.x(), rec.p.y(), rec.p.z()),
+                (Aov::ObjectId, Some(rec)) => gray((rec.object + 1) as Float),
+                (Aov::MaterialId, Some(_)) => gray(material_id as Float),
+            };
+
+            if aov.averaged() {
+                values[pixel] += value;
+            } else {
+                values[pixel] = value;
+            }
+        }
+    }
+
+    // The images of the AOVs, with the sums divided by the number of samples
+    // per pixel and light scaled by `exposure`.
+    pub(crate) fn finish(
+        self,
+        width: usize,
+        height: usize,
+        samples: Float,
+        exposure: Float,
+    ) -> Vec<(Aov, Image)> {
+        let sums = self.sums;
+        self.aovs
+            .into_iter()
+            .zip(self.values)
+            .map(|(aov, values)| {
+                let scale = match (aov.averaged(), aov.is_light()) {
+                    (true, true) => exposure / samples,
+                    (true, false) => 1.0 / samples,
+                    _ => 1.0,
+                };
+                let mut image = Image::new(width, height);
+                for (i, value) in values.into_iter().enumerate() {
+                    let value = if aov == Aov::Variance {
+                        // The unbiased variance of the samples, divided by
+                        // their number for that of their mean.
+                        let mean = sums[i] / samples;
+                        let spread = (value * scale - mean * mean).map(|v| v.max(0.0));
+                        spread * (exposure * exposure / (samples - 1.0).max(1.0))
+                    } else {
+                        value * scale
+                    };
+                    image.set(i % width, i / width, value);
+                }
+                (aov, image)
+            })
+            .collect()
+    }
+}
+
+fn gray(value: Float) -> Color {
+    Color::new(value, value, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use crate::geometry::{HittableList, Quad};
+    use crate::integrator::{material_color, IntegratorKind};
+    use crate::material::{Lambertian, Material, Metal};
+    use crate::render::{render_with_aovs, Background, RenderSettings};
+    use crate::sampler::SamplerKind;
+    use crate::utils::vec3::Vec3;
+    use std::rc::Rc;
+
+    const EPSILON: Float = 0.00001;
+
+    // Looks down -z with parallel rays, from x in [-2, 2] and y in [-1, 1].
+    struct Parallel;
+
+    impl Camera for Parallel {
+        fn get_ray(&self, s: Float, t: Float) -> Ray {
+            let origin = Vec3::new(4.0 * s - 2.0, 2.0 * t - 1.0, 1.0);
+            Ray::new(&origin, &Vec3::new(0.0, 0.0, -1.0))
+        }
+    }
+
+    // A red wall behind the left half of a 4 by 2 image and a mirror behind
+    // the third column, leaving the last to the white background. A grey
+    // quad behind the camera shades the wall and what the mirror sees.
+    fn render(aovs: &[Aov], integrator: IntegratorKind) -> Layers {
+        let red: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Lambertian::new(Color::new(0.8, 0.1, 0.1))));
+        let mirror: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Metal::new(Color::new(0.5, 0.5, 0.5), 0.0)));
+        let mut world = HittableList::new();
+        world.add(Box::new(Quad::new(
+            Vec3::new(-2.0, -1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            &red,
+        )));
+        world.add(Box::new(Quad::new(
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            &mirror,
+        )));
+        let grey: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        world.add(Box::new(Quad::new(
+            Vec3::new(-1.0, -0.5, 2.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &grey,
+        )));
+
+        let settings = RenderSettings {
+            image_width: 4,
+            image_height: 2,
+            samples_per_pixel: 8,
+            max_depth: 4,
+            roulette_depth: 4,
+            background: Background::Solid(Color::white()),
+            crop: None,
+            sampler: SamplerKind::Independent,
+            integrator,
+            lights: Vec::new(),
+        };
+        render_with_aovs(&world, &Parallel, &settings, aovs, |_| {})
+    }
+
+    fn assert_close(a: Color, b: Color) {
+        let d = a - b;
+        let distance = d.r().abs() + d.g().abs() + d.b().abs();
+        assert!(distance < EPSILON, "{:?} {:?}", a, b);
+    }
+
+    #[test]
+    fn describes_the_first_surface() {
+        let layers = render(&Aov::ALL, IntegratorKind::Path);
+        assert_eq!(layers.aovs.len(), Aov::ALL.len());
+        let aov = |aov, x| layers.aov(aov).unwrap().get(x, 1);
+
+        assert_close(aov(Aov::Albedo, 0), Color::new(0.8, 0.1, 0.1));
+        assert_close(aov(Aov::Albedo, 2), Color::new(0.5, 0.5, 0.5));
+        assert_close(aov(Aov::Albedo, 3), Color::black());
+
+        assert_close(aov(Aov::Normal, 1), Color::new(0.0, 0.0, 1.0));
+        assert_close(aov(Aov::Normal, 3), Color::black());
+
+        assert!((aov(Aov::Depth, 2).r() - 1.0).abs() < EPSILON);
+        assert_eq!(aov(Aov::Depth, 3).r(), Float::INFINITY);
+
+        let p = aov(Aov::Position, 1);
+        assert!((-1.0..0.0).contains(&p.r()) && (-1.0..0.0).contains(&p.g()));
+        assert!(p.b().abs() < EPSILON);
+
+        let ids: Vec<Float> = (0..4).map(|x| aov(Aov::ObjectId, x).r()).collect();
+        assert_eq!(ids, vec![1.0, 1.0, 2.0, 0.0]);
+        let ids: Vec<Float> = (0..4).map(|x| aov(Aov::MaterialId, x).r()).collect();
+        assert_eq!(ids, vec![1.0, 1.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn variance_of_the_mean() {
+        let mut buffers = AovBuffers::new(&[Aov::Variance], 1);
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 0.0), &Vec3::new(0.0, 0.0, -1.0));
+        let world = HittableList::new();
+        for (i, &v) in [0.0, 2.0, 0.0, 2.0].iter().enumerate() {
+            buffers.add_sample(0, i, &r, &world, Color::new(v, 0.5 * v, 0.0), None);
+        }
+
+        // The red samples' variance is 4/3, so their mean's is a quarter of
+        // that, scaled by the square of the exposure.
+        let layers = buffers.finish(1, 1, 4.0, 2.0);
+        assert_close(layers[0].1.get(0, 0), Color::new(4.0 / 3.0, 1.0 / 3.0, 0.0));
+    }
+
+    #[test]
+    fn material_ids_match_the_integrator() {
+        let layers = render(&[Aov::MaterialId], IntegratorKind::MaterialId);
+        let ids = layers.aov(Aov::MaterialId).unwrap();
+        for y in 0..2 {
+            for x in 0..4 {
+                let color = match ids.get(x, y).r() as usize {
+                    0 => Color::black(),
+                    id => material_color(id),
+                };
+                assert_close(layers.color.get(x, y), color);
+            }
+        }
+    }
+
+    #[test]
+    fn direct_and_indirect_split_the_image() {
+        // Light off the grey quad onto the wall is indirect.
+        let layers = render(&[Aov::Indirect, Aov::Direct], IntegratorKind::Path);
+        assert_eq!(layers.aovs[0].0, Aov::Indirect);
+        let mut all_indirect = Color::black();
+        for y in 0..2 {
+            for x in 0..4 {
+                let direct = layers.aov(Aov::Direct).unwrap().get(x, y);
+                let indirect = layers.aov(Aov::Indirect).unwrap().get(x, y);
+                assert_close(direct + indirect, layers.color.get(x, y));
+                all_indirect += indirect;
+            }
+        }
+        assert!(all_indirect.g() > 0.0);
+
+        // Direct lighting finds no indirect light, and integrators that do
+        // not tell the two apart leave both black.
+        let layers = render(
+            &[Aov::Direct, Aov::Indirect],
+            IntegratorKind::DirectLighting,
+        );
+        for x in 0..4 {
+            assert_close(
+                layers.aov(Aov::Direct).unwrap().get(x, 0),
+                layers.color.get(x, 0),
+            );
+            assert!(layers.aov(Aov::Indirect).unwrap().get(x, 0).is_black());
+        }
+        let layers = render(&[Aov::Direct, Aov::Indirect], IntegratorKind::Bidirectional);
+        for (_, image) in &layers.aovs {
+            assert!(image.pixels().iter().all(Color::is_black));
+        }
+        assert!(!layers.color.get(0, 0).is_black());
+    }
+
+    #[test]
+    fn exr_has_a_channel_per_aov_value() {
+        let layers = render(&[Aov::Normal, Aov::Depth], IntegratorKind::Path);
+        let mut buf = io::Cursor::new(Vec::new());
+        layers.write_exr(&mut buf).unwrap();
+
+        let data = buf.into_inner();
+        let contains = |name: &str| data.windows(name.len()).any(|w| w == name.as_bytes());
+        for name in &["R", "G", "B", "normal.X", "normal.Y", "normal.Z", "depth.Z"] {
+            assert!(contains(name), "{}", name);
+        }
+        assert!(!contains("depth.Y"));
+    }
+}
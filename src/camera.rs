@@ -1,58 +1,20 @@
-use crate::geometry::Ray;
-use crate::utils::vec3::Vec3;
-
-pub struct Camera {
-    origin: Vec3,
-    lower_left_corner: Vec3,
-    horizontal: Vec3,
-    vertical: Vec3,
-    u: Vec3,
-    v: Vec3,
-    lens_radius: f64,
-}
-
-impl Camera {
-    pub fn new(
-        lookfrom: Vec3,
-        lookat: Vec3,
-        vup: Vec3,
-        vfov: f64, // vertical field-of-view in degrees
-        aspect_ratio: f64,
-        aperture: f64,
-        focus_dist: f64,
-    ) -> Self {
-        let theta = vfov.to_radians();
-        let h = (theta / 2.0).tan();
-        let viewport_height = 2.0 * h;
-        let viewport_width = aspect_ratio * viewport_height;
-
-        let w = (lookfrom - lookat).unit();
-        let u = vup.cross(&w).unit();
-        let v = w.cross(&u);
-
-        let origin = lookfrom;
-        let horizontal = focus_dist * viewport_width * u;
-        let vertical = focus_dist * viewport_height * v;
-        let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0 - focus_dist * w;
-
-        Self {
-            origin,
-            lower_left_corner,
-            horizontal,
-            vertical,
-            u,
-            v,
-            lens_radius: aperture / 2.0,
-        }
-    }
-
-    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
-        let rd = self.lens_radius * Vec3::new_random_in_unit_disk();
-        let offset = self.u * rd.x() + self.v * rd.y();
-
-        let origin = self.origin + offset;
-        let direction = self.lower_left_corner + s * self.horizontal + t * self.vertical - origin;
-
-        Ray::new(&origin, &direction)
-    }
-}
+mod aperture;
+#[allow(clippy::module_inception)]
+mod camera;
+mod cylindrical;
+mod equirectangular;
+mod fisheye;
+mod orthographic;
+mod perspective;
+mod physical;
+mod view;
+
+pub use aperture::{Aperture, ApertureImage};
+pub use camera::{Camera, LensSample};
+pub use cylindrical::CylindricalCamera;
+pub use equirectangular::EquirectangularCamera;
+pub use fisheye::{FisheyeCamera, FisheyeProjection};
+pub use orthographic::OrthographicCamera;
+pub use perspective::PerspectiveCamera;
+pub use physical::{PhysicalCamera, PhysicalSettings};
+pub use view::View;
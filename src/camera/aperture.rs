@@ -0,0 +1,153 @@
+use crate::utils::consts::PI;
+use crate::utils::image::Image;
+use crate::utils::random::{sample_1d, sample_2d};
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// The shape of a lens opening, which is also the shape that out-of-focus
+/// highlights (bokeh) take on.
+#[derive(Default)]
+pub enum Aperture {
+    /// A perfectly round opening.
+    #[default]
+    Circle,
+    /// A regular polygon inscribed in the lens, as formed by `blades`
+    /// straight diaphragm blades, turned by `rotation` degrees.
+    Polygon { blades: u32, rotation: Float },
+    /// An arbitrary mask, e.g. a heart or star cut-out.
+    Image(ApertureImage),
+}
+
+impl Aperture {
+    /// A random point on the opening, in lens coordinates where the lens is
+    /// the unit disk. Draws from the `Lens` block of the current sample.
+    pub fn sample(&self) -> (Float, Float) {
+        match self {
+            Aperture::Circle => {
+                let p = Vec3::uniform_in_disk(sample_2d());
+                (p.x(), p.y())
+            }
+            Aperture::Polygon { blades, rotation } => sample_polygon(*blades, *rotation),
+            Aperture::Image(image) => image.sample(),
+        }
+    }
+}
+
+// Picks one of the polygon's triangles around the center, all of equal area,
+// and a uniform point inside it.
+fn sample_polygon(blades: u32, rotation: Float) -> (Float, Float) {
+    let blades = blades.max(3);
+    let step = 2.0 * PI / blades as Float;
+    let (mut s, mut t) = sample_2d();
+    let i = ((sample_1d() * blades as Float) as u32).min(blades - 1);
+
+    let a0 = rotation.to_radians() + i as Float * step;
+    let a1 = a0 + step;
+    if s + t > 1.0 {
+        s = 1.0 - s;
+        t = 1.0 - t;
+    }
+
+    (s * a0.cos() + t * a1.cos(), s * a0.sin() + t * a1.sin())
+}
+
+/// An aperture mask taken from an image, with brighter pixels letting
+/// through more light. The image spans the square around the lens, with its
+/// top row at the top of the lens.
+pub struct ApertureImage {
+    width: usize,
+    height: usize,
+    // Cumulative luminance of the pixels, row by row from the top.
+    cdf: Vec<Float>,
+}
+
+impl ApertureImage {
+    pub fn new(image: &Image) -> Self {
+        let mut total = 0.0;
+        let cdf: Vec<Float> = image
+            .pixels()
+            .iter()
+            .map(|c| {
+                total += c.luminance().max(0.0);
+                total
+            })
+            .collect();
+        assert!(total > 0.0, "an aperture image needs some bright pixels");
+
+        Self {
+            width: image.width(),
+            height: image.height(),
+            cdf,
+        }
+    }
+
+    fn sample(&self) -> (Float, Float) {
+        let (jx, jy) = sample_2d();
+        let total = *self.cdf.last().unwrap();
+        let target = sample_1d() * total;
+        let index = self
+            .cdf
+            .partition_point(|&c| c <= target)
+            .min(self.cdf.len() - 1);
+
+        // Jitter within the pixel, then fit the longer side to [-1, 1].
+        let x = (index % self.width) as Float + jx;
+        let y = (index / self.width) as Float + jy;
+        let scale = 2.0 / self.width.max(self.height) as Float;
+        (
+            (x - 0.5 * self.width as Float) * scale,
+            (0.5 * self.height as Float - y) * scale,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::color::Color;
+    use crate::utils::random;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn polygon_samples_stay_inside() {
+        random::seed(11);
+        let (blades, rotation) = (5, 18.0);
+        let aperture = Aperture::Polygon { blades, rotation };
+
+        // Inside means within the apothem along every edge's outward normal.
+        let step = 2.0 * PI / blades as Float;
+        let apothem = (step / 2.0).cos();
+        let mut max_radius: Float = 0.0;
+        for _ in 0..10_000 {
+            let (x, y) = aperture.sample();
+            for i in 0..blades {
+                let angle = rotation.to_radians() + (i as Float + 0.5) * step;
+                assert!(x * angle.cos() + y * angle.sin() <= apothem + EPSILON);
+            }
+            max_radius = max_radius.max(x.hypot(y));
+        }
+
+        // And the corners are reached.
+        assert!(max_radius > 0.95);
+    }
+
+    #[test]
+    fn image_samples_follow_mask() {
+        random::seed(12);
+        // Only the top-right quadrant of a 4x4 mask is open.
+        let mut image = Image::new(4, 4);
+        for y in 0..2 {
+            for x in 2..4 {
+                image.set(x, y, Color::white());
+            }
+        }
+        let aperture = Aperture::Image(ApertureImage::new(&image));
+
+        for _ in 0..10_000 {
+            let (x, y) = aperture.sample();
+            assert!((0.0..=1.0).contains(&x));
+            assert!((0.0..=1.0).contains(&y));
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::geometry::Ray;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+pub trait Camera {
+    /// The ray for image coordinates `s` and `t` in [0, 1], measured from the
+    /// lower left corner to the right and up.
+    fn get_ray(&self, s: Float, t: Float) -> Ray;
+
+    /// Factor the rendered radiance is scaled by to give pixel values.
+    fn exposure(&self) -> Float {
+        1.0
+    }
+
+    /// The density per unit solid angle with which `get_ray`, over the whole
+    /// image, produces the direction of `r` from its origin on the lens.
+    /// None for cameras that paths traced from the lights cannot reach.
+    fn direction_pdf(&self, _r: &Ray) -> Option<Float> {
+        None
+    }
+
+    /// Picks a point on the lens, with `u` uniform in the unit square, for
+    /// light leaving `p` towards the camera. None if the camera cannot be
+    /// reached this way or does not see `p` from that point.
+    fn connect(&self, _p: &Vec3, _u: (Float, Float)) -> Option<LensSample> {
+        None
+    }
+}
+
+/// A point on the lens that light from the scene is carried to.
+pub struct LensSample {
+    /// Where the light lands in the image, as `get_ray` takes it.
+    pub s: Float,
+    pub t: Float,
+    pub point: Vec3,
+    /// The response of the camera to the light, over the density per unit
+    /// solid angle with which `point` was picked as seen from the scene.
+    pub weight: Float,
+}
+
+/// The width over the height of an image of `(width, height)` pixels.
+pub(super) fn aspect_ratio((width, height): (usize, usize)) -> Float {
+    width as Float / height as Float
+}
+
+/// The orthonormal frame of a camera at `lookfrom` facing `lookat`: `u`
+/// points right, `v` up and `w` backwards, away from the view direction.
+pub(super) fn view_basis(lookfrom: &Vec3, lookat: &Vec3, vup: &Vec3) -> (Vec3, Vec3, Vec3) {
+    let w = (*lookfrom - *lookat).unit();
+    let u = vup.cross(&w).unit();
+    let v = w.cross(&u);
+
+    (u, v, w)
+}
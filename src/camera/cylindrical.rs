@@ -0,0 +1,79 @@
+use super::camera::{aspect_ratio, view_basis, Camera};
+use crate::geometry::Ray;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// A panorama projected onto a cylinder around the `vup` axis: angles are
+/// linear across the image, while verticals stay straight as in a
+/// perspective view. `hfov` may go up to 360°.
+pub struct CylindricalCamera {
+    origin: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    hfov: Float,
+    height: Float,
+}
+
+impl CylindricalCamera {
+    pub fn new(
+        lookfrom: Vec3,
+        lookat: Vec3,
+        vup: Vec3,
+        hfov: Float, // horizontal field-of-view in degrees
+        image_size: (usize, usize),
+    ) -> Self {
+        let (u, v, w) = view_basis(&lookfrom, &lookat, &vup);
+        let hfov = hfov.to_radians();
+
+        Self {
+            origin: lookfrom,
+            u,
+            v,
+            w,
+            hfov,
+            // On a unit cylinder the image is `hfov` wide.
+            height: hfov / aspect_ratio(image_size),
+        }
+    }
+}
+
+impl Camera for CylindricalCamera {
+    fn get_ray(&self, s: Float, t: Float) -> Ray {
+        let phi = (s - 0.5) * self.hfov;
+        let y = (t - 0.5) * self.height;
+
+        let direction = phi.sin() * self.u - phi.cos() * self.w + y * self.v;
+
+        Ray::new(&self.origin, &direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::consts::FRAC_PI_4;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn angles_are_linear_across_image() {
+        let camera = CylindricalCamera::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            180.0,
+            (200, 100),
+        );
+        let direction = |s, t| camera.get_ray(s, t).direction().unit();
+
+        assert!((direction(0.0, 0.5) - Vec3::new(-1.0, 0.0, 0.0)).length() < EPSILON);
+        assert!((direction(1.0, 0.5) - Vec3::new(1.0, 0.0, 0.0)).length() < EPSILON);
+        let diagonal = Vec3::new(1.0, 0.0, -1.0).unit();
+        assert!((direction(0.75, 0.5) - diagonal).length() < EPSILON);
+
+        // The top edge is pi / 4 above the horizon on the unit cylinder.
+        let d = *camera.get_ray(0.5, 1.0).direction();
+        assert!((d.y() / -d.z() - FRAC_PI_4).abs() < EPSILON);
+    }
+}
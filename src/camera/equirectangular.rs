@@ -0,0 +1,64 @@
+use super::camera::{view_basis, Camera};
+use crate::geometry::Ray;
+use crate::utils::consts::PI;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// A full 360° by 180° panorama in latitude-longitude layout, as used for
+/// environment maps and VR viewers. The view direction is at the center of
+/// the image and the image should be twice as wide as it is tall.
+pub struct EquirectangularCamera {
+    origin: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl EquirectangularCamera {
+    pub fn new(lookfrom: Vec3, lookat: Vec3, vup: Vec3) -> Self {
+        let (u, v, w) = view_basis(&lookfrom, &lookat, &vup);
+
+        Self {
+            origin: lookfrom,
+            u,
+            v,
+            w,
+        }
+    }
+}
+
+impl Camera for EquirectangularCamera {
+    fn get_ray(&self, s: Float, t: Float) -> Ray {
+        let longitude = (s - 0.5) * 2.0 * PI;
+        let latitude = (t - 0.5) * PI;
+
+        let direction = latitude.cos() * (longitude.sin() * self.u - longitude.cos() * self.w)
+            + latitude.sin() * self.v;
+
+        Ray::new(&self.origin, &direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn covers_full_sphere() {
+        let camera = EquirectangularCamera::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let direction = |s, t| camera.get_ray(s, t).direction().unit();
+
+        assert!((direction(0.5, 0.5) - Vec3::new(0.0, 0.0, -1.0)).length() < EPSILON);
+        assert!((direction(0.75, 0.5) - Vec3::new(1.0, 0.0, 0.0)).length() < EPSILON);
+        assert!((direction(0.25, 0.5) - Vec3::new(-1.0, 0.0, 0.0)).length() < EPSILON);
+        assert!((direction(0.0, 0.5) - Vec3::new(0.0, 0.0, 1.0)).length() < EPSILON);
+        assert!((direction(0.3, 1.0) - Vec3::new(0.0, 1.0, 0.0)).length() < EPSILON);
+        assert!((direction(0.3, 0.0) - Vec3::new(0.0, -1.0, 0.0)).length() < EPSILON);
+    }
+}
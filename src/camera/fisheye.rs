@@ -0,0 +1,127 @@
+use super::camera::{aspect_ratio, view_basis, Camera};
+use crate::geometry::Ray;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// How a fisheye lens maps the angle `theta` from the view axis to the
+/// distance `r` from the image center, for focal length `f`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FisheyeProjection {
+    /// `r = f * theta`, keeping angles evenly spaced.
+    Equidistant,
+    /// `r = 2 f * sin(theta / 2)`, keeping solid angles (areas) in proportion.
+    Equisolid,
+}
+
+/// A full-frame fisheye: `fov` is the angle across the image diagonal, so
+/// the whole frame is filled. Fields of view up to 360° are allowed.
+pub struct FisheyeCamera {
+    origin: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    projection: FisheyeProjection,
+    aspect_ratio: Float,
+    focal_length: Float,
+}
+
+impl FisheyeCamera {
+    pub fn new(
+        lookfrom: Vec3,
+        lookat: Vec3,
+        vup: Vec3,
+        fov: Float, // diagonal field-of-view in degrees
+        image_size: (usize, usize),
+        projection: FisheyeProjection,
+    ) -> Self {
+        let (u, v, w) = view_basis(&lookfrom, &lookat, &vup);
+        let aspect_ratio = aspect_ratio(image_size);
+
+        // The image is 2 units tall, so its corners are this far out.
+        let half_diagonal = aspect_ratio.hypot(1.0);
+        let half_fov = fov.to_radians() / 2.0;
+        let focal_length = match projection {
+            FisheyeProjection::Equidistant => half_diagonal / half_fov,
+            FisheyeProjection::Equisolid => half_diagonal / (2.0 * (half_fov / 2.0).sin()),
+        };
+
+        Self {
+            origin: lookfrom,
+            u,
+            v,
+            w,
+            projection,
+            aspect_ratio,
+            focal_length,
+        }
+    }
+}
+
+impl Camera for FisheyeCamera {
+    fn get_ray(&self, s: Float, t: Float) -> Ray {
+        let x = (2.0 * s - 1.0) * self.aspect_ratio;
+        let y = 2.0 * t - 1.0;
+        let r = x.hypot(y);
+
+        let theta = match self.projection {
+            FisheyeProjection::Equidistant => r / self.focal_length,
+            FisheyeProjection::Equisolid => 2.0 * (r / (2.0 * self.focal_length)).min(1.0).asin(),
+        };
+
+        let direction = if r > 0.0 {
+            let radial = (x * self.u + y * self.v) / r;
+            theta.sin() * radial - theta.cos() * self.w
+        } else {
+            -self.w
+        };
+
+        Ray::new(&self.origin, &direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: Float = 0.00001;
+
+    fn angle_from_axis(camera: &FisheyeCamera, s: Float, t: Float) -> Float {
+        let d = camera.get_ray(s, t).direction().unit();
+        (-d.z()).acos().to_degrees()
+    }
+
+    #[test]
+    fn corners_reach_half_fov() {
+        for &projection in &[FisheyeProjection::Equidistant, FisheyeProjection::Equisolid] {
+            let camera = FisheyeCamera::new(
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, -1.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                180.0,
+                (300, 200),
+                projection,
+            );
+
+            assert!(angle_from_axis(&camera, 0.5, 0.5).abs() < EPSILON);
+            assert!((angle_from_axis(&camera, 1.0, 1.0) - 90.0).abs() < 1e-3);
+            assert!((angle_from_axis(&camera, 0.0, 0.0) - 90.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn equidistant_is_linear_in_radius() {
+        let camera = FisheyeCamera::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            180.0,
+            (100, 100),
+            FisheyeProjection::Equidistant,
+        );
+
+        // Halfway to the corner along the diagonal is half the angle.
+        assert!((angle_from_axis(&camera, 0.75, 0.75) - 45.0).abs() < 1e-3);
+        let d = camera.get_ray(0.75, 0.75).direction().unit();
+        assert!((d.x() - d.y()).abs() < EPSILON && d.x() > 0.0);
+    }
+}
@@ -0,0 +1,67 @@
+use super::camera::{aspect_ratio, view_basis, Camera};
+use crate::geometry::Ray;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// A camera with parallel rays, seeing a `viewport_height` tall window
+/// centered on the view axis without perspective foreshortening.
+pub struct OrthographicCamera {
+    lower_left_corner: Vec3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    direction: Vec3,
+}
+
+impl OrthographicCamera {
+    pub fn new(
+        lookfrom: Vec3,
+        lookat: Vec3,
+        vup: Vec3,
+        viewport_height: Float,
+        image_size: (usize, usize),
+    ) -> Self {
+        let (u, v, w) = view_basis(&lookfrom, &lookat, &vup);
+        let horizontal = aspect_ratio(image_size) * viewport_height * u;
+        let vertical = viewport_height * v;
+
+        Self {
+            lower_left_corner: lookfrom - horizontal / 2.0 - vertical / 2.0,
+            horizontal,
+            vertical,
+            direction: -w,
+        }
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn get_ray(&self, s: Float, t: Float) -> Ray {
+        let origin = self.lower_left_corner + s * self.horizontal + t * self.vertical;
+        Ray::new(&origin, &self.direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn rays_are_parallel() {
+        let camera = OrthographicCamera::new(
+            Vec3::new(0.0, 0.0, 5.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            2.0,
+            (200, 100),
+        );
+
+        let r = camera.get_ray(0.0, 0.0);
+        assert!((*r.origin() - Vec3::new(-2.0, -1.0, 5.0)).length() < EPSILON);
+        assert!((*r.direction() - Vec3::new(0.0, 0.0, -1.0)).length() < EPSILON);
+
+        let r = camera.get_ray(1.0, 1.0);
+        assert!((*r.origin() - Vec3::new(2.0, 1.0, 5.0)).length() < EPSILON);
+        assert!((*r.direction() - Vec3::new(0.0, 0.0, -1.0)).length() < EPSILON);
+    }
+}
@@ -0,0 +1,202 @@
+use super::aperture::Aperture;
+use super::camera::{aspect_ratio, view_basis, Camera, LensSample};
+use crate::geometry::Ray;
+use crate::utils::consts::PI;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// A pinhole or thin-lens camera. With a non-zero `aperture`, points away
+/// from the plane at `focus_dist` are blurred into the shape of the lens
+/// opening, round unless set otherwise with `with_aperture`.
+pub struct PerspectiveCamera {
+    origin: Vec3,
+    lower_left_corner: Vec3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    focus_dist: Float,
+    lens_radius: Float,
+    aperture: Aperture,
+}
+
+impl PerspectiveCamera {
+    pub fn new(
+        lookfrom: Vec3,
+        lookat: Vec3,
+        vup: Vec3,
+        vfov: Float, // vertical field-of-view in degrees
+        image_size: (usize, usize),
+        aperture: Float,
+        focus_dist: Float,
+    ) -> Self {
+        let theta = vfov.to_radians();
+        let h = (theta / 2.0).tan();
+        let viewport_height = 2.0 * h;
+        let viewport_width = aspect_ratio(image_size) * viewport_height;
+
+        let (u, v, w) = view_basis(&lookfrom, &lookat, &vup);
+
+        let origin = lookfrom;
+        let horizontal = focus_dist * viewport_width * u;
+        let vertical = focus_dist * viewport_height * v;
+        let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0 - focus_dist * w;
+
+        Self {
+            origin,
+            lower_left_corner,
+            horizontal,
+            vertical,
+            u,
+            v,
+            w,
+            focus_dist,
+            lens_radius: aperture / 2.0,
+            aperture: Aperture::Circle,
+        }
+    }
+
+    pub fn with_aperture(mut self, aperture: Aperture) -> Self {
+        self.aperture = aperture;
+        self
+    }
+
+    // The area of the lens, counted as 1 for a pinhole. Only round lenses
+    // are sampled uniformly enough to be connected to.
+    fn lens_area(&self) -> Option<Float> {
+        if self.lens_radius == 0.0 {
+            Some(1.0)
+        } else if let Aperture::Circle = self.aperture {
+            Some(PI * self.lens_radius * self.lens_radius)
+        } else {
+            None
+        }
+    }
+
+    // The area of the image at unit distance from the lens.
+    fn image_area(&self) -> Float {
+        self.horizontal.length() * self.vertical.length() / (self.focus_dist * self.focus_dist)
+    }
+
+    // Where the ray from `origin` on the lens along `direction` lands in
+    // the image, as s and t, with the cosine of its angle to the view
+    // direction. Rays through the same point of the plane in focus land in
+    // the same place.
+    fn project(&self, origin: &Vec3, direction: &Vec3) -> Option<(Float, Float, Float)> {
+        let d = direction.unit();
+        let cosine = -d.dot(&self.w);
+        if cosine <= 0.0 {
+            return None;
+        }
+
+        let offset = *origin + (self.focus_dist / cosine) * d - self.lower_left_corner;
+        let s = offset.dot(&self.horizontal) / self.horizontal.length_squared();
+        let t = offset.dot(&self.vertical) / self.vertical.length_squared();
+        if (0.0..1.0).contains(&s) && (0.0..1.0).contains(&t) {
+            Some((s, t, cosine))
+        } else {
+            None
+        }
+    }
+}
+
+impl Camera for PerspectiveCamera {
+    fn get_ray(&self, s: Float, t: Float) -> Ray {
+        let (x, y) = self.aperture.sample();
+        let offset = self.u * (self.lens_radius * x) + self.v * (self.lens_radius * y);
+
+        let origin = self.origin + offset;
+        let direction = self.lower_left_corner + s * self.horizontal + t * self.vertical - origin;
+
+        Ray::new(&origin, &direction)
+    }
+
+    fn direction_pdf(&self, r: &Ray) -> Option<Float> {
+        self.lens_area()?;
+        // Uniform over the image, which at unit distance lies 1 / cos^2 as
+        // far from the lens and turned by the angle once more.
+        Some(match self.project(r.origin(), r.direction()) {
+            Some((_, _, cosine)) => 1.0 / (self.image_area() * cosine.powi(3)),
+            None => 0.0,
+        })
+    }
+
+    fn connect(&self, p: &Vec3, u: (Float, Float)) -> Option<LensSample> {
+        self.lens_area()?;
+        let disk = Vec3::uniform_in_disk(u);
+        let point = self.origin + self.lens_radius * (disk.x() * self.u + disk.y() * self.v);
+
+        let to_p = *p - point;
+        let (s, t, cosine) = self.project(&point, &to_p)?;
+
+        // The importance is 1 / (image area * lens area * cos^4), so that it
+        // integrates to one over the image and the lens; the point was
+        // picked with density distance^2 / (cos * lens area). The lens area
+        // cancels.
+        Some(LensSample {
+            s,
+            t,
+            point,
+            weight: 1.0 / (self.image_area() * cosine.powi(3) * to_p.length_squared()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn connect_finds_image_coordinates() {
+        for &aperture in &[0.0, 0.5] {
+            let camera = PerspectiveCamera::new(
+                Vec3::new(1.0, 2.0, 3.0),
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                40.0,
+                (300, 200),
+                aperture,
+                3.0,
+            );
+            // A point in focus, seen from anywhere on the lens, lands
+            // where `get_ray` aims.
+            let r = camera.get_ray(0.2, 0.7);
+            let p = r.at(1.0);
+            for &u in &[(0.1, 0.9), (0.5, 0.5), (0.8, 0.3)] {
+                let sample = camera.connect(&p, u).unwrap();
+                assert!((sample.s - 0.2).abs() < EPSILON);
+                assert!((sample.t - 0.7).abs() < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn direction_pdf_integrates_to_one() {
+        let camera = PerspectiveCamera::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            60.0,
+            (200, 100),
+            0.0,
+            1.0,
+        );
+        let n = 400;
+        let mut total = 0.0;
+        for i in 0..n {
+            for j in 0..n {
+                let u = (
+                    (i as Float + 0.5) / n as Float,
+                    (j as Float + 0.5) / n as Float,
+                );
+                let d = Vec3::uniform_on_sphere(u);
+                let r = Ray::new(&Vec3::new(0.0, 0.0, 0.0), &d);
+                total += camera.direction_pdf(&r).unwrap() * 4.0 * PI;
+            }
+        }
+        assert!((total / (n * n) as Float - 1.0).abs() < 0.01);
+    }
+}
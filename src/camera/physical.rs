@@ -0,0 +1,219 @@
+use super::aperture::Aperture;
+use super::camera::{Camera, LensSample};
+use super::perspective::PerspectiveCamera;
+use crate::geometry::Ray;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+// The luminance in cd/m² that a scene radiance of 1 stands for: a bright
+// overcast day, which the default settings are exposed for.
+const UNIT_LUMINANCE: Float = 7680.0;
+
+/// Camera and lens settings in photographic units. Scene units are taken to
+/// be metres.
+pub struct PhysicalSettings {
+    /// Focal length of the lens in millimetres.
+    pub focal_length: Float,
+    /// Sensor size in millimetres. The height sets the field of view, and
+    /// the image shows the whole width if it has the sensor's shape.
+    pub sensor_width: Float,
+    pub sensor_height: Float,
+    /// The f-stop: focal length divided by the diameter of the opening.
+    pub f_number: Float,
+    /// Distance to the plane in focus, in scene units.
+    pub focus_distance: Float,
+    pub iso: Float,
+    /// Shutter time in seconds.
+    pub shutter: Float,
+    pub aperture: Aperture,
+}
+
+impl Default for PhysicalSettings {
+    /// A 50mm lens on a full-frame sensor at f/8, ISO 100 and 1/100 s.
+    fn default() -> Self {
+        Self {
+            focal_length: 50.0,
+            sensor_width: 36.0,
+            sensor_height: 24.0,
+            f_number: 8.0,
+            focus_distance: 10.0,
+            iso: 100.0,
+            shutter: 0.01,
+            aperture: Aperture::Circle,
+        }
+    }
+}
+
+impl PhysicalSettings {
+    /// The shape of the sensor, for choosing the image height with
+    /// `RenderSettings::height_for`.
+    pub fn aspect_ratio(&self) -> Float {
+        self.sensor_width / self.sensor_height
+    }
+
+    /// Vertical field of view in degrees.
+    pub fn vfov(&self) -> Float {
+        2.0 * (self.sensor_height / (2.0 * self.focal_length))
+            .atan()
+            .to_degrees()
+    }
+
+    /// Diameter of the lens opening in scene units.
+    pub fn aperture_diameter(&self) -> Float {
+        self.focal_length / self.f_number / 1000.0
+    }
+
+    /// The exposure value at ISO 100 that these settings amount to.
+    pub fn ev100(&self) -> Float {
+        (self.f_number * self.f_number / self.shutter * 100.0 / self.iso).log2()
+    }
+
+    /// Brightness scale applied to scene radiance. A sensor saturates at a
+    /// luminance of `1.2 * 2^EV100` cd/m², which maps to 1 in the image, and
+    /// a scene radiance of 1 is taken to be a bright overcast sky, so the
+    /// default settings give an exposure of 1.
+    pub fn exposure(&self) -> Float {
+        UNIT_LUMINANCE / (1.2 * self.ev100().exp2())
+    }
+}
+
+/// A thin-lens camera set up from `PhysicalSettings` for an image of
+/// `image_size` pixels, which also scales the image brightness by the
+/// exposure.
+pub struct PhysicalCamera {
+    lens: PerspectiveCamera,
+    exposure: Float,
+}
+
+impl PhysicalCamera {
+    pub fn new(
+        lookfrom: Vec3,
+        lookat: Vec3,
+        vup: Vec3,
+        settings: PhysicalSettings,
+        image_size: (usize, usize),
+    ) -> Self {
+        let exposure = settings.exposure();
+        let lens = PerspectiveCamera::new(
+            lookfrom,
+            lookat,
+            vup,
+            settings.vfov(),
+            image_size,
+            settings.aperture_diameter(),
+            settings.focus_distance,
+        )
+        .with_aperture(settings.aperture);
+
+        Self { lens, exposure }
+    }
+}
+
+impl Camera for PhysicalCamera {
+    fn get_ray(&self, s: Float, t: Float) -> Ray {
+        self.lens.get_ray(s, t)
+    }
+
+    fn exposure(&self) -> Float {
+        self.exposure
+    }
+
+    fn direction_pdf(&self, r: &Ray) -> Option<Float> {
+        self.lens.direction_pdf(r)
+    }
+
+    fn connect(&self, p: &Vec3, u: (Float, Float)) -> Option<LensSample> {
+        self.lens.connect(p, u)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn lens_geometry() {
+        let settings = PhysicalSettings {
+            focal_length: 12.0,
+            ..Default::default()
+        };
+
+        // A 12mm lens sees 90 degrees across a 24mm tall sensor.
+        assert!((settings.vfov() - 90.0).abs() < 1e-3);
+        assert!((settings.aspect_ratio() - 1.5).abs() < EPSILON);
+        assert!((settings.aperture_diameter() - 0.0015).abs() < EPSILON);
+
+        let camera = PhysicalCamera::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            settings,
+            (36, 24),
+        );
+        let d = camera.get_ray(0.5, 1.0).direction().unit();
+        assert!((d.y() + d.z()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn exposure_follows_reciprocity() {
+        let base = PhysicalSettings::default();
+        // One stop slower shutter, one stop smaller opening: same exposure.
+        let same = PhysicalSettings {
+            shutter: 2.0 * base.shutter,
+            f_number: base.f_number * (2.0 as Float).sqrt(),
+            ..Default::default()
+        };
+        let brighter = PhysicalSettings {
+            iso: 400.0,
+            ..Default::default()
+        };
+
+        assert!((same.exposure() / base.exposure() - 1.0).abs() < EPSILON);
+        assert!((brighter.exposure() / base.exposure() - 4.0).abs() < EPSILON);
+        assert!((base.ev100() - (6400.0 as Float).log2()).abs() < EPSILON);
+        assert!((base.exposure() - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn default_render_is_not_black() {
+        use crate::geometry::{HittableList, Sphere};
+        use crate::integrator::IntegratorKind;
+        use crate::material::{Lambertian, Material};
+        use crate::render::{render, Background, RenderSettings};
+        use crate::sampler::SamplerKind;
+        use crate::utils::color::Color;
+        use std::rc::Rc;
+
+        // A grey ball under a white sky, filling about a third of the frame.
+        let grey: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let mut world = HittableList::new();
+        world.add(Box::new(Sphere::new(Vec3::new(0.0, 0.0, 0.0), 1.0, &grey)));
+        let settings = RenderSettings {
+            image_width: 12,
+            image_height: 8,
+            samples_per_pixel: 16,
+            max_depth: 8,
+            roulette_depth: 8,
+            background: Background::Solid(Color::white()),
+            crop: None,
+            sampler: SamplerKind::Independent,
+            integrator: IntegratorKind::Path,
+            lights: Vec::new(),
+        };
+        let camera = PhysicalCamera::new(
+            Vec3::new(0.0, 0.0, 10.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            PhysicalSettings::default(),
+            settings.image_size(),
+        );
+
+        let image = render(&world, &camera, &settings);
+        let pixels = image.pixels();
+        let mean = pixels.iter().map(|c| c.luminance()).sum::<Float>() / pixels.len() as Float;
+        assert!(mean > 0.5 && mean < 1.0, "{}", mean);
+    }
+}
@@ -0,0 +1,269 @@
+use super::camera::{aspect_ratio, view_basis};
+use super::perspective::PerspectiveCamera;
+use crate::geometry::{Aabb, Hittable, Ray};
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// Where a perspective camera stands and what it sees, with helpers to
+/// move it around and to focus it. Build the camera itself with
+/// `perspective`.
+#[derive(Copy, Clone, Debug)]
+pub struct View {
+    pub lookfrom: Vec3,
+    pub lookat: Vec3,
+    pub vup: Vec3,
+    pub vfov: Float, // vertical field-of-view in degrees
+    /// The width and height of the image in pixels.
+    pub image_size: (usize, usize),
+}
+
+impl View {
+    pub fn new(
+        lookfrom: Vec3,
+        lookat: Vec3,
+        vup: Vec3,
+        vfov: Float,
+        image_size: (usize, usize),
+    ) -> Self {
+        Self {
+            lookfrom,
+            lookat,
+            vup,
+            vfov,
+            image_size,
+        }
+    }
+
+    pub fn perspective(&self, aperture: Float, focus_dist: Float) -> PerspectiveCamera {
+        PerspectiveCamera::new(
+            self.lookfrom,
+            self.lookat,
+            self.vup,
+            self.vfov,
+            self.image_size,
+            aperture,
+            focus_dist,
+        )
+    }
+
+    // Half the width and height of the image plane at distance 1.
+    fn half_extent(&self) -> (Float, Float) {
+        let h = (self.vfov.to_radians() / 2.0).tan();
+        (aspect_ratio(self.image_size) * h, h)
+    }
+
+    /// The ray from the center of the lens through image coordinates `s`
+    /// and `t`, as for `Camera::get_ray` with no lens blur.
+    pub fn ray_through(&self, s: Float, t: Float) -> Ray {
+        let (u, v, w) = view_basis(&self.lookfrom, &self.lookat, &self.vup);
+        let (half_width, half_height) = self.half_extent();
+        let direction = (2.0 * s - 1.0) * half_width * u + (2.0 * t - 1.0) * half_height * v - w;
+
+        Ray::new(&self.lookfrom, &direction)
+    }
+
+    /// The image coordinates `(s, t)` that `p` projects to, or `None` if it
+    /// is not in front of the camera. Points outside the frame give values
+    /// outside [0, 1].
+    pub fn project(&self, p: &Vec3) -> Option<(Float, Float)> {
+        let (u, v, w) = view_basis(&self.lookfrom, &self.lookat, &self.vup);
+        let d = *p - self.lookfrom;
+        let depth = -d.dot(&w);
+        if depth <= 0.0 {
+            return None;
+        }
+
+        let (half_width, half_height) = self.half_extent();
+        Some((
+            0.5 * (d.dot(&u) / (depth * half_width) + 1.0),
+            0.5 * (d.dot(&v) / (depth * half_height) + 1.0),
+        ))
+    }
+
+    /// Like `project`, in the pixel coordinates of the image as produced by
+    /// `render`: `x` to the right and `y` down from the top row, with whole
+    /// numbers at pixel centers.
+    pub fn world_to_pixel(&self, p: &Vec3) -> Option<(Float, Float)> {
+        let (s, t) = self.project(p)?;
+        let (width, height) = self.image_size;
+        Some((s * width as Float - 0.5, (1.0 - t) * height as Float - 0.5))
+    }
+
+    /// The focus distance that puts `p` in sharp focus.
+    pub fn focus_on(&self, p: &Vec3) -> Float {
+        let (_, _, w) = view_basis(&self.lookfrom, &self.lookat, &self.vup);
+        -(*p - self.lookfrom).dot(&w)
+    }
+
+    /// Focuses on whatever is seen at image coordinates `s` and `t`, or
+    /// returns `None` if nothing is there.
+    pub fn autofocus<T: Hittable + ?Sized>(&self, world: &T, s: Float, t: Float) -> Option<Float> {
+        let rec = world.hit(&self.ray_through(s, t), 0.001, Float::INFINITY)?;
+        Some(self.focus_on(&rec.p))
+    }
+
+    /// Turns the camera around `lookat` by `yaw` degrees about `vup` and
+    /// then `pitch` degrees upwards, keeping its distance. The pitch stops
+    /// just short of looking straight up or down.
+    pub fn orbit(&self, yaw: Float, pitch: Float) -> Self {
+        let up = self.vup.unit();
+        let offset = (self.lookfrom - self.lookat).rotate(&up, yaw.to_radians());
+
+        // Limit the elevation to keep `vup` usable as a reference.
+        let limit = (89.0 as Float).to_radians();
+        let elevation = (offset.unit().dot(&up)).clamp(-1.0, 1.0).asin();
+        let target = (elevation + pitch.to_radians()).clamp(-limit, limit);
+        let right = up.cross(&offset).unit();
+        let offset = offset.rotate(&right, elevation - target);
+
+        Self {
+            lookfrom: self.lookat + offset,
+            ..*self
+        }
+    }
+
+    /// Moves the camera `distance` towards `lookat`, or away for negative
+    /// values, without passing it.
+    pub fn dolly(&self, distance: Float) -> Self {
+        let offset = self.lookfrom - self.lookat;
+        let length = offset.length();
+        let new_length = (length - distance).max(1e-3 * length);
+
+        Self {
+            lookfrom: self.lookat + new_length / length * offset,
+            ..*self
+        }
+    }
+
+    /// Looks at the center of `bounds` from the current direction, just far
+    /// enough away for all of it to be in frame.
+    pub fn frame(&self, bounds: &Aabb) -> Self {
+        let center = bounds.centroid();
+        let radius = 0.5 * (*bounds.max() - *bounds.min()).length();
+
+        let (half_width, half_height) = self.half_extent();
+        let half_angle = half_width.min(half_height).atan();
+        let distance = radius / half_angle.sin();
+
+        let direction = (self.lookfrom - self.lookat).unit();
+        Self {
+            lookfrom: center + distance * direction,
+            lookat: center,
+            ..*self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use crate::geometry::Sphere;
+    use crate::material::{Lambertian, Material};
+    use crate::utils::color::Color;
+    use std::rc::Rc;
+
+    const EPSILON: Float = 0.00001;
+
+    fn view() -> View {
+        View::new(
+            Vec3::new(3.0, 2.0, 5.0),
+            Vec3::new(0.0, 0.5, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            (300, 200),
+        )
+    }
+
+    #[test]
+    fn project_inverts_ray_through() {
+        let view = view();
+        for &(s, t) in &[(0.5, 0.5), (0.1, 0.9), (0.8, 0.3)] {
+            let p = view.ray_through(s, t).at(7.0);
+            let (ps, pt) = view.project(&p).unwrap();
+            assert!((ps - s).abs() < EPSILON);
+            assert!((pt - t).abs() < EPSILON);
+        }
+
+        assert!(view.project(&Vec3::new(6.0, 3.5, 10.0)).is_none());
+
+        let odd = View {
+            image_size: (301, 201),
+            ..view
+        };
+        let (x, y) = odd.world_to_pixel(&odd.lookat).unwrap();
+        assert!((x - 150.0).abs() < EPSILON);
+        assert!((y - 100.0).abs() < EPSILON);
+
+        // With an even size the center falls between two pixels.
+        let (x, y) = view.world_to_pixel(&view.lookat).unwrap();
+        assert!((x - 149.5).abs() < EPSILON);
+        assert!((y - 99.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn ray_through_matches_pinhole_camera() {
+        let view = view();
+        let camera = view.perspective(0.0, 1.0);
+        let d = camera.get_ray(0.2, 0.7).direction().unit();
+        let expected = view.ray_through(0.2, 0.7).direction().unit();
+        assert!((d - expected).length() < EPSILON);
+    }
+
+    #[test]
+    fn autofocus_on_object() {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        let sphere = Sphere::new(Vec3::new(0.0, 0.0, -5.0), 1.0, &mat);
+        let view = View::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            (100, 100),
+        );
+
+        assert!((view.autofocus(&sphere, 0.5, 0.5).unwrap() - 4.0).abs() < EPSILON);
+        assert!(view.autofocus(&sphere, 0.0, 0.0).is_none());
+        assert!((view.focus_on(&Vec3::new(3.0, 1.0, -6.0)) - 6.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn orbit_and_dolly_keep_target() {
+        let view = view();
+        let distance = (view.lookfrom - view.lookat).length();
+
+        let orbited = view.orbit(90.0, 10.0);
+        assert!(((orbited.lookfrom - orbited.lookat).length() - distance).abs() < EPSILON);
+        assert!(orbited.lookfrom.y() > view.lookfrom.y());
+
+        // Yaw alone rotates about the vertical through the target.
+        let turned = view.orbit(180.0, 0.0);
+        assert!((turned.lookfrom - Vec3::new(-3.0, 2.0, -5.0)).length() < EPSILON);
+
+        // Pitching far past the pole stops short of it.
+        let top = view.orbit(0.0, 200.0);
+        assert!((top.lookfrom - top.lookat).unit().y() < 1.0 - EPSILON);
+
+        let closer = view.dolly(1.0);
+        assert!(((closer.lookfrom - closer.lookat).length() - (distance - 1.0)).abs() < EPSILON);
+        assert!((closer.lookfrom - closer.lookat).dot(&(view.lookfrom - view.lookat)) > 0.0);
+        let past = view.dolly(2.0 * distance);
+        assert!((past.lookfrom - past.lookat).dot(&(view.lookfrom - view.lookat)) > 0.0);
+    }
+
+    #[test]
+    fn frame_fits_bounds() {
+        let bounds = Aabb::new(Vec3::new(-2.0, 0.0, -1.0), Vec3::new(4.0, 3.0, 1.0));
+        let framed = view().frame(&bounds);
+
+        for &x in &[bounds.min().x(), bounds.max().x()] {
+            for &y in &[bounds.min().y(), bounds.max().y()] {
+                for &z in &[bounds.min().z(), bounds.max().z()] {
+                    let (s, t) = framed.project(&Vec3::new(x, y, z)).unwrap();
+                    assert!((0.0..=1.0).contains(&s));
+                    assert!((0.0..=1.0).contains(&t));
+                }
+            }
+        }
+    }
+}
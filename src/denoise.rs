@@ -0,0 +1,388 @@
+use crate::utils::color::Color;
+use crate::utils::image::Image;
+use crate::utils::Float;
+
+// The weights of the taps on each side of the center, a B3 spline.
+const KERNEL: [Float; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+// Below this, the albedo is treated as missing rather than divided by.
+const MIN_ALBEDO: Float = 0.01;
+
+/// How `denoise` blends pixels.
+#[derive(Copy, Clone, Debug)]
+pub struct DenoiseSettings {
+    /// The number of filter passes. Each doubles the spacing of the taps, so
+    /// the filter reaches 2^(iterations + 1) - 2 pixels each way.
+    pub iterations: usize,
+    /// How many standard deviations of the noise two pixels' brightness may
+    /// differ by and still be blended.
+    pub color_sigma: Float,
+    /// How far apart two pixels' normals may be and still be blended.
+    pub normal_sigma: Float,
+    /// How different two pixels' albedo may be and still be blended.
+    pub albedo_sigma: Float,
+}
+
+impl Default for DenoiseSettings {
+    fn default() -> Self {
+        Self {
+            iterations: 5,
+            color_sigma: 4.0,
+            normal_sigma: 0.3,
+            albedo_sigma: 0.1,
+        }
+    }
+}
+
+/// Removes noise from `color` with an edge-avoiding à-trous wavelet filter
+/// (Dammertz et al., with the variance guidance of SVGF), guided by the
+/// `Aov::Albedo`, `Aov::Normal` and `Aov::Variance` images of the same
+/// render. Pixels are blended with their neighbours only where the features
+/// agree, so edges between objects and in textures stay sharp, and only as
+/// far as their difference in brightness is explained by the noise their
+/// samples showed, so shadow edges on a single surface stay sharp too.
+///
+/// The filter works on the light arriving at surfaces: the color divided by
+/// the albedo, which is multiplied back afterwards so that textures are not
+/// blurred. Panics if the images differ in size.
+pub fn denoise(
+    color: &Image,
+    albedo: &Image,
+    normal: &Image,
+    variance: &Image,
+    settings: &DenoiseSettings,
+) -> Image {
+    let (width, height) = (color.width(), color.height());
+    for image in &[albedo, normal, variance] {
+        assert!(
+            image.width() == width && image.height() == height,
+            "feature buffer is {}x{}, the image {}x{}",
+            image.width(),
+            image.height(),
+            width,
+            height
+        );
+    }
+
+    let factors: Vec<Color> = albedo.pixels().iter().map(|&a| albedo_factor(a)).collect();
+    let mut light: Vec<Color> = color
+        .pixels()
+        .iter()
+        .zip(factors.iter())
+        .map(|(&c, &a)| Color::new(c.r() / a.r(), c.g() / a.g(), c.b() / a.b()))
+        .collect();
+    let mut variance = light_variance(variance, &factors, width, height);
+
+    for iteration in 0..settings.iterations {
+        let step = 1 << iteration;
+        let mut next_light = vec![Color::black(); light.len()];
+        let mut next_variance = vec![0.0; light.len()];
+
+        for y in 0..height {
+            for x in 0..width {
+                let p = y * width + x;
+                let luminance = light[p].luminance();
+                // The expected difference in brightness from noise alone.
+                let spread = settings.color_sigma * variance[p].max(0.0).sqrt() + 1e-6;
+
+                let mut sum = Color::black();
+                let mut sum_variance = 0.0;
+                let mut weights = 0.0;
+                for (j, ky) in KERNEL.iter().enumerate() {
+                    let qy = y as isize + (j as isize - 2) * step;
+                    if qy < 0 || qy >= height as isize {
+                        continue;
+                    }
+                    for (i, kx) in KERNEL.iter().enumerate() {
+                        let qx = x as isize + (i as isize - 2) * step;
+                        if qx < 0 || qx >= width as isize {
+                            continue;
+                        }
+                        let q = qy as usize * width + qx as usize;
+
+                        let color_term = (luminance - light[q].luminance()).abs() / spread;
+                        let normal_term = distance_squared(normal.pixels()[p], normal.pixels()[q])
+                            / (settings.normal_sigma * settings.normal_sigma);
+                        let albedo_term = distance_squared(albedo.pixels()[p], albedo.pixels()[q])
+                            / (settings.albedo_sigma * settings.albedo_sigma);
+                        let weight = kx * ky * (-color_term - normal_term - albedo_term).exp();
+
+                        sum += weight * light[q];
+                        sum_variance += weight * weight * variance[q];
+                        weights += weight;
+                    }
+                }
+
+                // The center always counts, so the weights are never zero.
+                next_light[p] = sum / weights;
+                next_variance[p] = sum_variance / (weights * weights);
+            }
+        }
+
+        light = next_light;
+        variance = next_variance;
+    }
+
+    let mut image = Image::new(width, height);
+    for (i, (&l, &a)) in light.iter().zip(factors.iter()).enumerate() {
+        image.set(i % width, i / width, l * a);
+    }
+    image
+}
+
+// What a pixel's color is divided by before filtering: its albedo, or one
+// in channels where there is too little of it to divide by, such as on
+// lights and the background.
+fn albedo_factor(albedo: Color) -> Color {
+    albedo.map(|a| if a < MIN_ALBEDO { 1.0 } else { a })
+}
+
+// The variance of the brightness of the light at each pixel, from that of
+// its color, treating the noise of the channels as correlated, as it mostly
+// is. As in SVGF, it is blurred over the 3 by 3 pixels around each pixel,
+// since a few samples estimate it poorly.
+fn light_variance(variance: &Image, factors: &[Color], width: usize, height: usize) -> Vec<Float> {
+    let per_pixel: Vec<Float> = variance
+        .pixels()
+        .iter()
+        .zip(factors.iter())
+        .map(|(&v, &a)| {
+            let spread = v.map(|v| v.max(0.0).sqrt());
+            let spread = Color::new(spread.r() / a.r(), spread.g() / a.g(), spread.b() / a.b());
+            spread.luminance() * spread.luminance()
+        })
+        .collect();
+
+    let weights = [0.25, 0.5, 0.25];
+    let mut blurred = vec![0.0; per_pixel.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let (mut sum, mut total) = (0.0, 0.0);
+            for (j, wy) in weights.iter().enumerate() {
+                for (i, wx) in weights.iter().enumerate() {
+                    let (qx, qy) = (x as isize + i as isize - 1, y as isize + j as isize - 1);
+                    if qx < 0 || qy < 0 || qx >= width as isize || qy >= height as isize {
+                        continue;
+                    }
+                    sum += wx * wy * per_pixel[qy as usize * width + qx as usize];
+                    total += wx * wy;
+                }
+            }
+            blurred[y * width + x] = sum / total;
+        }
+    }
+    blurred
+}
+
+fn distance_squared(a: Color, b: Color) -> Float {
+    let d = a - b;
+    d.r() * d.r() + d.g() * d.g() + d.b() * d.b()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::random::{self, random_range};
+
+    fn filled(width: usize, height: usize, f: impl Fn(usize, usize) -> Color) -> Image {
+        let mut image = Image::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                image.set(x, y, f(x, y));
+            }
+        }
+        image
+    }
+
+    fn rms_error(image: &Image, expected: impl Fn(usize, usize) -> Float) -> Float {
+        let mut sum = 0.0;
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                let d = image.get(x, y).g() - expected(x, y);
+                sum += d * d;
+            }
+        }
+        (sum / (image.width() * image.height()) as Float).sqrt()
+    }
+
+    #[test]
+    fn smooths_noise_on_flat_surfaces() {
+        random::seed(1);
+        let gray = |v: Float| Color::new(v, v, v);
+        let noisy = filled(32, 32, |_, _| gray(0.5 * random_range(0.5, 1.5)));
+        let albedo = filled(32, 32, |_, _| gray(0.5));
+        let normal = filled(32, 32, |_, _| Color::new(0.0, 0.0, 1.0));
+        let variance = filled(32, 32, |_, _| gray(uniform_variance(0.5, 0.5)));
+
+        let denoised = denoise(
+            &noisy,
+            &albedo,
+            &normal,
+            &variance,
+            &DenoiseSettings::default(),
+        );
+        let before = rms_error(&noisy, |_, _| 0.5);
+        let after = rms_error(&denoised, |_, _| 0.5);
+        assert!(after < 0.2 * before, "{} {}", after, before);
+    }
+
+    // The variance of `value` times a uniform random factor in
+    // [1 - spread, 1 + spread].
+    fn uniform_variance(value: Float, spread: Float) -> Float {
+        let width = 2.0 * spread * value;
+        width * width / 12.0
+    }
+
+    #[test]
+    fn keeps_edges_in_the_normals() {
+        // Two walls at right angles meeting in the middle column, one lit
+        // five times as brightly as the other.
+        random::seed(2);
+        let gray = |v: Float| Color::new(v, v, v);
+        let truth = |x: usize, _| if x < 16 { 0.5 } else { 0.1 };
+        let noisy = filled(32, 32, |x, y| gray(truth(x, y) * random_range(0.5, 1.5)));
+        let albedo = filled(32, 32, |_, _| gray(0.5));
+        let normal = filled(32, 32, |x, _| {
+            if x < 16 {
+                Color::new(0.0, 0.0, 1.0)
+            } else {
+                Color::new(1.0, 0.0, 0.0)
+            }
+        });
+        let variance = filled(32, 32, |x, y| gray(uniform_variance(truth(x, y), 0.5)));
+
+        let denoised = denoise(
+            &noisy,
+            &albedo,
+            &normal,
+            &variance,
+            &DenoiseSettings::default(),
+        );
+        for y in 0..32 {
+            for &x in &[15, 16] {
+                let (value, expected) = (denoised.get(x, y).g(), truth(x, y));
+                assert!(
+                    (value / expected - 1.0).abs() < 0.15,
+                    "{} {}",
+                    value,
+                    expected
+                );
+            }
+        }
+        assert!(rms_error(&denoised, truth) < 0.3 * rms_error(&noisy, truth));
+    }
+
+    #[test]
+    fn denoised_render_is_closer_to_the_reference() {
+        use crate::aov::Aov;
+        use crate::camera::View;
+        use crate::geometry::{HittableList, Plane, Quad, Sphere};
+        use crate::integrator::IntegratorKind;
+        use crate::material::{DiffuseLight, Lambertian, Material};
+        use crate::render::{render_with_aovs, Background, RenderSettings};
+        use crate::sampler::SamplerKind;
+        use crate::utils::vec3::Vec3;
+        use std::rc::Rc;
+
+        // A ball on a floor under a square light.
+        let grey: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let red: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Lambertian::new(Color::new(0.7, 0.2, 0.2))));
+        let emitter: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0))));
+        let mut world = HittableList::new();
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &grey,
+        )));
+        world.add(Box::new(Sphere::new(Vec3::new(0.0, 0.5, 0.0), 0.5, &red)));
+        world.add(Box::new(Quad::new(
+            Vec3::new(-1.0, 3.0, -1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            &emitter,
+        )));
+
+        let mut settings = RenderSettings {
+            image_width: 24,
+            image_height: 24,
+            samples_per_pixel: 1024,
+            max_depth: 4,
+            roulette_depth: 4,
+            background: Background::Solid(Color::black()),
+            crop: None,
+            sampler: SamplerKind::Independent,
+            integrator: IntegratorKind::Path,
+            lights: Vec::new(),
+        };
+        let view = View::new(
+            Vec3::new(0.0, 1.5, 3.0),
+            Vec3::new(0.0, 0.4, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            50.0,
+            settings.image_size(),
+        );
+        let camera = view.perspective(0.0, 1.0);
+
+        random::seed(3);
+        let reference = render_with_aovs(&world, &camera, &settings, &[], |_| {}).color;
+        settings.samples_per_pixel = 16;
+        let layers = render_with_aovs(
+            &world,
+            &camera,
+            &settings,
+            &[Aov::Albedo, Aov::Normal, Aov::Variance],
+            |_| {},
+        );
+        let denoised = layers.denoised(&DenoiseSettings::default()).unwrap();
+
+        let expected = |x, y| reference.get(x, y).g();
+        let before = rms_error(&layers.color, expected);
+        let after = rms_error(&denoised, expected);
+        assert!(after < 0.5 * before, "{} {}", after, before);
+    }
+
+    #[test]
+    fn keeps_shadow_edges() {
+        // A plane of one color and normal, half in shadow. Only the noise
+        // each pixel's samples showed tells the edge from noise; the spread
+        // of the pixels around the edge would take it for noise.
+        random::seed(4);
+        let gray = |v: Float| Color::new(v, v, v);
+        let truth = |x: usize, _| if x < 16 { 0.5 } else { 0.05 };
+        let noisy = filled(32, 32, |x, y| gray(truth(x, y) * random_range(0.8, 1.2)));
+        let albedo = filled(32, 32, |_, _| gray(0.5));
+        let normal = filled(32, 32, |_, _| Color::new(0.0, 0.0, 1.0));
+        let variance = filled(32, 32, |x, y| gray(uniform_variance(truth(x, y), 0.2)));
+
+        let denoised = denoise(
+            &noisy,
+            &albedo,
+            &normal,
+            &variance,
+            &DenoiseSettings::default(),
+        );
+        for y in 0..32 {
+            for &x in &[15, 16] {
+                let (value, expected) = (denoised.get(x, y).g(), truth(x, y));
+                assert!(
+                    (value / expected - 1.0).abs() < 0.15,
+                    "{} {}",
+                    value,
+                    expected
+                );
+            }
+        }
+        assert!(rms_error(&denoised, truth) < 0.5 * rms_error(&noisy, truth));
+    }
+
+    #[test]
+    #[should_panic]
+    fn features_must_match_the_image() {
+        let color = Image::new(4, 4);
+        let small = Image::new(2, 2);
+        denoise(&color, &small, &color, &color, &DenoiseSettings::default());
+    }
+}
@@ -1,11 +1,50 @@
+mod aabb;
+mod alpha_mask;
+mod annulus;
+mod cone;
+mod csg;
+mod cuboid;
+mod cylinder;
+mod disk;
+mod heightfield;
 mod hit_record;
 mod hittable;
 mod hittable_list;
+mod implicit_surface;
+mod mesh;
+mod plane;
+mod polygon_mesh;
+mod quad;
 mod ray;
+mod solid;
 mod sphere;
+mod stats;
+mod torus;
+mod transform;
+mod triangle;
 
+pub use aabb::Aabb;
+pub use alpha_mask::AlphaMask;
+pub use annulus::Annulus;
+pub use cone::Cone;
+pub use csg::{Csg, CsgOp};
+pub use cuboid::Cuboid;
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use heightfield::Heightfield;
 pub use hit_record::HitRecord;
 pub use hittable::Hittable;
 pub use hittable_list::HittableList;
+pub use implicit_surface::ImplicitSurface;
+pub use mesh::Mesh;
+pub use plane::Plane;
+pub use polygon_mesh::PolygonMesh;
+pub use quad::Quad;
 pub use ray::Ray;
+pub use solid::{Interval, Solid};
+pub(crate) use sphere::sphere_uv;
 pub use sphere::Sphere;
+pub use stats::take_test_count;
+pub use torus::Torus;
+pub use transform::{Transform, Transformed};
+pub use triangle::Triangle;
@@ -0,0 +1,99 @@
+use super::ray::Ray;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// An axis-aligned bounding box.
+#[derive(Copy, Clone, Debug)]
+pub struct Aabb {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Aabb {
+    /// The box spanning two corners, given in any order.
+    pub fn new(a: Vec3, b: Vec3) -> Self {
+        Self {
+            min: Vec3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z())),
+            max: Vec3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z())),
+        }
+    }
+
+    pub fn surrounding(a: &Aabb, b: &Aabb) -> Self {
+        Self::new(
+            Vec3::new(
+                a.min.x().min(b.min.x()),
+                a.min.y().min(b.min.y()),
+                a.min.z().min(b.min.z()),
+            ),
+            Vec3::new(
+                a.max.x().max(b.max.x()),
+                a.max.y().max(b.max.y()),
+                a.max.z().max(b.max.z()),
+            ),
+        )
+    }
+
+    pub fn min(&self) -> &Vec3 {
+        &self.min
+    }
+
+    pub fn max(&self) -> &Vec3 {
+        &self.max
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        0.5 * (self.min + self.max)
+    }
+
+    /// The part of `[t_min, t_max]` during which the ray is inside the box.
+    pub fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<(Float, Float)> {
+        let origin = [r.origin().x(), r.origin().y(), r.origin().z()];
+        let direction = [r.direction().x(), r.direction().y(), r.direction().z()];
+        let min = [self.min.x(), self.min.y(), self.min.z()];
+        let max = [self.max.x(), self.max.y(), self.max.z()];
+
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for axis in 0..3 {
+            let inv_d = 1.0 / direction[axis];
+            let mut t0 = (min[axis] - origin[axis]) * inv_d;
+            let mut t1 = (max[axis] - origin[axis]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            // NaN (a ray in the slab's plane) leaves the bounds unchanged.
+            if t0 > t_min {
+                t_min = t0;
+            }
+            if t1 < t_max {
+                t_max = t1;
+            }
+            if t_max < t_min {
+                return None;
+            }
+        }
+
+        Some((t_min, t_max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn hit() {
+        let aabb = Aabb::new(Vec3::new(1.0, 1.0, 1.0), Vec3::new(-1.0, -1.0, -1.0));
+        let r = Ray::new(&Vec3::new(-3.0, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        let (t0, t1) = aabb.hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((t0 - 2.0).abs() < EPSILON);
+        assert!((t1 - 4.0).abs() < EPSILON);
+
+        let r = Ray::new(&Vec3::new(-3.0, 2.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(aabb.hit(&r, 0.0, Float::INFINITY).is_none());
+    }
+}
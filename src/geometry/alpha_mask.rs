@@ -0,0 +1,201 @@
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use crate::texture::Texture;
+use crate::utils::random::sample_1d;
+use crate::utils::Float;
+use std::rc::Rc;
+
+enum Mode {
+    Threshold(Float),
+    Stochastic,
+}
+
+/// Cuts holes into any object, e.g. a quad carrying a leaf or fence texture.
+///
+/// The opacity at a hit is the alpha of the `alpha` texture at its `(u, v)`:
+/// the alpha channel of an image texture that has one, or else the
+/// luminance. Hits on transparent parts are ignored and the ray continues
+/// through the object, so the surfaces behind, including the far side of the
+/// object itself, show through.
+pub struct AlphaMask {
+    object: Box<dyn Hittable>,
+    alpha: Rc<Box<dyn Texture>>,
+    mode: Mode,
+}
+
+impl AlphaMask {
+    /// Keeps the hits whose opacity is at least `threshold`.
+    pub fn new(object: Box<dyn Hittable>, alpha: &Rc<Box<dyn Texture>>, threshold: Float) -> Self {
+        Self {
+            object,
+            alpha: Rc::clone(alpha),
+            mode: Mode::Threshold(threshold),
+        }
+    }
+
+    /// Keeps each hit with probability equal to its opacity, so that partly
+    /// transparent regions average out to the right coverage.
+    pub fn stochastic(object: Box<dyn Hittable>, alpha: &Rc<Box<dyn Texture>>) -> Self {
+        Self {
+            object,
+            alpha: Rc::clone(alpha),
+            mode: Mode::Stochastic,
+        }
+    }
+
+    fn is_opaque(&self, rec: &HitRecord) -> bool {
+        let alpha = self.alpha.alpha(rec.u, rec.v, &rec.p);
+        match self.mode {
+            Mode::Threshold(threshold) => alpha >= threshold,
+            Mode::Stochastic => sample_1d() < alpha,
+        }
+    }
+}
+
+impl Hittable for AlphaMask {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let mut t_min = t_min;
+        loop {
+            let rec = self.object.hit(r, t_min, t_max)?;
+            if self.is_opaque(&rec) {
+                return Some(rec);
+            }
+
+            // Continue just past the rejected hit so it is not found again.
+            t_min = rec.t + 1e-5 * rec.t.abs().max(1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Mesh, PolygonMesh, Quad, Sphere};
+    use crate::material::{Lambertian, Material};
+    use crate::texture::ImageTexture;
+    use crate::utils::color::Color;
+    use crate::utils::image::Image;
+    use crate::utils::random;
+    use crate::utils::vec3::Vec3;
+
+    const EPSILON: Float = 0.00001;
+
+    // Transparent for u < 0.5, opaque beyond.
+    struct HalfMask;
+
+    impl Texture for HalfMask {
+        fn value(&self, u: Float, _v: Float, _p: &Vec3) -> Color {
+            if u < 0.5 {
+                Color::black()
+            } else {
+                Color::white()
+            }
+        }
+    }
+
+    fn mat() -> Rc<Box<dyn Material>> {
+        Rc::new(Box::new(Lambertian::new(Color::white())))
+    }
+
+    #[test]
+    fn transparent_hits_pass_through() {
+        let alpha: Rc<Box<dyn Texture>> = Rc::new(Box::new(HalfMask));
+        let quad = Quad::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            &mat(),
+        );
+        let masked = AlphaMask::new(Box::new(quad), &alpha, 0.5);
+
+        let r = Ray::new(&Vec3::new(1.5, 1.0, 1.0), &Vec3::new(0.0, 0.0, -1.0));
+        assert!(masked.hit(&r, 0.0, Float::INFINITY).is_some());
+        let r = Ray::new(&Vec3::new(0.5, 1.0, 1.0), &Vec3::new(0.0, 0.0, -1.0));
+        assert!(masked.hit(&r, 0.0, Float::INFINITY).is_none());
+    }
+
+    #[test]
+    fn mesh_follows_texture_coordinates() {
+        // A square of two triangles whose u runs from 0 to 1 along x, so
+        // the left half is cut away. Barycentric weights would instead
+        // repeat the mask on each triangle.
+        let obj = "v 0 0 0\nv 2 0 0\nv 2 2 0\nv 0 2 0\n\
+                   vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+                   f 1/1 2/2 3/3 4/4\n";
+        let polygons = PolygonMesh::read_obj(obj.as_bytes()).unwrap();
+        let mesh = Mesh::from_polygons(&polygons, &mat());
+        let alpha: Rc<Box<dyn Texture>> = Rc::new(Box::new(HalfMask));
+        let masked = AlphaMask::new(Box::new(mesh), &alpha, 0.5);
+
+        let hit = |x, y| {
+            let r = Ray::new(&Vec3::new(x, y, 1.0), &Vec3::new(0.0, 0.0, -1.0));
+            masked.hit(&r, 0.0, Float::INFINITY)
+        };
+        let rec = hit(1.5, 1.0).unwrap();
+        assert!((rec.u - 0.75).abs() < EPSILON);
+        assert!((rec.v - 0.5).abs() < EPSILON);
+        assert!(hit(1.5, 1.8).is_some());
+        assert!(hit(0.9, 0.1).is_none());
+        assert!(hit(0.5, 1.5).is_none());
+    }
+
+    #[test]
+    fn image_alpha_overrides_color() {
+        // Dark where opaque and bright where transparent, so luminance would
+        // cut the wrong half away.
+        let mut color = Image::new(2, 1);
+        color.set(0, 0, Color::white());
+        let mut alpha = Image::new(2, 1);
+        alpha.set(1, 0, Color::white());
+        let alpha: Rc<Box<dyn Texture>> = Rc::new(Box::new(ImageTexture::with_alpha(color, alpha)));
+        let quad = Quad::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            &mat(),
+        );
+        let masked = AlphaMask::new(Box::new(quad), &alpha, 0.5);
+
+        let r = Ray::new(&Vec3::new(1.9, 1.0, 1.0), &Vec3::new(0.0, 0.0, -1.0));
+        assert!(masked.hit(&r, 0.0, Float::INFINITY).is_some());
+        let r = Ray::new(&Vec3::new(0.1, 1.0, 1.0), &Vec3::new(0.0, 0.0, -1.0));
+        assert!(masked.hit(&r, 0.0, Float::INFINITY).is_none());
+    }
+
+    #[test]
+    fn far_side_shows_through() {
+        let alpha: Rc<Box<dyn Texture>> = Rc::new(Box::new(HalfMask));
+        let sphere = Sphere::new(Vec3::new(0.0, 0.0, 0.0), 1.0, &mat());
+        let masked = AlphaMask::new(Box::new(sphere), &alpha, 0.5);
+
+        // The near side at +z has u = 0.25 and is cut away; the far side at
+        // -z has u = 0.75 and is kept.
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+        let rec = masked.hit(&r, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 6.0).abs() < EPSILON);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn stochastic_coverage_matches_alpha() {
+        random::seed(3);
+        let gray: Rc<Box<dyn Texture>> = Rc::new(Box::new(crate::texture::SolidColor::new(
+            Color::new(0.3, 0.3, 0.3),
+        )));
+        let quad = Quad::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &mat(),
+        );
+        let masked = AlphaMask::stochastic(Box::new(quad), &gray);
+
+        let r = Ray::new(&Vec3::new(0.5, 0.5, 1.0), &Vec3::new(0.0, 0.0, -1.0));
+        let n = 100_000;
+        let hits = (0..n)
+            .filter(|_| masked.hit(&r, 0.0, Float::INFINITY).is_some())
+            .count();
+        assert!((hits as Float / n as Float - 0.3).abs() < 0.01);
+    }
+}
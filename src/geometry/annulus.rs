@@ -0,0 +1,142 @@
+use super::disk::{polar_tangents, polar_uv};
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::plane::hit_plane;
+use super::ray::Ray;
+use crate::material::Material;
+use crate::utils::onb::Onb;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// A flat ring between `inner_radius` and `outer_radius`, facing `normal`.
+/// `u` runs around the center, `v` from the inner to the outer edge.
+pub struct Annulus {
+    center: Vec3,
+    inner_radius: Float,
+    outer_radius: Float,
+    onb: Onb,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl Annulus {
+    pub fn new(
+        center: Vec3,
+        normal: Vec3,
+        inner_radius: Float,
+        outer_radius: Float,
+        mat: &Rc<Box<dyn Material>>,
+    ) -> Self {
+        Self {
+            center,
+            inner_radius,
+            outer_radius,
+            onb: Onb::from_w(&normal),
+            mat: Rc::clone(mat),
+        }
+    }
+}
+
+impl Hittable for Annulus {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let t = hit_plane(r, &self.center, self.onb.w(), t_min, t_max)?;
+        let p = r.at(t);
+
+        let (u, dist) = polar_uv(&self.onb, &self.center, &p);
+        if dist < self.inner_radius || dist > self.outer_radius {
+            return None;
+        }
+
+        let v = (dist - self.inner_radius) / (self.outer_radius - self.inner_radius);
+        let (dpdu, radial) = polar_tangents(&self.onb, &self.center, &p);
+        let dpdv = (self.outer_radius - self.inner_radius) * radial;
+        Some(HitRecord::new(p, t, (u, v), r, self.onb.w(), &self.mat).with_tangents(&dpdu, &dpdv))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.00001;
+
+    fn annulus() -> Annulus {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        Annulus::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            1.0,
+            2.0,
+            &mat,
+        )
+    }
+
+    fn along_x(y: Float, z: Float) -> Ray {
+        Ray::new(&Vec3::new(-5.0, y, z), &Vec3::new(1.0, 0.0, 0.0))
+    }
+
+    #[test]
+    fn hit_back() {
+        // The ring faces +x, so a ray going that way meets its back.
+        let rec = annulus()
+            .hit(&along_x(1.5, 0.0), 0.0, Float::INFINITY)
+            .unwrap();
+
+        assert!((rec.t - 5.0).abs() < EPSILON);
+        assert!((rec.normal - Vec3::new(-1.0, 0.0, 0.0)).length() < EPSILON);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn hit_front() {
+        let r = Ray::new(&Vec3::new(3.0, 0.0, -1.5), &Vec3::new(-1.0, 0.0, 0.0));
+        let rec = annulus().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 3.0).abs() < EPSILON);
+        assert!((rec.normal - Vec3::new(1.0, 0.0, 0.0)).length() < EPSILON);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn miss_hole_and_outside() {
+        assert!(annulus()
+            .hit(&along_x(0.5, 0.5), 0.0, Float::INFINITY)
+            .is_none());
+        assert!(annulus()
+            .hit(&along_x(0.0, 0.0), 0.0, Float::INFINITY)
+            .is_none());
+        assert!(annulus()
+            .hit(&along_x(1.5, 1.5), 0.0, Float::INFINITY)
+            .is_none());
+    }
+
+    #[test]
+    fn uv_runs_across_the_ring() {
+        let annulus = annulus();
+        let uv_at = |y, z| {
+            let rec = annulus.hit(&along_x(y, z), 0.0, Float::INFINITY).unwrap();
+            (rec.u, rec.v)
+        };
+
+        // v goes from 0 at the inner edge to 1 at the outer one.
+        assert!((uv_at(1.001, 0.0).1 - 0.001).abs() < EPSILON);
+        assert!((uv_at(0.0, -1.5).1 - 0.5).abs() < EPSILON);
+        assert!((uv_at(0.0, 1.999).1 - 0.999).abs() < EPSILON);
+        let (u, _) = uv_at(1.5, 0.0);
+        let (u2, _) = uv_at(-1.5, 0.0);
+        assert!(((u2 - u).rem_euclid(1.0) - 0.5).abs() < EPSILON);
+
+        // Stepping by the derivatives moves the coordinates one for one.
+        let rec = annulus
+            .hit(&along_x(0.9, 1.2), 0.0, Float::INFINITY)
+            .unwrap();
+        let h = 1e-4;
+        for (d, du, dv) in [(rec.dpdu, h, 0.0), (rec.dpdv, 0.0, h)] {
+            let p = rec.p + h * d;
+            let (u2, v2) = uv_at(p.y(), p.z());
+            assert!((u2 - rec.u - du).abs() < 1e-2 * h && (v2 - rec.v - dv).abs() < 1e-2 * h);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use super::cylinder::angle_u;
+use super::disk::polar_tangents;
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use super::solid::{convex_intervals, Interval, Solid};
+use crate::material::Material;
+use crate::utils::onb::Onb;
+use crate::utils::roots::solve_quadratic;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// A closed cone with its base disk centered on `base` and its apex
+/// `height` along `axis`.
+///
+/// On the side, `u` runs around the axis and `v` from base to apex; on the
+/// base, `v` is the distance from the axis divided by the radius.
+pub struct Cone {
+    base: Vec3,
+    radius: Float,
+    height: Float,
+    onb: Onb,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl Cone {
+    pub fn new(
+        base: Vec3,
+        axis: Vec3,
+        radius: Float,
+        height: Float,
+        mat: &Rc<Box<dyn Material>>,
+    ) -> Self {
+        Self {
+            base,
+            radius,
+            height,
+            onb: Onb::from_w(&axis),
+            mat: Rc::clone(mat),
+        }
+    }
+
+    /// All crossings of the ray with the surface, nearest first, as
+    /// `(t, outward normal, (u, v))`.
+    pub(super) fn intersections(&self, r: &Ray) -> Vec<(Float, Vec3, (Float, Float))> {
+        let o = self.onb.to_local(&(*r.origin() - self.base));
+        let d = self.onb.to_local(r.direction());
+        let mut hits = Vec::new();
+
+        // Side: x^2 + y^2 = k^2 (height - z)^2 with 0 <= z <= height.
+        let k2 = (self.radius / self.height).powi(2);
+        let h = self.height - o.z();
+        let a = d.x() * d.x() + d.y() * d.y() - k2 * d.z() * d.z();
+        let b = 2.0 * (o.x() * d.x() + o.y() * d.y() + k2 * h * d.z());
+        let c = o.x() * o.x() + o.y() * o.y() - k2 * h * h;
+        for t in solve_quadratic(a, b, c) {
+            let p = o + t * d;
+            if (0.0..=self.height).contains(&p.z()) {
+                let normal = Vec3::new(p.x(), p.y(), k2 * (self.height - p.z()));
+                // The apex has no normal of its own; point along the axis
+                // rather than let a zero vector turn into NaN.
+                let normal = if normal.near_zero() {
+                    Vec3::new(0.0, 0.0, 1.0)
+                } else {
+                    normal.unit()
+                };
+                hits.push((t, normal, (angle_u(p.x(), p.y()), p.z() / self.height)));
+            }
+        }
+
+        // Base cap at z = 0.
+        if d.z() != 0.0 {
+            let t = -o.z() / d.z();
+            let (x, y) = (o.x() + t * d.x(), o.y() + t * d.y());
+            let dist = x.hypot(y);
+            if dist <= self.radius {
+                let normal = Vec3::new(0.0, 0.0, -1.0);
+                hits.push((t, normal, (angle_u(x, y), dist / self.radius)));
+            }
+        }
+
+        hits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        hits.into_iter()
+            .map(|(t, n, uv)| (t, self.onb.local(&n), uv))
+            .collect()
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let (t, normal, uv) = self
+            .intersections(r)
+            .into_iter()
+            .find(|&(t, _, _)| t_min <= t && t <= t_max)?;
+
+        let p = r.at(t);
+        let (dpdu, radial) = polar_tangents(&self.onb, &self.base, &p);
+        // v runs up the side, narrowing towards the apex, and outwards on
+        // the base.
+        let dpdv = if normal.dot(self.onb.w()).abs() > 0.999 {
+            self.radius * radial
+        } else {
+            self.height * *self.onb.w() - self.radius * radial
+        };
+
+        Some(HitRecord::new(p, t, uv, r, &normal, &self.mat).with_tangents(&dpdu, &dpdv))
+    }
+}
+
+impl Solid for Cone {
+    fn intervals(&self, r: &Ray) -> Vec<Interval> {
+        convex_intervals(r, self.intersections(r), &self.mat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.00001;
+
+    fn cone() -> Cone {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        Cone::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            1.0,
+            &mat,
+        )
+    }
+
+    #[test]
+    fn apex_has_a_normal() {
+        // Straight down the axis, and steeper than the side.
+        for direction in &[Vec3::new(0.0, -1.0, 0.0), Vec3::new(1.0, -2.0, 0.0)] {
+            let origin = Vec3::new(0.0, 1.0, 0.0) - 2.0 * *direction;
+            let r = Ray::new(&origin, direction);
+            let rec = cone().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+            assert!((rec.p - Vec3::new(0.0, 1.0, 0.0)).length() < EPSILON);
+            assert!((rec.normal.length() - 1.0).abs() < EPSILON);
+            assert!((rec.shading_normal.length() - 1.0).abs() < EPSILON);
+            assert!(rec.normal.dot(direction) < 0.0);
+        }
+    }
+
+    #[test]
+    fn hit_side() {
+        let r = Ray::new(&Vec3::new(-5.0, 0.5, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        let rec = cone().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        // At half height the radius is 0.5; the slope is 45 degrees.
+        assert!((rec.t - 4.5).abs() < EPSILON);
+        let expected = Vec3::new(-1.0, 1.0, 0.0).unit();
+        assert!((rec.normal - expected).length() < EPSILON);
+        assert!((rec.v - 0.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn hit_base() {
+        let r = Ray::new(&Vec3::new(0.2, -1.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        let rec = cone().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 1.0).abs() < EPSILON);
+        assert!((rec.normal.y() + 1.0).abs() < EPSILON);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn miss_beside_apex() {
+        let r = Ray::new(&Vec3::new(-5.0, 0.9, 0.3), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(cone().hit(&r, 0.0, Float::INFINITY).is_none());
+    }
+}
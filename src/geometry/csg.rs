@@ -0,0 +1,220 @@
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use super::solid::{first_boundary, Interval, Solid};
+use crate::utils::Float;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    /// Everything in the first solid that is not in the second.
+    Difference,
+}
+
+impl CsgOp {
+    fn contains(&self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            CsgOp::Union => in_a || in_b,
+            CsgOp::Intersection => in_a && in_b,
+            CsgOp::Difference => in_a && !in_b,
+        }
+    }
+}
+
+/// A constructive solid geometry node combining two closed solids. Each
+/// surface keeps the material of the solid it came from.
+pub struct Csg {
+    op: CsgOp,
+    a: Box<dyn Solid>,
+    b: Box<dyn Solid>,
+}
+
+impl Csg {
+    pub fn new(op: CsgOp, a: Box<dyn Solid>, b: Box<dyn Solid>) -> Self {
+        Self { op, a, b }
+    }
+
+    pub fn union(a: Box<dyn Solid>, b: Box<dyn Solid>) -> Self {
+        Self::new(CsgOp::Union, a, b)
+    }
+
+    pub fn intersection(a: Box<dyn Solid>, b: Box<dyn Solid>) -> Self {
+        Self::new(CsgOp::Intersection, a, b)
+    }
+
+    pub fn difference(a: Box<dyn Solid>, b: Box<dyn Solid>) -> Self {
+        Self::new(CsgOp::Difference, a, b)
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        first_boundary(self.intervals(r), t_min, t_max)
+    }
+}
+
+impl Solid for Csg {
+    fn intervals(&self, r: &Ray) -> Vec<Interval> {
+        // Sweep over the boundaries of both operands in order, tracking which
+        // solids the ray is inside of.
+        let mut events: Vec<(bool, HitRecord)> = Vec::new();
+        for (from_a, intervals) in [(true, self.a.intervals(r)), (false, self.b.intervals(r))] {
+            for interval in intervals {
+                events.push((from_a, interval.enter));
+                events.push((from_a, interval.exit));
+            }
+        }
+        events.sort_by(|x, y| x.1.t.partial_cmp(&y.1.t).unwrap());
+
+        let mut in_a = false;
+        let mut in_b = false;
+        let mut inside = false;
+        let mut enter: Option<HitRecord> = None;
+        let mut result = Vec::new();
+
+        for (from_a, mut rec) in events {
+            if from_a {
+                in_a = !in_a;
+            } else {
+                in_b = !in_b;
+            }
+
+            if self.op.contains(in_a, in_b) == inside {
+                continue;
+            }
+            inside = !inside;
+
+            // Surfaces of the subtracted solid face the other way in the
+            // result. The normal already faces the ray; only the side flips.
+            if self.op == CsgOp::Difference && !from_a {
+                rec.front_face = !rec.front_face;
+            }
+
+            if inside {
+                enter = Some(rec);
+            } else if let Some(enter) = enter.take() {
+                result.push(Interval { enter, exit: rec });
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Cuboid, Cylinder, Sphere};
+    use crate::material::{Lambertian, Material};
+    use crate::utils::color::Color;
+    use crate::utils::vec3::Vec3;
+    use std::rc::Rc;
+
+    const EPSILON: Float = 0.00001;
+
+    fn mat() -> Rc<Box<dyn Material>> {
+        Rc::new(Box::new(Lambertian::new(Color::white())))
+    }
+
+    fn sphere_minus_sphere() -> Csg {
+        Csg::difference(
+            Box::new(Sphere::new(Vec3::new(0.0, 0.0, 0.0), 1.0, &mat())),
+            Box::new(Sphere::new(Vec3::new(0.0, 0.0, 1.0), 0.5, &mat())),
+        )
+    }
+
+    fn box_minus_cylinder() -> Csg {
+        Csg::difference(
+            Box::new(Cuboid::new(
+                Vec3::new(-1.0, -1.0, -1.0),
+                Vec3::new(1.0, 1.0, 1.0),
+                &mat(),
+            )),
+            Box::new(Cylinder::new(
+                Vec3::new(0.0, -2.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                0.5,
+                4.0,
+                &mat(),
+            )),
+        )
+    }
+
+    #[test]
+    fn sphere_minus_sphere_hits_bottom_of_dent() {
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+        let rec = sphere_minus_sphere().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        // The dent's surface is the inner sphere's far side, facing +z.
+        assert!((rec.t - 4.5).abs() < EPSILON);
+        assert!((rec.normal.z() - 1.0).abs() < EPSILON);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn sphere_minus_sphere_keeps_untouched_surface() {
+        let r = Ray::new(&Vec3::new(0.0, 0.0, -5.0), &Vec3::new(0.0, 0.0, 1.0));
+        let csg = sphere_minus_sphere();
+        let rec = csg.hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 4.0).abs() < EPSILON);
+        assert!((rec.normal.z() + 1.0).abs() < EPSILON);
+
+        // From inside the solid part the next boundary is the dent, seen
+        // from behind.
+        let rec = csg.hit(&r, 4.1, Float::INFINITY).unwrap();
+        assert!((rec.t - 5.5).abs() < EPSILON);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn box_minus_cylinder_has_a_hole() {
+        let csg = box_minus_cylinder();
+
+        // Straight down the hole.
+        let r = Ray::new(&Vec3::new(0.0, 5.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        assert!(csg.hit(&r, 0.0, Float::INFINITY).is_none());
+
+        // Beside the hole the top face is intact.
+        let r = Ray::new(&Vec3::new(0.75, 5.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let rec = csg.hit(&r, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 4.0).abs() < EPSILON);
+        assert!((rec.normal.y() - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn box_minus_cylinder_intervals_across_hole() {
+        let r = Ray::new(&Vec3::new(-5.0, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        let intervals = box_minus_cylinder().intervals(&r);
+
+        assert_eq!(intervals.len(), 2);
+        assert!((intervals[0].enter.t - 4.0).abs() < EPSILON);
+        assert!((intervals[0].exit.t - 4.5).abs() < EPSILON);
+        assert!((intervals[1].enter.t - 5.5).abs() < EPSILON);
+        assert!((intervals[1].exit.t - 6.0).abs() < EPSILON);
+
+        // The hole's wall is entered from inside the box material: the ray
+        // leaves the solid there, so it sees the back of the surface.
+        assert!(!intervals[0].exit.front_face);
+        assert!(intervals[1].enter.front_face);
+        assert!((intervals[1].enter.normal.x() + 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn union_and_intersection_of_spheres() {
+        let a = || Box::new(Sphere::new(Vec3::new(-0.5, 0.0, 0.0), 1.0, &mat()));
+        let b = || Box::new(Sphere::new(Vec3::new(0.5, 0.0, 0.0), 1.0, &mat()));
+        let r = Ray::new(&Vec3::new(-5.0, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+
+        let union = Csg::union(a(), b()).intervals(&r);
+        assert_eq!(union.len(), 1);
+        assert!((union[0].enter.t - 3.5).abs() < EPSILON);
+        assert!((union[0].exit.t - 6.5).abs() < EPSILON);
+
+        let intersection = Csg::intersection(a(), b()).intervals(&r);
+        assert_eq!(intersection.len(), 1);
+        assert!((intersection[0].enter.t - 4.5).abs() < EPSILON);
+        assert!((intersection[0].exit.t - 5.5).abs() < EPSILON);
+    }
+}
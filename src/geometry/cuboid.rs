@@ -0,0 +1,95 @@
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use super::solid::{convex_intervals, first_boundary, Interval, Solid};
+use crate::material::Material;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// An axis-aligned box spanning the corners `min` and `max`. Each face is
+/// mapped to the full [0, 1] UV square.
+pub struct Cuboid {
+    min: Vec3,
+    max: Vec3,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl Cuboid {
+    pub fn new(a: Vec3, b: Vec3, mat: &Rc<Box<dyn Material>>) -> Self {
+        Self {
+            min: Vec3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z())),
+            max: Vec3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z())),
+            mat: Rc::clone(mat),
+        }
+    }
+
+    // Slab test: the entry and exit crossings with the box, if any.
+    fn crossings(&self, r: &Ray) -> Vec<(Float, Vec3, (Float, Float))> {
+        let origin = [r.origin().x(), r.origin().y(), r.origin().z()];
+        let direction = [r.direction().x(), r.direction().y(), r.direction().z()];
+        let min = [self.min.x(), self.min.y(), self.min.z()];
+        let max = [self.max.x(), self.max.y(), self.max.z()];
+
+        let mut enter = (Float::NEG_INFINITY, 0, 0.0);
+        let mut exit = (Float::INFINITY, 0, 0.0);
+        for axis in 0..3 {
+            if direction[axis] == 0.0 {
+                if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                    return Vec::new();
+                }
+                continue;
+            }
+
+            let inv_d = 1.0 / direction[axis];
+            let t0 = (min[axis] - origin[axis]) * inv_d;
+            let t1 = (max[axis] - origin[axis]) * inv_d;
+            // The sign of the outward normal on the face crossed first.
+            let (near, far, sign) = if inv_d < 0.0 {
+                (t1, t0, 1.0)
+            } else {
+                (t0, t1, -1.0)
+            };
+
+            if near > enter.0 {
+                enter = (near, axis, sign);
+            }
+            if far < exit.0 {
+                exit = (far, axis, -sign);
+            }
+        }
+
+        if enter.0 >= exit.0 || !enter.0.is_finite() {
+            return Vec::new();
+        }
+
+        [enter, exit]
+            .iter()
+            .map(|&(t, axis, sign)| {
+                let p = r.at(t);
+                let p = [p.x(), p.y(), p.z()];
+                let mut normal = [0.0; 3];
+                normal[axis] = sign;
+
+                // The two remaining axes give the face coordinates.
+                let (i, j) = ((axis + 1) % 3, (axis + 2) % 3);
+                let u = (p[i] - min[i]) / (max[i] - min[i]);
+                let v = (p[j] - min[j]) / (max[j] - min[j]);
+
+                (t, Vec3::new(normal[0], normal[1], normal[2]), (u, v))
+            })
+            .collect()
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        first_boundary(self.intervals(r), t_min, t_max)
+    }
+}
+
+impl Solid for Cuboid {
+    fn intervals(&self, r: &Ray) -> Vec<Interval> {
+        convex_intervals(r, self.crossings(r), &self.mat)
+    }
+}
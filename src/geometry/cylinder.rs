@@ -0,0 +1,163 @@
+use super::disk::polar_tangents;
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use super::solid::{convex_intervals, Interval, Solid};
+use crate::material::Material;
+use crate::utils::consts::PI;
+use crate::utils::onb::Onb;
+use crate::utils::roots::solve_quadratic;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// A closed cylinder whose bottom cap is centered on `base` and which
+/// extends `height` along `axis`.
+///
+/// On the side, `u` runs around the axis and `v` from bottom to top; on the
+/// caps, `v` is the distance from the axis divided by the radius.
+pub struct Cylinder {
+    base: Vec3,
+    radius: Float,
+    height: Float,
+    onb: Onb,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl Cylinder {
+    pub fn new(
+        base: Vec3,
+        axis: Vec3,
+        radius: Float,
+        height: Float,
+        mat: &Rc<Box<dyn Material>>,
+    ) -> Self {
+        Self {
+            base,
+            radius,
+            height,
+            onb: Onb::from_w(&axis),
+            mat: Rc::clone(mat),
+        }
+    }
+
+    /// All crossings of the ray with the surface, nearest first, as
+    /// `(t, outward normal, (u, v))`.
+    pub(super) fn intersections(&self, r: &Ray) -> Vec<(Float, Vec3, (Float, Float))> {
+        let o = self.onb.to_local(&(*r.origin() - self.base));
+        let d = self.onb.to_local(r.direction());
+        let mut hits = Vec::new();
+
+        // Side: x^2 + y^2 = radius^2 with 0 <= z <= height.
+        let a = d.x() * d.x() + d.y() * d.y();
+        let half_b = o.x() * d.x() + o.y() * d.y();
+        let c = o.x() * o.x() + o.y() * o.y() - self.radius * self.radius;
+        if a > 0.0 {
+            for t in solve_quadratic(a, 2.0 * half_b, c) {
+                let z = o.z() + t * d.z();
+                if (0.0..=self.height).contains(&z) {
+                    let (x, y) = (o.x() + t * d.x(), o.y() + t * d.y());
+                    let normal = Vec3::new(x, y, 0.0) / self.radius;
+                    hits.push((t, normal, (angle_u(x, y), z / self.height)));
+                }
+            }
+        }
+
+        // Caps at z = 0 and z = height.
+        if d.z() != 0.0 {
+            for &(z, nz) in [(0.0, -1.0), (self.height, 1.0)].iter() {
+                let t = (z - o.z()) / d.z();
+                let (x, y) = (o.x() + t * d.x(), o.y() + t * d.y());
+                let dist = x.hypot(y);
+                if dist <= self.radius {
+                    let normal = Vec3::new(0.0, 0.0, nz);
+                    hits.push((t, normal, (angle_u(x, y), dist / self.radius)));
+                }
+            }
+        }
+
+        hits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        hits.into_iter()
+            .map(|(t, n, uv)| (t, self.onb.local(&n), uv))
+            .collect()
+    }
+}
+
+/// The angle of `(x, y)` around the local axis, scaled to [0, 1].
+pub(super) fn angle_u(x: Float, y: Float) -> Float {
+    (y.atan2(x) + PI) / (2.0 * PI)
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let (t, normal, uv) = self
+            .intersections(r)
+            .into_iter()
+            .find(|&(t, _, _)| t_min <= t && t <= t_max)?;
+
+        let p = r.at(t);
+        let (dpdu, radial) = polar_tangents(&self.onb, &self.base, &p);
+        // v runs up the side and outwards on the caps.
+        let dpdv = if normal.dot(self.onb.w()).abs() > 0.999 {
+            self.radius * radial
+        } else {
+            self.height * *self.onb.w()
+        };
+
+        Some(HitRecord::new(p, t, uv, r, &normal, &self.mat).with_tangents(&dpdu, &dpdv))
+    }
+}
+
+impl Solid for Cylinder {
+    fn intervals(&self, r: &Ray) -> Vec<Interval> {
+        convex_intervals(r, self.intersections(r), &self.mat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.00001;
+
+    fn cylinder() -> Cylinder {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        Cylinder::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            2.0,
+            &mat,
+        )
+    }
+
+    #[test]
+    fn hit_side() {
+        let r = Ray::new(&Vec3::new(-5.0, 1.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        let rec = cylinder().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 4.0).abs() < EPSILON);
+        assert!((rec.normal.x() + 1.0).abs() < EPSILON);
+        assert!((rec.v - 0.5).abs() < EPSILON);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn hit_cap_from_inside() {
+        let r = Ray::new(&Vec3::new(0.5, 1.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        let rec = cylinder().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 1.0).abs() < EPSILON);
+        assert!((rec.normal.y() + 1.0).abs() < EPSILON);
+        assert!((rec.v - 0.5).abs() < EPSILON);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn miss_above() {
+        let r = Ray::new(&Vec3::new(-5.0, 2.5, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(cylinder().hit(&r, 0.0, Float::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,156 @@
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::plane::hit_plane;
+use super::ray::Ray;
+use crate::material::Material;
+use crate::utils::consts::PI;
+use crate::utils::onb::Onb;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// A flat disk facing `normal`. `u` runs around the center, `v` outwards.
+pub struct Disk {
+    center: Vec3,
+    radius: Float,
+    onb: Onb,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl Disk {
+    pub fn new(center: Vec3, normal: Vec3, radius: Float, mat: &Rc<Box<dyn Material>>) -> Self {
+        Self {
+            center,
+            radius,
+            onb: Onb::from_w(&normal),
+            mat: Rc::clone(mat),
+        }
+    }
+}
+
+/// Polar coordinates of `p` in the plane of `onb`: (u, distance to center),
+/// with u the angle scaled to [0, 1].
+pub(super) fn polar_uv(onb: &Onb, center: &Vec3, p: &Vec3) -> (Float, Float) {
+    let local = onb.to_local(&(*p - *center));
+    let phi = local.y().atan2(local.x()) + PI;
+
+    (phi / (2.0 * PI), local.x().hypot(local.y()))
+}
+
+/// The derivative of `p` by the `u` of `polar_uv`, and the unit vector away
+/// from the axis through `center`, which is zero on the axis.
+pub(super) fn polar_tangents(onb: &Onb, center: &Vec3, p: &Vec3) -> (Vec3, Vec3) {
+    let local = onb.to_local(&(*p - *center));
+    let dist = local.x().hypot(local.y());
+    let radial = if dist > 0.0 {
+        onb.local(&Vec3::new(local.x(), local.y(), 0.0)) / dist
+    } else {
+        Vec3::new(0.0, 0.0, 0.0)
+    };
+
+    (
+        2.0 * PI * onb.local(&Vec3::new(-local.y(), local.x(), 0.0)),
+        radial,
+    )
+}
+
+impl Hittable for Disk {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let t = hit_plane(r, &self.center, self.onb.w(), t_min, t_max)?;
+        let p = r.at(t);
+
+        let (u, dist) = polar_uv(&self.onb, &self.center, &p);
+        if dist > self.radius {
+            return None;
+        }
+
+        let (dpdu, radial) = polar_tangents(&self.onb, &self.center, &p);
+        Some(
+            HitRecord::new(p, t, (u, dist / self.radius), r, self.onb.w(), &self.mat)
+                .with_tangents(&dpdu, &(self.radius * radial)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.00001;
+
+    fn disk() -> Disk {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        Disk::new(
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            2.0,
+            &mat,
+        )
+    }
+
+    fn down_at(x: Float, y: Float) -> Ray {
+        Ray::new(&Vec3::new(x, y, 5.0), &Vec3::new(0.0, 0.0, -1.0))
+    }
+
+    #[test]
+    fn hit_front() {
+        let rec = disk()
+            .hit(&down_at(1.0, 0.0), 0.0, Float::INFINITY)
+            .unwrap();
+
+        assert!((rec.t - 4.0).abs() < EPSILON);
+        assert!((rec.normal - Vec3::new(0.0, 0.0, 1.0)).length() < EPSILON);
+        assert!(rec.front_face);
+        assert!((rec.v - 0.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn hit_back() {
+        let r = Ray::new(&Vec3::new(0.5, 0.5, -1.0), &Vec3::new(0.0, 0.0, 1.0));
+        let rec = disk().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 2.0).abs() < EPSILON);
+        assert!((rec.normal - Vec3::new(0.0, 0.0, -1.0)).length() < EPSILON);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn miss_beyond_rim() {
+        assert!(disk()
+            .hit(&down_at(2.1, 0.0), 0.0, Float::INFINITY)
+            .is_none());
+        assert!(disk()
+            .hit(&down_at(1.5, -1.5), 0.0, Float::INFINITY)
+            .is_none());
+        assert!(disk().hit(&down_at(1.0, 0.0), 0.0, 3.0).is_none());
+    }
+
+    #[test]
+    fn uv_is_polar() {
+        let disk = disk();
+        let uv_at = |x, y| {
+            let rec = disk.hit(&down_at(x, y), 0.0, Float::INFINITY).unwrap();
+            (rec.u, rec.v)
+        };
+
+        // v grows from the center to the rim, and opposite points are half a
+        // turn apart.
+        assert!(uv_at(0.0, 0.0).1.abs() < EPSILON);
+        assert!((uv_at(0.0, -1.999).1 - 0.9995).abs() < EPSILON);
+        let (u, v) = uv_at(0.6, 0.8);
+        let (u2, v2) = uv_at(-0.6, -0.8);
+        assert!(((u2 - u).rem_euclid(1.0) - 0.5).abs() < EPSILON);
+        assert!((v - 0.5).abs() < EPSILON && (v2 - 0.5).abs() < EPSILON);
+
+        // Stepping by the derivatives moves the coordinates one for one.
+        let rec = disk.hit(&down_at(0.6, 0.8), 0.0, Float::INFINITY).unwrap();
+        let h = 1e-4;
+        for (d, du, dv) in [(rec.dpdu, h, 0.0), (rec.dpdv, 0.0, h)] {
+            let p = rec.p + h * d;
+            let (u2, v2) = uv_at(p.x(), p.y());
+            assert!((u2 - u - du).abs() < 1e-2 * h && (v2 - v - dv).abs() < 1e-2 * h);
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use super::aabb::Aabb;
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use super::stats::count_tests;
+use super::triangle::hit_triangle;
+use crate::material::Material;
+use crate::utils::image::Image;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+// Grid coordinates of a triangle's three vertices.
+type CellTriangle = [(usize, usize); 3];
+
+/// A terrain surface defined by a grid of height samples.
+///
+/// The grid covers `size.x()` by `size.z()` starting at `corner`, with
+/// heights in [0, 1] scaled by `size.y()`. Rays walk the grid cell by cell
+/// and each cell is split into two triangles on the fly, with shading normals
+/// and UVs interpolated across them.
+pub struct Heightfield {
+    nx: usize,
+    nz: usize,
+    corner: Vec3,
+    size: Vec3,
+    heights: Vec<Float>,
+    normals: Vec<Vec3>,
+    // Lowest and highest world-space height in each cell.
+    cell_bounds: Vec<(Float, Float)>,
+    bounds: Aabb,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl Heightfield {
+    /// `heights` holds `nx * nz` samples in [0, 1], row by row along x.
+    pub fn new(
+        nx: usize,
+        nz: usize,
+        heights: Vec<Float>,
+        corner: Vec3,
+        size: Vec3,
+        mat: &Rc<Box<dyn Material>>,
+    ) -> Self {
+        assert!(
+            nx >= 2 && nz >= 2,
+            "a heightfield needs at least 2x2 samples"
+        );
+        assert_eq!(heights.len(), nx * nz);
+
+        let world_height = |i: usize, j: usize| corner.y() + size.y() * heights[j * nx + i];
+
+        let dx = size.x() / (nx - 1) as Float;
+        let dz = size.z() / (nz - 1) as Float;
+        let mut normals = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                // Central differences, one-sided at the borders.
+                let (i0, i1) = (i.saturating_sub(1), (i + 1).min(nx - 1));
+                let (j0, j1) = (j.saturating_sub(1), (j + 1).min(nz - 1));
+                let slope_x =
+                    (world_height(i1, j) - world_height(i0, j)) / ((i1 - i0) as Float * dx);
+                let slope_z =
+                    (world_height(i, j1) - world_height(i, j0)) / ((j1 - j0) as Float * dz);
+                normals.push(Vec3::new(-slope_x, 1.0, -slope_z).unit());
+            }
+        }
+
+        let mut cell_bounds = Vec::with_capacity((nx - 1) * (nz - 1));
+        for j in 0..nz - 1 {
+            for i in 0..nx - 1 {
+                let corners = [
+                    world_height(i, j),
+                    world_height(i + 1, j),
+                    world_height(i, j + 1),
+                    world_height(i + 1, j + 1),
+                ];
+                let low = corners.iter().cloned().fold(Float::INFINITY, Float::min);
+                let high = corners
+                    .iter()
+                    .cloned()
+                    .fold(Float::NEG_INFINITY, Float::max);
+                cell_bounds.push((low, high));
+            }
+        }
+
+        let low = cell_bounds
+            .iter()
+            .map(|b| b.0)
+            .fold(Float::INFINITY, Float::min);
+        let high = cell_bounds
+            .iter()
+            .map(|b| b.1)
+            .fold(Float::NEG_INFINITY, Float::max);
+        let bounds = Aabb::new(
+            Vec3::new(corner.x(), low, corner.z()),
+            Vec3::new(corner.x() + size.x(), high, corner.z() + size.z()),
+        );
+
+        Self {
+            nx,
+            nz,
+            corner,
+            size,
+            heights,
+            normals,
+            cell_bounds,
+            bounds,
+            mat: Rc::clone(mat),
+        }
+    }
+
+    /// Samples `height(u, v)` on an `nx` by `nz` grid, with `u` and `v` in
+    /// [0, 1] along x and z. A procedural noise function plugs in here.
+    pub fn from_fn<F: Fn(Float, Float) -> Float>(
+        nx: usize,
+        nz: usize,
+        height: F,
+        corner: Vec3,
+        size: Vec3,
+        mat: &Rc<Box<dyn Material>>,
+    ) -> Self {
+        let mut heights = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                heights.push(height(
+                    i as Float / (nx - 1) as Float,
+                    j as Float / (nz - 1) as Float,
+                ));
+            }
+        }
+
+        Self::new(nx, nz, heights, corner, size, mat)
+    }
+
+    /// Uses the brightness of each pixel as the height, with the top row of
+    /// the image at the far (`+z`) edge.
+    pub fn from_image(
+        image: &Image,
+        corner: Vec3,
+        size: Vec3,
+        mat: &Rc<Box<dyn Material>>,
+    ) -> Self {
+        let (nx, nz) = (image.width(), image.height());
+        let mut heights = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                // Height maps store plain values, so undo the gamma decoding
+                // applied when the image was read.
+                heights.push(image.get(i, nz - 1 - j).luminance().sqrt());
+            }
+        }
+
+        Self::new(nx, nz, heights, corner, size, mat)
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Vec3 {
+        Vec3::new(
+            self.corner.x() + self.size.x() * i as Float / (self.nx - 1) as Float,
+            self.corner.y() + self.size.y() * self.heights[j * self.nx + i],
+            self.corner.z() + self.size.z() * j as Float / (self.nz - 1) as Float,
+        )
+    }
+
+    // Intersects the two triangles of cell (i, j).
+    fn hit_cell(
+        &self,
+        r: &Ray,
+        i: usize,
+        j: usize,
+        t_min: Float,
+        t_max: Float,
+    ) -> Option<HitRecord> {
+        let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+        let mut closest: Option<(Float, CellTriangle, Float, Float)> = None;
+
+        for tri in [
+            [corners[0], corners[2], corners[1]],
+            [corners[0], corners[3], corners[2]],
+        ] {
+            let limit = closest.map_or(t_max, |c| c.0);
+            let [a, b, c] = tri.map(|(i, j)| self.vertex(i, j));
+            if let Some((t, b1, b2)) = hit_triangle(r, &a, &b, &c, t_min, limit) {
+                closest = Some((t, tri, b1, b2));
+            }
+        }
+
+        let (t, tri, b1, b2) = closest?;
+        let [a, b, c] = tri.map(|(i, j)| self.vertex(i, j));
+        let normal = (b - a).cross(&(c - a)).unit();
+
+        let b0 = 1.0 - b1 - b2;
+        let shading_normal = (b0 * self.normals[tri[0].1 * self.nx + tri[0].0]
+            + b1 * self.normals[tri[1].1 * self.nx + tri[1].0]
+            + b2 * self.normals[tri[2].1 * self.nx + tri[2].0])
+            .unit();
+
+        let p = r.at(t);
+        let u = (p.x() - self.corner.x()) / self.size.x();
+        let v = (p.z() - self.corner.z()) / self.size.z();
+
+        // u and v run along x and z across the whole field, climbing with
+        // the triangle.
+        let dpdu = self.size.x() * Vec3::new(1.0, -normal.x() / normal.y(), 0.0);
+        let dpdv = self.size.z() * Vec3::new(0.0, -normal.z() / normal.y(), 1.0);
+
+        Some(
+            HitRecord::new(p, t, (u, v), r, &normal, &self.mat)
+                .with_shading_normal(&shading_normal)
+                .with_tangents(&dpdu, &dpdv),
+        )
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let (t_enter, t_exit) = self.bounds.hit(r, t_min, t_max)?;
+
+        let cells_x = self.nx - 1;
+        let cells_z = self.nz - 1;
+        let dx = self.size.x() / cells_x as Float;
+        let dz = self.size.z() / cells_z as Float;
+
+        let start = r.at(t_enter);
+        let cell_of = |offset: Float, d: Float, cells: usize| {
+            ((offset / d).floor().max(0.0) as usize).min(cells - 1)
+        };
+        let mut i = cell_of(start.x() - self.corner.x(), dx, cells_x);
+        let mut j = cell_of(start.z() - self.corner.z(), dz, cells_z);
+
+        // 2D DDA: the ray parameter at the next x and z cell boundaries.
+        let dir = r.direction();
+        let setup = |cell: usize, d: Float, origin: Float, corner: Float, delta: Float| {
+            if d > 0.0 {
+                (
+                    1i64,
+                    (corner + (cell + 1) as Float * delta - origin) / d,
+                    delta / d,
+                )
+            } else if d < 0.0 {
+                (
+                    -1i64,
+                    (corner + cell as Float * delta - origin) / d,
+                    -delta / d,
+                )
+            } else {
+                (0i64, Float::INFINITY, Float::INFINITY)
+            }
+        };
+        let (step_x, mut t_next_x, t_delta_x) =
+            setup(i, dir.x(), r.origin().x(), self.corner.x(), dx);
+        let (step_z, mut t_next_z, t_delta_z) =
+            setup(j, dir.z(), r.origin().z(), self.corner.z(), dz);
+
+        let mut t_cell = t_enter;
+        loop {
+            let t_leave = t_next_x.min(t_next_z).min(t_exit);
+
+            // Skip cells the ray passes entirely above or below.
+            count_tests(1);
+            let (low, high) = self.cell_bounds[j * cells_x + i];
+            let (y0, y1) = (r.at(t_cell).y(), r.at(t_leave).y());
+            if y0.min(y1) <= high && y0.max(y1) >= low {
+                if let Some(rec) = self.hit_cell(r, i, j, t_min, t_max) {
+                    return Some(rec);
+                }
+            }
+
+            if t_leave >= t_exit {
+                return None;
+            }
+
+            t_cell = t_leave;
+            if t_next_x < t_next_z {
+                let next = i as i64 + step_x;
+                if next < 0 || next >= cells_x as i64 {
+                    return None;
+                }
+                i = next as usize;
+                t_next_x += t_delta_x;
+            } else {
+                let next = j as i64 + step_z;
+                if next < 0 || next >= cells_z as i64 {
+                    return None;
+                }
+                j = next as usize;
+                t_next_z += t_delta_z;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.00001;
+
+    fn mat() -> Rc<Box<dyn Material>> {
+        Rc::new(Box::new(Lambertian::new(Color::white())))
+    }
+
+    // A ramp rising from 0 at x = 0 to 1 at x = 4, over 4x4 units.
+    fn ramp() -> Heightfield {
+        Heightfield::from_fn(
+            9,
+            5,
+            |u, _| u,
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(4.0, 1.0, 4.0),
+            &mat(),
+        )
+    }
+
+    #[test]
+    fn hit_from_above() {
+        let r = Ray::new(&Vec3::new(2.0, 5.0, 1.3), &Vec3::new(0.0, -1.0, 0.0));
+        let rec = ramp().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 4.5).abs() < EPSILON);
+        assert!((rec.u - 0.5).abs() < EPSILON);
+        assert!((rec.v - 0.325).abs() < EPSILON);
+        let expected = Vec3::new(-0.25, 1.0, 0.0).unit();
+        assert!((rec.normal - expected).length() < EPSILON);
+        assert!((rec.shading_normal - expected).length() < EPSILON);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn hit_grazing_across_cells() {
+        // Travels along +x at height 0.6, meeting the ramp at x = 2.4.
+        let r = Ray::new(&Vec3::new(-1.0, 0.6, 2.2), &Vec3::new(1.0, 0.0, 0.0));
+        let rec = ramp().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.p.x() - 2.4).abs() < EPSILON);
+    }
+
+    #[test]
+    fn miss_outside_grid() {
+        let r = Ray::new(&Vec3::new(5.0, 5.0, 1.0), &Vec3::new(0.0, -1.0, 0.0));
+        assert!(ramp().hit(&r, 0.0, Float::INFINITY).is_none());
+
+        let r = Ray::new(&Vec3::new(-1.0, 1.5, 2.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(ramp().hit(&r, 0.0, Float::INFINITY).is_none());
+    }
+}
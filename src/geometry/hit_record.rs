@@ -1,37 +1,167 @@
 use crate::geometry::ray::Ray;
 use crate::material::Material;
+use crate::utils::onb::Onb;
 use crate::utils::vec3::Vec3;
+use crate::utils::Float;
 use std::rc::Rc;
 
+#[derive(Clone)]
 pub struct HitRecord {
     pub p: Vec3,
+    // Geometric normal, always facing against the incoming ray.
     pub normal: Vec3,
-    pub t: f64,
+    // Interpolated normal used for shading, on the same side as `normal`.
+    // Equal to `normal` unless the surface supplies its own.
+    pub shading_normal: Vec3,
+    // The derivatives `dp/du` and `dp/dv` of the surface, which need not be
+    // unit length or perpendicular. Zero for surfaces without a natural
+    // parametrization.
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
+    pub t: Float,
+    // Surface coordinates of the hit point, usually in [0, 1].
+    pub u: Float,
+    pub v: Float,
     pub front_face: bool,
     pub mat: Rc<Box<dyn Material>>,
+    // The index of the object hit in the outermost `HittableList`, which
+    // tells objects apart. 0 until a list sets it.
+    pub object: usize,
 }
 
 impl HitRecord {
     pub fn new(
         p: Vec3,
-        t: f64,
+        t: Float,
+        (u, v): (Float, Float),
         r: &Ray,
         outward_normal: &Vec3,
         mat: &Rc<Box<dyn Material>>,
     ) -> Self {
         let front_face = r.direction().dot(outward_normal) < 0.0;
         let normal = if front_face {
-            outward_normal.clone()
+            *outward_normal
         } else {
-            -outward_normal.clone()
+            -*outward_normal
         };
 
         Self {
             p,
             normal,
+            shading_normal: normal,
+            dpdu: Vec3::new(0.0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, 0.0),
             t,
+            u,
+            v,
             front_face,
             mat: Rc::clone(mat),
+            object: 0,
         }
     }
+
+    /// Replaces the shading normal with an outward-facing one, flipping it to
+    /// the side of the geometric normal.
+    pub fn with_shading_normal(mut self, outward_normal: &Vec3) -> Self {
+        self.shading_normal = if self.front_face {
+            *outward_normal
+        } else {
+            -*outward_normal
+        };
+        self
+    }
+
+    /// Sets the surface derivatives `dp/du` and `dp/dv`, which need not be
+    /// unit length or perpendicular.
+    pub fn with_tangents(mut self, dpdu: &Vec3, dpdv: &Vec3) -> Self {
+        self.dpdu = *dpdu;
+        self.dpdv = *dpdv;
+        self
+    }
+
+    /// Unit vectors perpendicular to `shading_normal` along which `u` and `v`
+    /// increase. Built on demand, since only some materials need them.
+    ///
+    /// The derivatives are made orthonormal against the shading normal,
+    /// falling back to an arbitrary frame where they degenerate, e.g. at the
+    /// poles of a sphere or on surfaces without a parametrization.
+    pub fn tangents(&self) -> (Vec3, Vec3) {
+        let n = self.shading_normal;
+
+        let tangent = self.dpdu - n.dot(&self.dpdu) * n;
+        let tangent = if tangent.near_zero() {
+            *Onb::from_w(&n).u()
+        } else {
+            tangent.unit()
+        };
+
+        let bitangent = self.dpdv - n.dot(&self.dpdv) * n - tangent.dot(&self.dpdv) * tangent;
+        let bitangent = if bitangent.near_zero() {
+            n.cross(&tangent)
+        } else {
+            bitangent.unit()
+        };
+
+        (tangent, bitangent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.00001;
+
+    fn record(outward_normal: &Vec3) -> HitRecord {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+        HitRecord::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            5.0,
+            (0.0, 0.0),
+            &r,
+            outward_normal,
+            &mat,
+        )
+    }
+
+    fn assert_orthonormal(rec: &HitRecord) {
+        let n = rec.shading_normal;
+        let (tangent, bitangent) = rec.tangents();
+        for (a, b) in [(n, tangent), (n, bitangent), (tangent, bitangent)] {
+            assert!(a.dot(&b).abs() < EPSILON);
+        }
+        for v in [n, tangent, bitangent] {
+            assert!((v.length() - 1.0).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn default_frame_is_orthonormal() {
+        assert_orthonormal(&record(&Vec3::new(0.3, -0.2, 1.0).unit()));
+        assert_orthonormal(&record(&Vec3::new(0.0, 0.0, -1.0)));
+    }
+
+    #[test]
+    fn tangents_follow_derivatives() {
+        let rec = record(&Vec3::new(0.0, 0.0, 1.0))
+            .with_tangents(&Vec3::new(2.0, 0.0, 0.5), &Vec3::new(1.0, 3.0, 0.0));
+
+        let (tangent, bitangent) = rec.tangents();
+        assert!((tangent - Vec3::new(1.0, 0.0, 0.0)).length() < EPSILON);
+        assert!((bitangent - Vec3::new(0.0, 1.0, 0.0)).length() < EPSILON);
+
+        // Tilting the shading normal keeps the frame orthonormal.
+        let rec = rec.with_shading_normal(&Vec3::new(0.6, 0.0, 0.8));
+        assert_orthonormal(&rec);
+        let (tangent, bitangent) = rec.tangents();
+        assert!(tangent.x() > 0.0 && bitangent.y() > 0.0);
+
+        // Degenerate derivatives fall back to some valid frame.
+        let rec = record(&Vec3::new(0.0, 0.0, 1.0))
+            .with_tangents(&Vec3::new(0.0, 0.0, 1.0), &Vec3::new(0.0, 0.0, 0.0));
+        assert_orthonormal(&rec);
+    }
 }
@@ -1,6 +1,7 @@
-use super::ray::Ray;
 use super::hit_record::HitRecord;
+use super::ray::Ray;
+use crate::utils::Float;
 
 pub trait Hittable {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord>;
 }
@@ -1,6 +1,8 @@
 use super::hit_record::HitRecord;
 use super::hittable::Hittable;
 use super::ray::Ray;
+use super::stats::count_tests;
+use crate::utils::Float;
 
 pub struct HittableList {
     objects: Vec<Box<dyn Hittable>>,
@@ -22,14 +24,22 @@ impl HittableList {
     }
 }
 
+impl Default for HittableList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Hittable for HittableList {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
         let mut temp_rec = None;
         let mut closest_so_far = t_max;
 
-        for object in self.objects.iter() {
-            if let Some(rec) = object.hit(r, t_min, closest_so_far) {
+        count_tests(self.objects.len());
+        for (index, object) in self.objects.iter().enumerate() {
+            if let Some(mut rec) = object.hit(r, t_min, closest_so_far) {
                 closest_so_far = rec.t;
+                rec.object = index;
                 temp_rec = Some(rec);
             }
         }
@@ -0,0 +1,118 @@
+use super::aabb::Aabb;
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use crate::material::Material;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// The surface `f(p) = 0` of an arbitrary function inside `bounds`, with `f`
+/// negative inside.
+///
+/// Rays are sampled every `step` units of distance and refined by bisection
+/// on a sign change, so features thinner than `step` may be missed. Normals
+/// come from the gradient of `f`.
+pub struct ImplicitSurface<F> {
+    f: F,
+    bounds: Aabb,
+    step: Float,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl<F: Fn(&Vec3) -> Float> ImplicitSurface<F> {
+    pub fn new(f: F, bounds: Aabb, step: Float, mat: &Rc<Box<dyn Material>>) -> Self {
+        Self {
+            f,
+            bounds,
+            step,
+            mat: Rc::clone(mat),
+        }
+    }
+
+    fn gradient(&self, p: &Vec3) -> Vec3 {
+        let h = 1e-4 * self.step.max(1.0);
+        let axes = [
+            Vec3::new(h, 0.0, 0.0),
+            Vec3::new(0.0, h, 0.0),
+            Vec3::new(0.0, 0.0, h),
+        ];
+        let [gx, gy, gz] = axes.map(|a| (self.f)(&(*p + a)) - (self.f)(&(*p - a)));
+
+        Vec3::new(gx, gy, gz)
+    }
+}
+
+impl<F: Fn(&Vec3) -> Float> Hittable for ImplicitSurface<F> {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let (t_start, t_end) = self.bounds.hit(r, t_min, t_max)?;
+        let dt = self.step / r.direction().length();
+
+        let mut t0 = t_start;
+        let mut f0 = (self.f)(&r.at(t0));
+        while t0 < t_end {
+            let t1 = (t0 + dt).min(t_end);
+            let f1 = (self.f)(&r.at(t1));
+
+            if (f0 < 0.0) != (f1 < 0.0) {
+                // Bisect the bracket down to the root.
+                let (mut lo, mut hi) = (t0, t1);
+                for _ in 0..40 {
+                    let mid = 0.5 * (lo + hi);
+                    if ((self.f)(&r.at(mid)) < 0.0) == (f0 < 0.0) {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+
+                let t = 0.5 * (lo + hi);
+                if t > t_min {
+                    let p = r.at(t);
+                    let outward_normal = self.gradient(&p).unit();
+                    return Some(HitRecord::new(
+                        p,
+                        t,
+                        (0.0, 0.0),
+                        r,
+                        &outward_normal,
+                        &self.mat,
+                    ));
+                }
+            }
+
+            t0 = t1;
+            f0 = f1;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn hit_sphere() {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        let surface = ImplicitSurface::new(
+            |p: &Vec3| p.length_squared() - 1.0,
+            Aabb::new(Vec3::new(-2.0, -2.0, -2.0), Vec3::new(2.0, 2.0, 2.0)),
+            0.1,
+            &mat,
+        );
+
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+        let rec = surface.hit(&r, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 4.0).abs() < EPSILON);
+        assert!((rec.normal.z() - 1.0).abs() < EPSILON);
+
+        let r = Ray::new(&Vec3::new(0.0, 1.5, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+        assert!(surface.hit(&r, 0.0, Float::INFINITY).is_none());
+    }
+}
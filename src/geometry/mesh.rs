@@ -0,0 +1,346 @@
+use super::aabb::Aabb;
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::polygon_mesh::PolygonMesh;
+use super::ray::Ray;
+use super::stats::count_tests;
+use super::triangle::hit_triangle;
+use crate::material::Material;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+// Triangles per leaf of the acceleration tree.
+const LEAF_SIZE: usize = 4;
+
+enum Node {
+    Leaf {
+        bounds: Aabb,
+        start: usize,
+        end: usize,
+    },
+    Inner {
+        bounds: Aabb,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Node {
+    fn bounds(&self) -> &Aabb {
+        match self {
+            Node::Leaf { bounds, .. } | Node::Inner { bounds, .. } => bounds,
+        }
+    }
+}
+
+/// A smooth-shaded triangle mesh.
+///
+/// Each vertex gets a normal averaged from the faces around it, weighted by
+/// area, and hits interpolate these into `HitRecord::shading_normal` while
+/// `HitRecord::normal` stays the flat face normal. The front faces are the
+/// counter-clockwise ones. `(u, v)` are interpolated from texture
+/// coordinates at the corners of each triangle, if the mesh has them, and are
+/// otherwise the barycentric weights of the second and third vertex of the
+/// triangle that was hit.
+pub struct Mesh {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    // Sorted so that every leaf of `nodes` covers a contiguous range.
+    triangles: Vec<Face>,
+    nodes: Vec<Node>,
+    mat: Rc<Box<dyn Material>>,
+}
+
+#[derive(Copy, Clone)]
+struct Face {
+    vertices: [usize; 3],
+    uvs: [(Float, Float); 3],
+}
+
+// Texture coordinates that make `(u, v)` the barycentric weights.
+const BARYCENTRIC_UVS: [(Float, Float); 3] = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+
+impl Mesh {
+    pub fn new(
+        positions: Vec<Vec3>,
+        triangles: Vec<[usize; 3]>,
+        mat: &Rc<Box<dyn Material>>,
+    ) -> Self {
+        let uvs = vec![BARYCENTRIC_UVS; triangles.len()];
+        Self::textured(positions, triangles, uvs, mat)
+    }
+
+    /// A mesh whose triangles have the texture coordinates `uvs` at their
+    /// vertices, in the same order.
+    pub fn textured(
+        positions: Vec<Vec3>,
+        triangles: Vec<[usize; 3]>,
+        uvs: Vec<[(Float, Float); 3]>,
+        mat: &Rc<Box<dyn Material>>,
+    ) -> Self {
+        assert!(!triangles.is_empty(), "a mesh needs at least one triangle");
+        assert_eq!(uvs.len(), triangles.len(), "one set of uvs per triangle");
+
+        let mut normals = vec![Vec3::new(0.0, 0.0, 0.0); positions.len()];
+        for tri in &triangles {
+            let [a, b, c] = tri.map(|i| positions[i]);
+            // The cross product's length is twice the area.
+            let n = (b - a).cross(&(c - a));
+            for &i in tri {
+                normals[i] += n;
+            }
+        }
+        for n in normals.iter_mut() {
+            if !n.near_zero() {
+                *n = n.unit();
+            }
+        }
+
+        let mut mesh = Self {
+            positions,
+            normals,
+            triangles: triangles
+                .into_iter()
+                .zip(uvs)
+                .map(|(vertices, uvs)| Face { vertices, uvs })
+                .collect(),
+            nodes: Vec::new(),
+            mat: Rc::clone(mat),
+        };
+        let len = mesh.triangles.len();
+        mesh.build(0, len);
+
+        mesh
+    }
+
+    /// Triangulates a polygon mesh, e.g. one loaded from OBJ and subdivided,
+    /// keeping its texture coordinates if it has them.
+    pub fn from_polygons(polygons: &PolygonMesh, mat: &Rc<Box<dyn Material>>) -> Self {
+        let triangulated = polygons.triangulate();
+        let triangles = triangulated
+            .faces()
+            .iter()
+            .map(|f| [f[0], f[1], f[2]])
+            .collect();
+        let positions = triangulated.positions().to_vec();
+
+        if triangulated.uvs().is_empty() {
+            Self::new(positions, triangles, mat)
+        } else {
+            let uvs = triangulated
+                .uvs()
+                .iter()
+                .map(|f| [f[0], f[1], f[2]])
+                .collect();
+            Self::textured(positions, triangles, uvs, mat)
+        }
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    fn triangle_bounds(&self, tri: &[usize; 3]) -> Aabb {
+        let [a, b, c] = tri.map(|i| self.positions[i]);
+        Aabb::surrounding(&Aabb::new(a, b), &Aabb::new(c, c))
+    }
+
+    // Builds the subtree over `triangles[start..end]`, splitting at the median
+    // centroid along the longest axis, and returns its node index.
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let bounds = self.triangles[start..end]
+            .iter()
+            .map(|face| self.triangle_bounds(&face.vertices))
+            .reduce(|a, b| Aabb::surrounding(&a, &b))
+            .unwrap();
+
+        if end - start <= LEAF_SIZE {
+            self.nodes.push(Node::Leaf { bounds, start, end });
+            return self.nodes.len() - 1;
+        }
+
+        let extent = *bounds.max() - *bounds.min();
+        let axis = if extent.x() > extent.y() && extent.x() > extent.z() {
+            0
+        } else if extent.y() > extent.z() {
+            1
+        } else {
+            2
+        };
+        let key = |mesh: &Self, face: &Face| {
+            let c = mesh.triangle_bounds(&face.vertices).centroid();
+            [c.x(), c.y(), c.z()][axis]
+        };
+
+        let mid = (start + end) / 2;
+        let mut slice = std::mem::take(&mut self.triangles);
+        slice[start..end].select_nth_unstable_by(mid - start, |a, b| {
+            key(self, a).partial_cmp(&key(self, b)).unwrap()
+        });
+        self.triangles = slice;
+
+        let left = self.build(start, mid);
+        let right = self.build(mid, end);
+        self.nodes.push(Node::Inner {
+            bounds,
+            left,
+            right,
+        });
+        self.nodes.len() - 1
+    }
+}
+
+impl Hittable for Mesh {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let mut closest: Option<(Float, usize, Float, Float)> = None;
+        let mut stack = vec![self.nodes.len() - 1];
+
+        while let Some(index) = stack.pop() {
+            let limit = closest.map_or(t_max, |c| c.0);
+            let node = &self.nodes[index];
+            count_tests(1);
+            if node.bounds().hit(r, t_min, limit).is_none() {
+                continue;
+            }
+
+            match *node {
+                Node::Leaf { start, end, .. } => {
+                    count_tests(end - start);
+                    for i in start..end {
+                        let limit = closest.map_or(t_max, |c| c.0);
+                        let [a, b, c] = self.triangles[i].vertices.map(|v| self.positions[v]);
+                        if let Some((t, b1, b2)) = hit_triangle(r, &a, &b, &c, t_min, limit) {
+                            closest = Some((t, i, b1, b2));
+                        }
+                    }
+                }
+                Node::Inner { left, right, .. } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+
+        let (t, i, b1, b2) = closest?;
+        let face = self.triangles[i];
+        let tri = face.vertices;
+        let [a, b, c] = tri.map(|v| self.positions[v]);
+        let normal = (b - a).cross(&(c - a)).unit();
+
+        let [na, nb, nc] = tri.map(|v| self.normals[v]);
+        let mut shading_normal = (1.0 - b1 - b2) * na + b1 * nb + b2 * nc;
+        shading_normal = if shading_normal.near_zero() {
+            normal
+        } else {
+            shading_normal.unit()
+        };
+
+        let [uva, uvb, uvc] = face.uvs;
+        let uv = (
+            (1.0 - b1 - b2) * uva.0 + b1 * uvb.0 + b2 * uvc.0,
+            (1.0 - b1 - b2) * uva.1 + b1 * uvb.1 + b2 * uvc.1,
+        );
+
+        // Solve the edges for the derivatives along u and v. The edges
+        // themselves serve where the texture coordinates are degenerate.
+        let (e1, e2) = (b - a, c - a);
+        let (du1, dv1) = (uvb.0 - uva.0, uvb.1 - uva.1);
+        let (du2, dv2) = (uvc.0 - uva.0, uvc.1 - uva.1);
+        let det = du1 * dv2 - dv1 * du2;
+        let (dpdu, dpdv) = if det.abs() < 1e-12 {
+            (e1, e2)
+        } else {
+            ((dv2 * e1 - dv1 * e2) / det, (du1 * e2 - du2 * e1) / det)
+        };
+
+        Some(
+            HitRecord::new(r.at(t), t, uv, r, &normal, &self.mat)
+                .with_shading_normal(&shading_normal)
+                .with_tangents(&dpdu, &dpdv),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.00001;
+
+    fn mat() -> Rc<Box<dyn Material>> {
+        Rc::new(Box::new(Lambertian::new(Color::white())))
+    }
+
+    // A roof of two faces meeting at a ridge along z, open at the ends.
+    fn roof() -> Mesh {
+        let positions = vec![
+            Vec3::new(-1.0, 0.0, -1.0),
+            Vec3::new(-1.0, 0.0, 1.0),
+            Vec3::new(0.0, 1.0, -1.0),
+            Vec3::new(0.0, 1.0, 1.0),
+            Vec3::new(1.0, 0.0, -1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+        ];
+        let triangles = vec![[0, 1, 3], [0, 3, 2], [3, 5, 4], [3, 4, 2]];
+        Mesh::new(positions, triangles, &mat())
+    }
+
+    #[test]
+    fn shading_normal_is_interpolated() {
+        let r = Ray::new(&Vec3::new(-0.25, 5.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let rec = roof().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 4.25).abs() < EPSILON);
+        let face = Vec3::new(-1.0, 1.0, 0.0).unit();
+        assert!((rec.normal - face).length() < EPSILON);
+
+        // Halfway between the eaves (face normal) and the ridge (straight up).
+        let expected = (0.25 * face + 0.75 * Vec3::new(0.0, 1.0, 0.0)).unit();
+        assert!((rec.shading_normal - expected).length() < EPSILON);
+    }
+
+    #[test]
+    fn shading_normal_follows_geometric_side() {
+        let r = Ray::new(&Vec3::new(-0.25, -5.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        let rec = roof().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!(!rec.front_face);
+        assert!(rec.normal.y() < 0.0);
+        assert!(rec.shading_normal.y() < 0.0);
+    }
+
+    #[test]
+    fn hit_many_triangles() {
+        // A 20x20 grid, enough to need several levels of the tree.
+        let n = 20;
+        let mut positions = Vec::new();
+        for j in 0..=n {
+            for i in 0..=n {
+                positions.push(Vec3::new(i as Float, 0.0, -(j as Float)));
+            }
+        }
+        let mut triangles = Vec::new();
+        for j in 0..n {
+            for i in 0..n {
+                let v = j * (n + 1) + i;
+                triangles.push([v, v + 1, v + n + 2]);
+                triangles.push([v, v + n + 2, v + n + 1]);
+            }
+        }
+        let mesh = Mesh::new(positions, triangles, &mat());
+
+        for &(x, z) in &[(0.5, -0.5), (13.3, -7.9), (19.9, -19.1)] {
+            let r = Ray::new(&Vec3::new(x, 1.0, z), &Vec3::new(0.0, -1.0, 0.0));
+            let rec = mesh.hit(&r, 0.0, Float::INFINITY).unwrap();
+            assert!((rec.p.x() - x).abs() < EPSILON);
+            assert!((rec.p.z() - z).abs() < EPSILON);
+            assert!((rec.shading_normal.y() - 1.0).abs() < EPSILON);
+        }
+
+        let r = Ray::new(&Vec3::new(20.5, 1.0, -3.0), &Vec3::new(0.0, -1.0, 0.0));
+        assert!(mesh.hit(&r, 0.0, Float::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,135 @@
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use crate::material::Material;
+use crate::utils::onb::Onb;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// An infinite plane through `point`. UVs repeat every unit of distance
+/// along the plane.
+pub struct Plane {
+    point: Vec3,
+    normal: Vec3,
+    onb: Onb,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl Plane {
+    pub fn new(point: Vec3, normal: Vec3, mat: &Rc<Box<dyn Material>>) -> Self {
+        let onb = Onb::from_w(&normal);
+
+        Self {
+            point,
+            normal: *onb.w(),
+            onb,
+            mat: Rc::clone(mat),
+        }
+    }
+}
+
+/// Intersects `r` with the plane through `point` with the given unit normal.
+pub(super) fn hit_plane(
+    r: &Ray,
+    point: &Vec3,
+    normal: &Vec3,
+    t_min: Float,
+    t_max: Float,
+) -> Option<Float> {
+    let denom = normal.dot(r.direction());
+
+    // No hit if the ray is parallel to the plane.
+    if denom.abs() < 1e-8 {
+        return None;
+    }
+
+    let t = (*point - *r.origin()).dot(normal) / denom;
+    if t < t_min || t_max < t {
+        None
+    } else {
+        Some(t)
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let t = hit_plane(r, &self.point, &self.normal, t_min, t_max)?;
+        let p = r.at(t);
+        let local = self.onb.to_local(&(p - self.point));
+        let uv = (local.x().rem_euclid(1.0), local.y().rem_euclid(1.0));
+
+        Some(
+            HitRecord::new(p, t, uv, r, &self.normal, &self.mat)
+                .with_tangents(self.onb.u(), self.onb.v()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.00001;
+
+    fn plane() -> Plane {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        Plane::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 2.0, 0.0), &mat)
+    }
+
+    #[test]
+    fn hit_front() {
+        let r = Ray::new(&Vec3::new(0.3, 5.0, 0.7), &Vec3::new(0.0, -2.0, 0.0));
+        let rec = plane().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 2.0).abs() < EPSILON);
+        assert!((rec.p - Vec3::new(0.3, 1.0, 0.7)).length() < EPSILON);
+        assert!((rec.normal - Vec3::new(0.0, 1.0, 0.0)).length() < EPSILON);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn hit_back() {
+        let r = Ray::new(&Vec3::new(0.0, -1.0, 0.0), &Vec3::new(1.0, 1.0, 0.0));
+        let rec = plane().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 2.0).abs() < EPSILON);
+        assert!((rec.normal - Vec3::new(0.0, -1.0, 0.0)).length() < EPSILON);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn misses() {
+        // Parallel to the plane, heading away from it, and out of range.
+        let r = Ray::new(&Vec3::new(0.0, 2.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(plane().hit(&r, 0.0, Float::INFINITY).is_none());
+        let r = Ray::new(&Vec3::new(0.0, 2.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        assert!(plane().hit(&r, 0.0, Float::INFINITY).is_none());
+        let r = Ray::new(&Vec3::new(0.0, 2.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        assert!(plane().hit(&r, 0.0, 0.5).is_none());
+    }
+
+    #[test]
+    fn uv_repeats_every_unit() {
+        let plane = plane();
+        let uv_at = |p: Vec3| {
+            let r = Ray::new(&(p + Vec3::new(0.0, 1.0, 0.0)), &Vec3::new(0.0, -1.0, 0.0));
+            let rec = plane.hit(&r, 0.0, Float::INFINITY).unwrap();
+            (rec.u, rec.v)
+        };
+
+        let start = Vec3::new(0.1, 1.0, 0.2);
+        let (u, v) = uv_at(start);
+        assert!((0.0..1.0).contains(&u) && (0.0..1.0).contains(&v));
+
+        // A quarter step along the tangent moves u a quarter, wrapping
+        // around, and a whole step comes back to the same place.
+        let (u2, v2) = uv_at(start + 0.25 * *plane.onb.u());
+        assert!(((u2 - u).rem_euclid(1.0) - 0.25).abs() < EPSILON);
+        assert!((v2 - v).abs() < EPSILON);
+        let (u2, v2) = uv_at(start + 3.0 * *plane.onb.u() - 2.0 * *plane.onb.v());
+        assert!((u2 - u).abs() < EPSILON && (v2 - v).abs() < EPSILON);
+    }
+}
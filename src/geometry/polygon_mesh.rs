@@ -0,0 +1,597 @@
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+
+/// An editable mesh of polygons, used to load and subdivide geometry before
+/// turning it into a `Mesh` for rendering.
+///
+/// Faces list vertex indices counter-clockwise when seen from the front.
+/// Faces may carry texture coordinates for each of their corners, which
+/// triangulation and subdivision keep. Subdivision interpolates them linearly
+/// within each face, so seams between faces stay sharp while the positions
+/// are smoothed.
+#[derive(Clone, Debug, Default)]
+pub struct PolygonMesh {
+    positions: Vec<Vec3>,
+    faces: Vec<Vec<usize>>,
+    // The texture coordinates of each corner of each face, or empty.
+    uvs: Vec<Vec<(Float, Float)>>,
+}
+
+// What a subdivision step knows about an edge, keyed by its sorted vertex pair.
+struct Edge {
+    // Index of the edge's new vertex.
+    point: usize,
+    faces: Vec<usize>,
+}
+
+impl PolygonMesh {
+    pub fn new(positions: Vec<Vec3>, faces: Vec<Vec<usize>>) -> Self {
+        for face in &faces {
+            assert!(face.len() >= 3, "a face needs at least three vertices");
+            assert!(face.iter().all(|&i| i < positions.len()));
+        }
+
+        Self {
+            positions,
+            faces,
+            uvs: Vec::new(),
+        }
+    }
+
+    /// Sets the texture coordinates of the corners of each face, in the
+    /// order of its vertices.
+    pub fn with_uvs(mut self, uvs: Vec<Vec<(Float, Float)>>) -> Self {
+        assert_eq!(uvs.len(), self.faces.len(), "one set of uvs per face");
+        for (face, uv) in self.faces.iter().zip(uvs.iter()) {
+            assert_eq!(face.len(), uv.len(), "one uv per corner");
+        }
+        self.uvs = uvs;
+        self
+    }
+
+    /// Reads the vertices, texture coordinates and faces of a Wavefront OBJ
+    /// file. Texture coordinates are kept only if every face has them.
+    /// Normals and all other statements are ignored.
+    pub fn read_obj<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut positions = Vec::new();
+        let mut texcoords = Vec::new();
+        let mut faces = Vec::new();
+        let mut uvs = Vec::new();
+        let mut textured = true;
+
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("v") => {
+                    let mut coords = [0.0; 3];
+                    for c in coords.iter_mut() {
+                        *c = tokens
+                            .next()
+                            .and_then(|t| t.parse::<Float>().ok())
+                            .ok_or_else(|| invalid_data(number, "bad vertex"))?;
+                    }
+                    positions.push(Vec3::new(coords[0], coords[1], coords[2]));
+                }
+                Some("vt") => {
+                    // The optional third coordinate is ignored.
+                    let mut coords = [0.0; 2];
+                    for c in coords.iter_mut() {
+                        *c = tokens
+                            .next()
+                            .and_then(|t| t.parse::<Float>().ok())
+                            .ok_or_else(|| invalid_data(number, "bad texture coordinate"))?;
+                    }
+                    texcoords.push((coords[0], coords[1]));
+                }
+                Some("f") => {
+                    let mut face = Vec::new();
+                    let mut face_uvs = Vec::new();
+                    for token in tokens {
+                        // The position and texture coordinate indices of
+                        // `v/vt/vn`; the normal is not used.
+                        let mut indices = token.split('/');
+                        let index = indices
+                            .next()
+                            .and_then(|t| t.parse::<i64>().ok())
+                            .ok_or_else(|| invalid_data(number, "bad face index"))?;
+                        face.push(resolve_index(index, positions.len(), number)?);
+
+                        match indices.next().filter(|t| !t.is_empty()) {
+                            Some(t) => {
+                                let index = t
+                                    .parse::<i64>()
+                                    .map_err(|_| invalid_data(number, "bad face index"))?;
+                                face_uvs.push(
+                                    texcoords[resolve_index(index, texcoords.len(), number)?],
+                                );
+                            }
+                            None => textured = false,
+                        }
+                    }
+
+                    if face.len() < 3 {
+                        return Err(invalid_data(number, "face with fewer than three vertices"));
+                    }
+                    faces.push(face);
+                    uvs.push(face_uvs);
+                }
+                _ => {}
+            }
+        }
+
+        if !textured {
+            uvs.clear();
+        }
+        Ok(Self {
+            positions,
+            faces,
+            uvs,
+        })
+    }
+
+    pub fn positions(&self) -> &[Vec3] {
+        &self.positions
+    }
+
+    pub fn faces(&self) -> &[Vec<usize>] {
+        &self.faces
+    }
+
+    /// The texture coordinates of the corners of each face, or an empty
+    /// slice if the faces have none.
+    pub fn uvs(&self) -> &[Vec<(Float, Float)>] {
+        &self.uvs
+    }
+
+    /// Splits every face into a fan of triangles.
+    pub fn triangulate(&self) -> Self {
+        let faces = self
+            .faces
+            .iter()
+            .flat_map(|f| (1..f.len() - 1).map(move |i| vec![f[0], f[i], f[i + 1]]))
+            .collect();
+        let uvs = self
+            .uvs
+            .iter()
+            .flat_map(|f| (1..f.len() - 1).map(move |i| vec![f[0], f[i], f[i + 1]]))
+            .collect();
+
+        Self {
+            positions: self.positions.clone(),
+            faces,
+            uvs,
+        }
+    }
+
+    /// Applies `levels` steps of Loop subdivision, triangulating first. Each
+    /// step splits every triangle into four.
+    pub fn loop_subdivide(&self, levels: u32) -> Self {
+        let mut mesh = self.triangulate();
+        for _ in 0..levels {
+            mesh = mesh.loop_step();
+        }
+        mesh
+    }
+
+    /// Applies `levels` steps of Catmull-Clark subdivision. Each step splits
+    /// every n-sided face into n quads.
+    pub fn catmull_clark(&self, levels: u32) -> Self {
+        let mut mesh = self.clone();
+        for _ in 0..levels {
+            mesh = mesh.catmull_clark_step();
+        }
+        mesh
+    }
+
+    // Numbers the edges after the existing vertices and records the faces
+    // on each side. Edges with a single face lie on the boundary.
+    fn edges(&self) -> HashMap<(usize, usize), Edge> {
+        let mut edges: HashMap<(usize, usize), Edge> = HashMap::new();
+        for (f, face) in self.faces.iter().enumerate() {
+            for (i, &a) in face.iter().enumerate() {
+                let b = face[(i + 1) % face.len()];
+                let next = self.positions.len() + edges.len();
+                edges
+                    .entry(edge_key(a, b))
+                    .or_insert(Edge {
+                        point: next,
+                        faces: Vec::new(),
+                    })
+                    .faces
+                    .push(f);
+            }
+        }
+        edges
+    }
+
+    // For each vertex, its neighbors across edges, and whether it sits on
+    // the boundary along with its two boundary neighbors.
+    fn neighbors(
+        &self,
+        edges: &HashMap<(usize, usize), Edge>,
+    ) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+        let mut all = vec![Vec::new(); self.positions.len()];
+        let mut boundary = vec![Vec::new(); self.positions.len()];
+        for (&(a, b), edge) in edges {
+            all[a].push(b);
+            all[b].push(a);
+            if edge.faces.len() == 1 {
+                boundary[a].push(b);
+                boundary[b].push(a);
+            }
+        }
+        (all, boundary)
+    }
+
+    fn loop_step(&self) -> Self {
+        let edges = self.edges();
+        let (neighbors, boundary) = self.neighbors(&edges);
+        let mut positions = vec![Vec3::new(0.0, 0.0, 0.0); self.positions.len() + edges.len()];
+
+        for (v, p) in self.positions.iter().enumerate() {
+            positions[v] = if boundary[v].len() == 2 {
+                0.75 * *p
+                    + 0.125 * (self.positions[boundary[v][0]] + self.positions[boundary[v][1]])
+            } else if !boundary[v].is_empty() {
+                // Non-manifold corners stay put.
+                *p
+            } else {
+                let n = neighbors[v].len();
+                let beta = if n == 3 {
+                    3.0 / 16.0
+                } else {
+                    3.0 / (8.0 * n as Float)
+                };
+                let sum = neighbors[v]
+                    .iter()
+                    .fold(Vec3::new(0.0, 0.0, 0.0), |acc, &u| acc + self.positions[u]);
+                (1.0 - n as Float * beta) * *p + beta * sum
+            };
+        }
+
+        for (&(a, b), edge) in &edges {
+            let ends = self.positions[a] + self.positions[b];
+            positions[edge.point] = if edge.faces.len() == 2 {
+                let opposite = |f: usize| {
+                    let face = &self.faces[f];
+                    face.iter().find(|&&v| v != a && v != b).copied().unwrap()
+                };
+                0.375 * ends
+                    + 0.125
+                        * (self.positions[opposite(edge.faces[0])]
+                            + self.positions[opposite(edge.faces[1])])
+            } else {
+                0.5 * ends
+            };
+        }
+
+        let mut faces = Vec::with_capacity(4 * self.faces.len());
+        for face in &self.faces {
+            let (a, b, c) = (face[0], face[1], face[2]);
+            let ab = edges[&edge_key(a, b)].point;
+            let bc = edges[&edge_key(b, c)].point;
+            let ca = edges[&edge_key(c, a)].point;
+            faces.push(vec![a, ab, ca]);
+            faces.push(vec![ab, b, bc]);
+            faces.push(vec![ca, bc, c]);
+            faces.push(vec![ab, bc, ca]);
+        }
+
+        let mut uvs = Vec::with_capacity(4 * self.uvs.len());
+        for uv in &self.uvs {
+            let (a, b, c) = (uv[0], uv[1], uv[2]);
+            let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+            uvs.push(vec![a, ab, ca]);
+            uvs.push(vec![ab, b, bc]);
+            uvs.push(vec![ca, bc, c]);
+            uvs.push(vec![ab, bc, ca]);
+        }
+
+        Self {
+            positions,
+            faces,
+            uvs,
+        }
+    }
+
+    fn catmull_clark_step(&self) -> Self {
+        let edges = self.edges();
+        let (neighbors, boundary) = self.neighbors(&edges);
+        let face_start = self.positions.len() + edges.len();
+        let mut positions = vec![Vec3::new(0.0, 0.0, 0.0); face_start + self.faces.len()];
+
+        for (f, face) in self.faces.iter().enumerate() {
+            let sum = face
+                .iter()
+                .fold(Vec3::new(0.0, 0.0, 0.0), |acc, &v| acc + self.positions[v]);
+            positions[face_start + f] = sum / face.len() as Float;
+        }
+
+        for (&(a, b), edge) in &edges {
+            let ends = self.positions[a] + self.positions[b];
+            positions[edge.point] = if edge.faces.len() == 2 {
+                0.25 * (ends
+                    + positions[face_start + edge.faces[0]]
+                    + positions[face_start + edge.faces[1]])
+            } else {
+                0.5 * ends
+            };
+        }
+
+        // Faces around each vertex, for averaging their points.
+        let mut adjacent = vec![Vec::new(); self.positions.len()];
+        for (f, face) in self.faces.iter().enumerate() {
+            for &v in face {
+                adjacent[v].push(f);
+            }
+        }
+
+        for (v, p) in self.positions.iter().enumerate() {
+            positions[v] = if boundary[v].len() == 2 {
+                0.75 * *p
+                    + 0.125 * (self.positions[boundary[v][0]] + self.positions[boundary[v][1]])
+            } else if !boundary[v].is_empty() || adjacent[v].is_empty() {
+                *p
+            } else {
+                let n = neighbors[v].len() as Float;
+                let face_average = adjacent[v]
+                    .iter()
+                    .fold(Vec3::new(0.0, 0.0, 0.0), |acc, &f| {
+                        acc + positions[face_start + f]
+                    })
+                    / adjacent[v].len() as Float;
+                let edge_average = neighbors[v]
+                    .iter()
+                    .fold(Vec3::new(0.0, 0.0, 0.0), |acc, &u| {
+                        acc + 0.5 * (*p + self.positions[u])
+                    })
+                    / n;
+                (face_average + 2.0 * edge_average + (n - 3.0) * *p) / n
+            };
+        }
+
+        let mut faces = Vec::new();
+        for (f, face) in self.faces.iter().enumerate() {
+            let k = face.len();
+            for i in 0..k {
+                let prev = face[(i + k - 1) % k];
+                let v = face[i];
+                let next = face[(i + 1) % k];
+                faces.push(vec![
+                    v,
+                    edges[&edge_key(v, next)].point,
+                    face_start + f,
+                    edges[&edge_key(prev, v)].point,
+                ]);
+            }
+        }
+
+        let mut uvs = Vec::new();
+        for uv in &self.uvs {
+            let k = uv.len();
+            let sum = uv
+                .iter()
+                .fold((0.0, 0.0), |acc, &(u, v)| (acc.0 + u, acc.1 + v));
+            let center = (sum.0 / k as Float, sum.1 / k as Float);
+            for i in 0..k {
+                let (prev, next) = (uv[(i + k - 1) % k], uv[(i + 1) % k]);
+                uvs.push(vec![
+                    uv[i],
+                    midpoint(uv[i], next),
+                    center,
+                    midpoint(prev, uv[i]),
+                ]);
+            }
+        }
+
+        Self {
+            positions,
+            faces,
+            uvs,
+        }
+    }
+}
+
+fn midpoint(a: (Float, Float), b: (Float, Float)) -> (Float, Float) {
+    (0.5 * (a.0 + b.0), 0.5 * (a.1 + b.1))
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+// Turns a 1-based OBJ index into an index into a list of `len` items so
+// far; negative ones count back from the latest item.
+fn resolve_index(index: i64, len: usize, line: usize) -> io::Result<usize> {
+    let resolved = if index < 0 {
+        len as i64 + index
+    } else {
+        index - 1
+    };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(invalid_data(line, "face index out of range"));
+    }
+    Ok(resolved as usize)
+}
+
+fn invalid_data(line: usize, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line + 1, message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: Float = 0.00001;
+
+    const CUBE: &str = "\
+# unit cube centered on the origin
+v -1 -1 -1
+v  1 -1 -1
+v  1  1 -1
+v -1  1 -1
+v -1 -1  1
+v  1 -1  1
+v  1  1  1
+v -1  1  1
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+f 2/1 3/2 7/3 6/4
+f 3//1 4//1 8//1 7//1
+f -4 -1 -5 -8
+";
+
+    fn cube() -> PolygonMesh {
+        PolygonMesh::read_obj(CUBE.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn read_obj() {
+        let mesh = cube();
+
+        assert_eq!(mesh.positions().len(), 8);
+        assert_eq!(mesh.faces().len(), 6);
+        assert_eq!(mesh.faces()[3], vec![1, 2, 6, 5]);
+        assert_eq!(mesh.faces()[5], vec![4, 7, 3, 0]);
+
+        // Only some faces have texture coordinates.
+        assert!(mesh.uvs().is_empty());
+
+        assert!(PolygonMesh::read_obj("v 0 0 0\nf 1 2 3\n".as_bytes()).is_err());
+        assert!(PolygonMesh::read_obj("v 0 0 0\nf 1/1 1/1 1/1\n".as_bytes()).is_err());
+        assert!(PolygonMesh::read_obj("v 0 0\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn read_obj_texture_coordinates() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+                   vt 0 0\nvt 2 0\nvt 2 2\nvt 0 2\n\
+                   f 1/1 2/2 3/3 4/-1\n";
+        let mesh = PolygonMesh::read_obj(obj.as_bytes()).unwrap();
+        assert_eq!(
+            mesh.uvs(),
+            &[vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]]
+        );
+
+        let triangles = mesh.triangulate();
+        assert_eq!(triangles.faces(), &[vec![0, 1, 2], vec![0, 2, 3]]);
+        assert_eq!(triangles.uvs()[1], vec![(0.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+    }
+
+    #[test]
+    fn subdivision_interpolates_texture_coordinates() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+                   vt 0 0\nvt 2 0\nvt 2 2\nvt 0 2\n\
+                   f 1/1 2/2 3/3 4/4\n";
+        let mesh = PolygonMesh::read_obj(obj.as_bytes()).unwrap();
+
+        let quads = mesh.catmull_clark(2);
+        assert_eq!(quads.uvs().len(), quads.faces().len());
+        assert_eq!(
+            mesh.catmull_clark(1).uvs()[0],
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+        );
+
+        let triangles = mesh.loop_subdivide(2);
+        assert_eq!(triangles.uvs().len(), triangles.faces().len());
+        assert_eq!(
+            mesh.loop_subdivide(1).uvs()[3],
+            vec![(1.0, 0.0), (2.0, 1.0), (1.0, 1.0)]
+        );
+
+        // New edge and face points land where the uvs put them, at u = 2x
+        // and v = 2y; only the old corners are smoothed.
+        let once = mesh.catmull_clark(1);
+        for (face, uv) in once.faces().iter().zip(once.uvs()) {
+            for (&i, &(u, v)) in face.iter().zip(uv).filter(|(&i, _)| i >= 4) {
+                let p = once.positions()[i];
+                assert!((u - 2.0 * p.x()).abs() < EPSILON);
+                assert!((v - 2.0 * p.y()).abs() < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn catmull_clark_cube() {
+        let mesh = cube().catmull_clark(1);
+
+        assert_eq!(mesh.faces().len(), 24);
+        assert_eq!(mesh.positions().len(), 8 + 12 + 6);
+
+        // The standard result for a cube: corners move to 5/9, edge points to
+        // 3/4 along both axes, and face points stay on the faces.
+        let corner = mesh.positions()[6];
+        for c in &[corner.x(), corner.y(), corner.z()] {
+            assert!((c - 5.0 / 9.0).abs() < EPSILON);
+        }
+        for p in mesh.positions() {
+            let mut coords = [p.x().abs(), p.y().abs(), p.z().abs()];
+            coords.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let expected = match coords.iter().filter(|&&c| c < EPSILON).count() {
+                0 => [5.0 / 9.0; 3],
+                1 => [0.0, 0.75, 0.75],
+                _ => [0.0, 0.0, 1.0],
+            };
+            for (c, e) in coords.iter().zip(expected.iter()) {
+                assert!((c - e).abs() < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn loop_subdivide_tetrahedron() {
+        let positions = vec![
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+        ];
+        let faces = vec![vec![0, 1, 2], vec![0, 2, 3], vec![0, 3, 1], vec![1, 3, 2]];
+        let mesh = PolygonMesh::new(positions, faces).loop_subdivide(2);
+
+        assert_eq!(mesh.faces().len(), 4 * 16);
+        assert_eq!(mesh.positions().len(), 4 + 6 + 24);
+
+        // The surface shrinks towards the center but stays symmetric.
+        let centroid = mesh
+            .positions()
+            .iter()
+            .fold(Vec3::new(0.0, 0.0, 0.0), |acc, p| acc + *p);
+        assert!(centroid.length() < EPSILON);
+        for p in mesh.positions() {
+            assert!(p.length() < (3.0 as Float).sqrt());
+        }
+    }
+
+    #[test]
+    fn boundary_stays_in_plane() {
+        // A flat, open square: subdivision must not pull it off the plane
+        // or shrink its corners away from the boundary.
+        let positions = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+        let mesh = PolygonMesh::new(positions, vec![vec![0, 1, 2, 3]]);
+
+        for subdivided in &[mesh.catmull_clark(2), mesh.loop_subdivide(2)] {
+            for p in subdivided.positions() {
+                assert!(p.z().abs() < EPSILON);
+                assert!((-EPSILON..=1.0 + EPSILON).contains(&p.x()));
+                assert!((-EPSILON..=1.0 + EPSILON).contains(&p.y()));
+            }
+        }
+    }
+}
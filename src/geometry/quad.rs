@@ -0,0 +1,66 @@
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use crate::material::Material;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// A parallelogram with corner `q` and edges `u` and `v`. The front face is
+/// on the side of `u × v`.
+pub struct Quad {
+    q: Vec3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    d: Float,
+    w: Vec3,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl Quad {
+    pub fn new(q: Vec3, u: Vec3, v: Vec3, mat: &Rc<Box<dyn Material>>) -> Self {
+        let n = u.cross(&v);
+        let normal = n.unit();
+
+        Self {
+            q,
+            u,
+            v,
+            normal,
+            d: normal.dot(&q),
+            w: n / n.dot(&n),
+            mat: Rc::clone(mat),
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let denom = self.normal.dot(r.direction());
+
+        // No hit if the ray is parallel to the plane.
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(r.origin())) / denom;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        // Express the hit point in the (u, v) frame of the quad.
+        let p = r.at(t);
+        let planar = p - self.q;
+        let alpha = self.w.dot(&planar.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        Some(
+            HitRecord::new(p, t, (alpha, beta), r, &self.normal, &self.mat)
+                .with_tangents(&self.u, &self.v),
+        )
+    }
+}
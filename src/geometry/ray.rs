@@ -1,4 +1,5 @@
 use crate::utils::vec3::Vec3;
+use crate::utils::Float;
 
 #[derive(Debug)]
 pub struct Ray {
@@ -9,8 +10,8 @@ pub struct Ray {
 impl Ray {
     pub fn new(origin: &Vec3, direction: &Vec3) -> Ray {
         Ray {
-            origin: origin.clone(),
-            direction: direction.clone(),
+            origin: *origin,
+            direction: *direction,
         }
     }
 
@@ -22,8 +23,8 @@ impl Ray {
         &self.direction
     }
 
-    pub fn at(&self, t: f64) -> Vec3 {
-        self.origin.clone() + t * self.direction.clone()
+    pub fn at(&self, t: Float) -> Vec3 {
+        self.origin + t * self.direction
     }
 }
 
@@ -31,7 +32,7 @@ impl Ray {
 mod tests {
     use super::*;
 
-    const EPSILON: f64 = 0.00001;
+    const EPSILON: Float = 0.00001;
 
     #[test]
     fn new() {
@@ -0,0 +1,57 @@
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use crate::material::Material;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// A span of a ray that lies inside a solid, bounded by the surface hits
+/// where the ray enters and leaves it.
+#[derive(Clone)]
+pub struct Interval {
+    pub enter: HitRecord,
+    pub exit: HitRecord,
+}
+
+/// A closed `Hittable` that can report every interval along a ray that lies
+/// inside it, rather than only the closest hit.
+pub trait Solid: Hittable {
+    /// The inside intervals of the whole line through `r` (including
+    /// negative `t`), sorted and non-overlapping.
+    fn intervals(&self, r: &Ray) -> Vec<Interval>;
+}
+
+/// Builds the intervals of a convex solid from its sorted surface crossings,
+/// given as `(t, outward normal, (u, v))`.
+pub(super) fn convex_intervals(
+    r: &Ray,
+    crossings: Vec<(Float, Vec3, (Float, Float))>,
+    mat: &Rc<Box<dyn Material>>,
+) -> Vec<Interval> {
+    // A single crossing is a ray grazing an edge; it bounds no volume.
+    if crossings.len() < 2 {
+        return Vec::new();
+    }
+
+    let record = |&(t, normal, uv): &(Float, Vec3, (Float, Float))| {
+        HitRecord::new(r.at(t), t, uv, r, &normal, mat)
+    };
+
+    vec![Interval {
+        enter: record(&crossings[0]),
+        exit: record(&crossings[crossings.len() - 1]),
+    }]
+}
+
+/// The first interval boundary within `[t_min, t_max]`.
+pub(super) fn first_boundary(
+    intervals: Vec<Interval>,
+    t_min: Float,
+    t_max: Float,
+) -> Option<HitRecord> {
+    intervals
+        .into_iter()
+        .flat_map(|interval| vec![interval.enter, interval.exit])
+        .find(|rec| t_min <= rec.t && rec.t <= t_max)
+}
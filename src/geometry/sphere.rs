@@ -1,18 +1,21 @@
 use super::hit_record::HitRecord;
 use super::hittable::Hittable;
 use super::ray::Ray;
+use super::solid::{Interval, Solid};
 use crate::material::Material;
+use crate::utils::consts::PI;
 use crate::utils::vec3::Vec3;
+use crate::utils::Float;
 use std::rc::Rc;
 
 pub struct Sphere {
     center: Vec3,
-    radius: f64,
+    radius: Float,
     mat: Rc<Box<dyn Material>>,
 }
 
 impl Sphere {
-    pub fn new(center: Vec3, radius: f64, mat: &Rc<Box<dyn Material>>) -> Self {
+    pub fn new(center: Vec3, radius: Float, mat: &Rc<Box<dyn Material>>) -> Self {
         Self {
             center,
             radius,
@@ -21,9 +24,49 @@ impl Sphere {
     }
 }
 
+// Maps a point on the unit sphere to (u, v): u is the angle around the Y axis
+// from X=-1, v the angle from Y=-1 to Y=+1, both scaled to [0, 1].
+pub(crate) fn sphere_uv(p: &Vec3) -> (Float, Float) {
+    let theta = (-p.y()).acos();
+    let phi = (-p.z()).atan2(p.x()) + PI;
+
+    (phi / (2.0 * PI), theta / PI)
+}
+
+impl Sphere {
+    fn record(&self, r: &Ray, t: Float) -> HitRecord {
+        let p = r.at(t);
+        let outward_normal = (p - self.center) / self.radius;
+
+        // u runs around the y axis and v from the bottom pole to the top,
+        // over a full turn and half a turn. At the poles, where u has no
+        // direction, v runs straight up.
+        let n = outward_normal;
+        let ring = n.x().hypot(n.z());
+        let dpdu = 2.0 * PI * self.radius * Vec3::new(n.z(), 0.0, -n.x());
+        let dpdv = PI
+            * self.radius
+            * if ring > 0.0 {
+                Vec3::new(-n.y() * n.x() / ring, ring, -n.y() * n.z() / ring)
+            } else {
+                Vec3::new(0.0, 1.0, 0.0)
+            };
+
+        HitRecord::new(
+            p,
+            t,
+            sphere_uv(&outward_normal),
+            r,
+            &outward_normal,
+            &self.mat,
+        )
+        .with_tangents(&dpdu, &dpdv)
+    }
+}
+
 impl Hittable for Sphere {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let oc = r.origin().clone() - self.center;
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let oc = *r.origin() - self.center;
         let a = r.direction().length_squared();
         let half_b = oc.dot(r.direction());
         let c = oc.length_squared() - (self.radius * self.radius);
@@ -43,9 +86,63 @@ impl Hittable for Sphere {
             }
         }
 
-        let p = r.at(root);
-        let outward_normal = (p - self.center) / self.radius;
+        Some(self.record(r, root))
+    }
+}
 
-        Some(HitRecord::new(p, root, r, &outward_normal, &self.mat))
+impl Solid for Sphere {
+    fn intervals(&self, r: &Ray) -> Vec<Interval> {
+        let oc = *r.origin() - self.center;
+        let a = r.direction().length_squared();
+        let half_b = oc.dot(r.direction());
+        let c = oc.length_squared() - (self.radius * self.radius);
+
+        let discriminant = half_b * half_b - a * c;
+        if discriminant <= 0.0 {
+            return Vec::new();
+        }
+        let sqrtd = discriminant.sqrt();
+
+        vec![Interval {
+            enter: self.record(r, (-half_b - sqrtd) / a),
+            exit: self.record(r, (-half_b + sqrtd) / a),
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::utils::color::Color;
+
+    #[test]
+    fn tangents_follow_uv() {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        let sphere = Sphere::new(Vec3::new(0.0, 0.0, 0.0), 2.0, &mat);
+
+        for &origin in &[
+            Vec3::new(3.0, 1.0, 5.0),
+            Vec3::new(-4.0, -2.0, 1.0),
+            Vec3::new(0.5, 0.5, -6.0),
+        ] {
+            let r = Ray::new(&origin, &(-origin));
+            let rec = sphere.hit(&r, 0.0, Float::INFINITY).unwrap();
+
+            // Stepping along each tangent increases only the matching coordinate.
+            let step = |d: &Vec3| sphere_uv(&(rec.p + 1e-4 * *d).unit());
+            let (tangent, bitangent) = rec.tangents();
+            let (u, v) = step(&tangent);
+            assert!(u > rec.u && (v - rec.v).abs() < 1e-6);
+            let (u, v) = step(&bitangent);
+            assert!(v > rec.v && (u - rec.u).abs() < 1e-6);
+
+            // Stepping by the derivatives moves the coordinates one for one.
+            let h = 1e-4;
+            let (u, v) = step(&rec.dpdu);
+            assert!((u - rec.u - h).abs() < 1e-2 * h && (v - rec.v).abs() < 1e-2 * h);
+            let (u, v) = step(&rec.dpdv);
+            assert!((v - rec.v - h).abs() < 1e-2 * h && (u - rec.u).abs() < 1e-2 * h);
+        }
     }
 }
@@ -0,0 +1,19 @@
+use std::cell::Cell;
+
+// The number of intersection tests made on this thread, which the
+// traversal-cost heatmap reads.
+thread_local! {
+    static TESTS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Records `n` tests of rays against bounding boxes, grid cells or
+/// primitives.
+pub(crate) fn count_tests(n: usize) {
+    TESTS.with(|tests| tests.set(tests.get() + n as u64));
+}
+
+/// Returns the number of intersection tests made on this thread since the
+/// last call.
+pub fn take_test_count() -> u64 {
+    TESTS.with(|tests| tests.replace(0))
+}
@@ -0,0 +1,149 @@
+use super::cylinder::angle_u;
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use crate::material::Material;
+use crate::utils::consts::PI;
+use crate::utils::onb::Onb;
+use crate::utils::roots::solve_quartic;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// A torus around `axis`: a tube of radius `minor_radius` swept along a
+/// circle of radius `major_radius`.
+///
+/// `u` runs around the axis and `v` around the tube, both in [0, 1].
+pub struct Torus {
+    center: Vec3,
+    major_radius: Float,
+    minor_radius: Float,
+    onb: Onb,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl Torus {
+    pub fn new(
+        center: Vec3,
+        axis: Vec3,
+        major_radius: Float,
+        minor_radius: Float,
+        mat: &Rc<Box<dyn Material>>,
+    ) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            onb: Onb::from_w(&axis),
+            mat: Rc::clone(mat),
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        // Work in the local frame with a unit direction, so distances along
+        // the ray are `length` times larger than `t`.
+        let length = r.direction().length();
+        let d = self.onb.to_local(r.direction()) / length;
+        let mut o = self.onb.to_local(&(*r.origin() - self.center));
+
+        // Move the origin up to the bounding sphere first; the quartic is
+        // badly conditioned for distant origins.
+        let bound = self.major_radius + self.minor_radius;
+        let half_b = o.dot(&d);
+        let c = o.length_squared() - bound * bound;
+        let discriminant = half_b * half_b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let s0 = (-half_b - discriminant.sqrt()).max(0.0);
+        o += s0 * d;
+
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + y^2)
+        let r2 = self.major_radius * self.major_radius;
+        let m = o.dot(&d);
+        let k = o.length_squared() + r2 - self.minor_radius * self.minor_radius;
+        let a = 4.0 * m;
+        let b = 4.0 * m * m + 2.0 * k - 4.0 * r2 * (d.x() * d.x() + d.y() * d.y());
+        let c = 4.0 * m * k - 8.0 * r2 * (o.x() * d.x() + o.y() * d.y());
+        let e = k * k - 4.0 * r2 * (o.x() * o.x() + o.y() * o.y());
+
+        let t = solve_quartic(a, b, c, e)
+            .into_iter()
+            .map(|s| (s0 + s) / length)
+            .find(|&t| t_min <= t && t <= t_max)?;
+
+        let local = self.onb.to_local(&(r.at(t) - self.center));
+        let ring = local.x().hypot(local.y());
+        let outward_normal =
+            (local - self.major_radius * Vec3::new(local.x(), local.y(), 0.0) / ring).unit();
+        let u = angle_u(local.x(), local.y());
+        let v = angle_u(ring - self.major_radius, local.z());
+
+        // u runs around the axis and v around the tube, each over a full turn.
+        let dpdu = 2.0 * PI * Vec3::new(-local.y(), local.x(), 0.0);
+        let radial = Vec3::new(local.x(), local.y(), 0.0) / ring;
+        let dpdv = 2.0
+            * PI
+            * (-local.z() * radial + (ring - self.major_radius) * Vec3::new(0.0, 0.0, 1.0));
+
+        Some(
+            HitRecord::new(
+                r.at(t),
+                t,
+                (u, v),
+                r,
+                &self.onb.local(&outward_normal),
+                &self.mat,
+            )
+            .with_tangents(&self.onb.local(&dpdu), &self.onb.local(&dpdv)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.00001;
+
+    fn torus() -> Torus {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        Torus::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            2.0,
+            0.5,
+            &mat,
+        )
+    }
+
+    #[test]
+    fn hit_outer_edge() {
+        let r = Ray::new(&Vec3::new(-10.0, 0.0, 0.0), &Vec3::new(2.0, 0.0, 0.0));
+        let rec = torus().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 3.75).abs() < EPSILON);
+        assert!((rec.normal.x() + 1.0).abs() < EPSILON);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn hit_from_inside_tube() {
+        let r = Ray::new(&Vec3::new(2.0, 0.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        let rec = torus().hit(&r, 0.0, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 0.5).abs() < EPSILON);
+        assert!((rec.normal.y() + 1.0).abs() < EPSILON);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn miss_through_hole() {
+        let r = Ray::new(&Vec3::new(0.0, -10.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        assert!(torus().hit(&r, 0.0, Float::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,205 @@
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// A uniform scale, then a rotation by Euler angles in degrees about the x,
+/// y and z axes in that order, then a translation.
+#[derive(Copy, Clone, Debug)]
+pub struct Transform {
+    translation: Vec3,
+    rotation: Vec3,
+    scale: Float,
+    // Rows of the rotation matrix.
+    matrix: [Vec3; 3],
+}
+
+impl Transform {
+    pub fn new(translation: Vec3, rotation: Vec3, scale: Float) -> Self {
+        assert!(scale > 0.0, "the scale of a transform must be positive");
+
+        let axes = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+        let rotate = |v: Vec3| {
+            v.rotate(&axes[0], rotation.x().to_radians())
+                .rotate(&axes[1], rotation.y().to_radians())
+                .rotate(&axes[2], rotation.z().to_radians())
+        };
+        // The images of the axes are the columns; transpose them into rows.
+        let [cx, cy, cz] = axes.map(rotate);
+        let matrix = [
+            Vec3::new(cx.x(), cy.x(), cz.x()),
+            Vec3::new(cx.y(), cy.y(), cz.y()),
+            Vec3::new(cx.z(), cy.z(), cz.z()),
+        ];
+
+        Self {
+            translation,
+            rotation,
+            scale,
+            matrix,
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0), 1.0)
+    }
+
+    pub fn translation(&self) -> &Vec3 {
+        &self.translation
+    }
+
+    pub fn rotation(&self) -> &Vec3 {
+        &self.rotation
+    }
+
+    pub fn scale(&self) -> Float {
+        self.scale
+    }
+
+    fn rotate(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(
+            self.matrix[0].dot(v),
+            self.matrix[1].dot(v),
+            self.matrix[2].dot(v),
+        )
+    }
+
+    fn unrotate(&self, v: &Vec3) -> Vec3 {
+        v.x() * self.matrix[0] + v.y() * self.matrix[1] + v.z() * self.matrix[2]
+    }
+
+    pub fn point(&self, p: &Vec3) -> Vec3 {
+        self.scale * self.rotate(p) + self.translation
+    }
+
+    pub fn vector(&self, v: &Vec3) -> Vec3 {
+        self.scale * self.rotate(v)
+    }
+
+    /// Normals only turn, since the scale is uniform.
+    pub fn normal(&self, n: &Vec3) -> Vec3 {
+        self.rotate(n)
+    }
+
+    pub fn inverse_point(&self, p: &Vec3) -> Vec3 {
+        self.unrotate(&(*p - self.translation)) / self.scale
+    }
+
+    pub fn inverse_vector(&self, v: &Vec3) -> Vec3 {
+        self.unrotate(v) / self.scale
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// Places a shared object in the world with a `Transform`, e.g. one mesh
+/// drawn several times or moved from frame to frame.
+pub struct Transformed {
+    object: Rc<Box<dyn Hittable>>,
+    transform: Transform,
+}
+
+impl Transformed {
+    pub fn new(object: &Rc<Box<dyn Hittable>>, transform: Transform) -> Self {
+        Self {
+            object: Rc::clone(object),
+            transform,
+        }
+    }
+}
+
+impl Hittable for Transformed {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        // Mapping the direction along with the origin keeps `t` unchanged.
+        let local = Ray::new(
+            &self.transform.inverse_point(r.origin()),
+            &self.transform.inverse_vector(r.direction()),
+        );
+        let mut rec = self.object.hit(&local, t_min, t_max)?;
+
+        rec.p = r.at(rec.t);
+        rec.normal = self.transform.normal(&rec.normal);
+        rec.shading_normal = self.transform.normal(&rec.shading_normal);
+        rec.dpdu = self.transform.vector(&rec.dpdu);
+        rec.dpdv = self.transform.vector(&rec.dpdv);
+
+        Some(rec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Quad, Sphere};
+    use crate::material::{Lambertian, Material};
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.00001;
+
+    fn mat() -> Rc<Box<dyn Material>> {
+        Rc::new(Box::new(Lambertian::new(Color::white())))
+    }
+
+    #[test]
+    fn point_round_trip() {
+        let transform = Transform::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(30.0, -45.0, 60.0), 2.5);
+        let p = Vec3::new(-0.3, 0.7, 1.9);
+
+        assert!((transform.inverse_point(&transform.point(&p)) - p).length() < EPSILON);
+        let v = transform.vector(&p);
+        assert!((v.length() - 2.5 * p.length()).abs() < EPSILON);
+
+        // A quarter turn about y takes +x to -z.
+        let turn = Transform::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 90.0, 0.0), 1.0);
+        let x = turn.vector(&Vec3::new(1.0, 0.0, 0.0));
+        assert!((x - Vec3::new(0.0, 0.0, -1.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn hit_scaled_and_moved_sphere() {
+        let sphere: Rc<Box<dyn Hittable>> =
+            Rc::new(Box::new(Sphere::new(Vec3::new(0.0, 0.0, 0.0), 1.0, &mat())));
+        let transform = Transform::new(Vec3::new(0.0, 0.0, -10.0), Vec3::new(0.0, 45.0, 0.0), 2.0);
+        let object = Transformed::new(&sphere, transform);
+
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 0.0), &Vec3::new(0.0, 0.0, -1.0));
+        let rec = object.hit(&r, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 8.0).abs() < EPSILON);
+        assert!((rec.p - Vec3::new(0.0, 0.0, -8.0)).length() < EPSILON);
+        assert!((rec.normal - Vec3::new(0.0, 0.0, 1.0)).length() < EPSILON);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn hit_rotated_quad() {
+        // A unit quad in the xy plane, turned to face +x.
+        let quad: Rc<Box<dyn Hittable>> = Rc::new(Box::new(Quad::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &mat(),
+        )));
+        let transform = Transform::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 90.0, 0.0), 1.0);
+        let object = Transformed::new(&quad, transform);
+
+        let r = Ray::new(&Vec3::new(5.0, 0.5, -0.5), &Vec3::new(-1.0, 0.0, 0.0));
+        let rec = object.hit(&r, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 5.0).abs() < EPSILON);
+        assert!((rec.normal - Vec3::new(1.0, 0.0, 0.0)).length() < EPSILON);
+        assert!((rec.tangents().0 - Vec3::new(0.0, 0.0, -1.0)).length() < EPSILON);
+        assert!((rec.u - 0.5).abs() < EPSILON);
+
+        let r = Ray::new(&Vec3::new(5.0, 0.5, 0.5), &Vec3::new(-1.0, 0.0, 0.0));
+        assert!(object.hit(&r, 0.0, Float::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,79 @@
+use super::hit_record::HitRecord;
+use super::hittable::Hittable;
+use super::ray::Ray;
+use crate::material::Material;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// A flat triangle. The front face is on the side of `(b - a) × (c - a)`,
+/// i.e. the vertices wind counter-clockwise when seen from the front.
+pub struct Triangle {
+    a: Vec3,
+    b: Vec3,
+    c: Vec3,
+    normal: Vec3,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl Triangle {
+    pub fn new(a: Vec3, b: Vec3, c: Vec3, mat: &Rc<Box<dyn Material>>) -> Self {
+        Self {
+            a,
+            b,
+            c,
+            normal: (b - a).cross(&(c - a)).unit(),
+            mat: Rc::clone(mat),
+        }
+    }
+}
+
+/// Möller-Trumbore ray/triangle intersection, returning `t` and the
+/// barycentric weights of `b` and `c`.
+pub(super) fn hit_triangle(
+    r: &Ray,
+    a: &Vec3,
+    b: &Vec3,
+    c: &Vec3,
+    t_min: Float,
+    t_max: Float,
+) -> Option<(Float, Float, Float)> {
+    let edge1 = *b - *a;
+    let edge2 = *c - *a;
+    let h = r.direction().cross(&edge2);
+    let det = edge1.dot(&h);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let s = *r.origin() - *a;
+    let u = inv_det * s.dot(&h);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(&edge1);
+    let v = inv_det * r.direction().dot(&q);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = inv_det * edge2.dot(&q);
+    if t < t_min || t_max < t {
+        return None;
+    }
+
+    Some((t, u, v))
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let (t, u, v) = hit_triangle(r, &self.a, &self.b, &self.c, t_min, t_max)?;
+
+        Some(
+            HitRecord::new(r.at(t), t, (u, v), r, &self.normal, &self.mat)
+                .with_tangents(&(self.b - self.a), &(self.c - self.a)),
+        )
+    }
+}
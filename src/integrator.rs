@@ -0,0 +1,22 @@
+mod ambient_occlusion;
+mod bidirectional;
+mod debug;
+mod direct;
+#[allow(clippy::module_inception)]
+mod integrator;
+mod metropolis;
+mod path;
+mod photon;
+
+pub use ambient_occlusion::AmbientOcclusionIntegrator;
+pub use bidirectional::BidirectionalIntegrator;
+#[cfg(test)]
+pub(crate) use debug::material_color;
+pub use debug::{
+    DepthIntegrator, HeatmapIntegrator, MaterialIdIntegrator, NormalsIntegrator, UvIntegrator,
+};
+pub use direct::DirectLightingIntegrator;
+pub use integrator::{Integrator, IntegratorKind};
+pub use metropolis::MetropolisIntegrator;
+pub use path::PathIntegrator;
+pub use photon::PhotonMappingIntegrator;
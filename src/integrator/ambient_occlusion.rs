@@ -0,0 +1,109 @@
+use super::integrator::Integrator;
+use crate::geometry::{Hittable, Ray};
+use crate::render::RenderSettings;
+use crate::sampler::Dimensions;
+use crate::utils::color::Color;
+use crate::utils::random::{self, sample_2d};
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// Ambient occlusion: white where a surface sees open space all around,
+/// darker where other geometry closer than `distance` blocks its view. Each
+/// sample casts one cosine-distributed ray from the first surface hit.
+pub struct AmbientOcclusionIntegrator {
+    distance: Float,
+}
+
+impl AmbientOcclusionIntegrator {
+    pub fn new(distance: Float) -> Self {
+        Self { distance }
+    }
+}
+
+impl Integrator for AmbientOcclusionIntegrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, _settings: &RenderSettings) -> Color {
+        random::start_dimensions(Dimensions::Bounce(0));
+        let rec = match world.hit(r, 0.001, Float::INFINITY) {
+            Some(rec) => rec,
+            None => return Color::white(),
+        };
+
+        let mut direction = rec.normal + Vec3::uniform_on_sphere(sample_2d());
+        if direction.near_zero() {
+            direction = rec.normal;
+        }
+
+        random::start_dimensions(Dimensions::Bounce(1));
+        let t_max = self.distance / direction.length();
+        if world
+            .hit(&Ray::new(&rec.p, &direction), 0.001, t_max)
+            .is_some()
+        {
+            Color::black()
+        } else {
+            Color::white()
+        }
+    }
+
+    fn measures_light(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{HittableList, Plane};
+    use crate::integrator::IntegratorKind;
+    use crate::material::{Lambertian, Material};
+    use crate::render::Background;
+    use crate::sampler::SamplerKind;
+    use std::rc::Rc;
+
+    fn occlusion(world: &HittableList, distance: Float) -> Float {
+        let settings = RenderSettings {
+            image_width: 1,
+            image_height: 1,
+            samples_per_pixel: 1,
+            max_depth: 1,
+            roulette_depth: 1,
+            background: Background::Sky,
+            crop: None,
+            sampler: SamplerKind::Independent,
+            integrator: IntegratorKind::AmbientOcclusion { distance },
+            lights: Vec::new(),
+        };
+        let integrator = AmbientOcclusionIntegrator::new(distance);
+        let r = Ray::new(&Vec3::new(0.0, 0.5, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let n = 1000;
+        let open: Float = (0..n)
+            .map(|_| integrator.li(&r, world, &settings).g())
+            .sum();
+        open / n as Float
+    }
+
+    #[test]
+    fn ceiling_occludes_within_distance() {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        let mut world = HittableList::new();
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &mat,
+        )));
+        assert_eq!(occlusion(&world, 10.0), 1.0);
+
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            &mat,
+        )));
+        assert_eq!(occlusion(&world, 1000.0), 0.0);
+
+        // Within a distance of 2, only rays steeper than cos θ = 1/2 reach
+        // the ceiling 1 above: three quarters of a cosine-distributed
+        // hemisphere, leaving a quarter open.
+        let open = occlusion(&world, 2.0);
+        assert!((open - 0.25).abs() < 0.05, "open {}", open);
+    }
+}
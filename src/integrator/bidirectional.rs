@@ -0,0 +1,813 @@
+use super::integrator::Integrator;
+use crate::camera::Camera;
+use crate::geometry::{HitRecord, Hittable, Ray};
+use crate::render::RenderSettings;
+use crate::sampler::Dimensions;
+use crate::utils::color::Color;
+use crate::utils::random::{self, sample_1d, sample_2d};
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+// Keeps rays from hitting the surface they leave, as in `ray_color`.
+const T_MIN: Float = 0.001;
+
+/// Bidirectional path tracing: traces a path from the camera and one from
+/// `RenderSettings::lights`, and joins every vertex of one to every vertex
+/// of the other, weighting each way of building a path by how likely it is
+/// to find it (multiple importance sampling, with the balance heuristic).
+///
+/// Light paths that land on diffuse surfaces are also joined to the camera
+/// itself, which resolves caustics seen through glass and off mirrors. That
+/// needs a camera that supports `Camera::connect`. Paths meet at most
+/// `max_depth` surfaces, as in path tracing, and are never ended by Russian
+/// roulette. With no lights this is plain path tracing, without roulette.
+pub struct BidirectionalIntegrator;
+
+impl Integrator for BidirectionalIntegrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, settings: &RenderSettings) -> Color {
+        let tracer = Tracer {
+            world,
+            camera: None,
+            settings,
+        };
+        tracer.li(r, &mut |_, _| {})
+    }
+
+    fn li_with_camera(
+        &self,
+        r: &Ray,
+        world: &dyn Hittable,
+        camera: &dyn Camera,
+        settings: &RenderSettings,
+        splat: &mut dyn FnMut((Float, Float), Color),
+    ) -> Color {
+        let tracer = Tracer {
+            world,
+            camera: Some(camera),
+            settings,
+        };
+        tracer.li(r, splat)
+    }
+}
+
+enum Kind {
+    Camera,
+    // A point on light `light` of the settings or, at infinity, the point one
+    // unit towards it. `light` is None for the background when no light
+    // stands for it.
+    Light {
+        light: Option<usize>,
+        infinite: bool,
+    },
+    // A surface reached along the unit vector `incoming`.
+    Surface {
+        rec: Box<HitRecord>,
+        incoming: Vec3,
+    },
+}
+
+struct Vertex {
+    kind: Kind,
+    p: Vec3,
+    // The geometric normal; zero off surfaces.
+    n: Vec3,
+    // The light, or the camera's response, carried from the start of the
+    // subpath to here, over the density of sampling it.
+    beta: Color,
+    // Scatters specularly, so it cannot be joined to.
+    delta: bool,
+    // The densities of sampling this vertex from the one before it in its
+    // subpath, and in reverse from the one after it, per unit area (per
+    // unit solid angle for lights at infinity). Specular scattering has no
+    // density; it counts as 1 per unit solid angle, which cancels between
+    // the ways of building a path that can cross it.
+    pdf_fwd: Float,
+    pdf_rev: Float,
+}
+
+impl Vertex {
+    fn new(kind: Kind, p: Vec3, n: Vec3, beta: Color) -> Self {
+        Self {
+            kind,
+            p,
+            n,
+            beta,
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+        }
+    }
+
+    fn is_infinite(&self) -> bool {
+        matches!(self.kind, Kind::Light { infinite: true, .. })
+    }
+
+    fn on_surface(&self) -> bool {
+        self.n.length_squared() > 0.0
+    }
+
+    fn direction_to(&self, other: &Vertex) -> Vec3 {
+        (other.p - self.p).unit()
+    }
+
+    fn connectible(&self) -> bool {
+        match &self.kind {
+            Kind::Camera => !self.delta,
+            Kind::Light { .. } => true,
+            Kind::Surface { rec, .. } => !rec.mat.is_specular(),
+        }
+    }
+
+    // The light scattered towards `next`, times the cosine there.
+    fn f(&self, next: &Vertex) -> Color {
+        match &self.kind {
+            Kind::Surface { rec, incoming } => {
+                rec.mat.eval(rec, incoming, &self.direction_to(next))
+            }
+            _ => Color::black(),
+        }
+    }
+
+    // Turns the density per solid angle of sampling `next` from here into one
+    // per unit area at `next`.
+    fn convert_density(&self, pdf: Float, next: &Vertex) -> Float {
+        if next.is_infinite() {
+            return pdf;
+        }
+
+        let w = next.p - self.p;
+        let distance_squared = w.length_squared();
+        if distance_squared == 0.0 {
+            return 0.0;
+        }
+        let mut pdf = pdf / distance_squared;
+        if next.on_surface() {
+            pdf *= next.n.dot(&w).abs() / distance_squared.sqrt();
+        }
+        pdf
+    }
+}
+
+struct Tracer<'a> {
+    world: &'a dyn Hittable,
+    camera: Option<&'a dyn Camera>,
+    settings: &'a RenderSettings,
+}
+
+impl<'a> Tracer<'a> {
+    fn li(&self, r: &Ray, splat: &mut dyn FnMut((Float, Float), Color)) -> Color {
+        let max_depth = self.settings.max_depth.max(0) as usize;
+        let camera_path = self.camera_subpath(r, max_depth);
+        let light_path = self.light_subpath(max_depth);
+
+        // A path with `s` light and `t` camera vertices meets s + t - 1
+        // surfaces, counting the background as one.
+        let mut color = Color::black();
+        for t in 1..=camera_path.len() {
+            for s in 0..=light_path.len() {
+                if s + t < 2 || (s == 1 && t == 1) || s + t - 1 > max_depth {
+                    continue;
+                }
+                if t == 1 {
+                    if let Some((position, light)) = self.connect_camera(&light_path, s) {
+                        splat(position, light);
+                    }
+                } else {
+                    color += self.connect(&light_path, &camera_path, s, t);
+                }
+            }
+        }
+
+        color
+    }
+
+    fn camera_subpath(&self, r: &Ray, max_depth: usize) -> Vec<Vertex> {
+        let pdf = self.camera.and_then(|camera| camera.direction_pdf(r));
+        let mut camera = Vertex::new(
+            Kind::Camera,
+            *r.origin(),
+            Vec3::new(0.0, 0.0, 0.0),
+            Color::white(),
+        );
+        // Cameras that cannot be joined to are as good as a pinhole nobody
+        // finds.
+        camera.delta = pdf.is_none();
+
+        let mut path = vec![camera];
+        let ray = Ray::new(r.origin(), r.direction());
+        self.random_walk(
+            ray,
+            Color::white(),
+            pdf.unwrap_or(1.0),
+            max_depth,
+            false,
+            &mut path,
+        );
+        path
+    }
+
+    fn light_subpath(&self, max_depth: usize) -> Vec<Vertex> {
+        let lights = &self.settings.lights;
+        let mut path = Vec::new();
+        if lights.is_empty() || max_depth == 0 {
+            return path;
+        }
+
+        random::start_dimensions(Dimensions::LightBounce(0));
+        let choice = 1.0 / lights.len() as Float;
+        let index = ((sample_1d() * lights.len() as Float) as usize).min(lights.len() - 1);
+        let light = &lights[index];
+        let emission = match light.sample_emission(sample_2d(), sample_2d()) {
+            Some(emission) if emission.pdf_position > 0.0 && emission.pdf_direction > 0.0 => {
+                emission
+            }
+            _ => return path,
+        };
+
+        let infinite = light.is_infinite();
+        let direction = *emission.ray.direction();
+        let normal = if infinite {
+            Vec3::new(0.0, 0.0, 0.0)
+        } else {
+            emission.normal
+        };
+        let kind = Kind::Light {
+            light: Some(index),
+            infinite,
+        };
+        let mut start = Vertex::new(kind, *emission.ray.origin(), normal, emission.radiance);
+        start.pdf_fwd = emission.pdf_position * choice;
+        path.push(start);
+
+        let beta = emission.radiance * emission.normal.dot(&direction).abs()
+            / (choice * emission.pdf_position * emission.pdf_direction);
+        self.random_walk(
+            emission.ray,
+            beta,
+            emission.pdf_direction,
+            max_depth - 1,
+            true,
+            &mut path,
+        );
+
+        // Light from infinity is picked by direction first, then across the
+        // beam.
+        if infinite {
+            if let Some(first) = path.get_mut(1) {
+                first.pdf_fwd = emission.pdf_position;
+                if first.on_surface() {
+                    first.pdf_fwd *= direction.dot(&first.n).abs();
+                }
+            }
+            path[0].pdf_fwd = self.pdf_infinite(&path[0].p, &direction);
+        }
+
+        path
+    }
+
+    // Extends `path` by up to `max_vertices` vertices where `ray` and the
+    // rays scattered after it meet the world, starting with throughput
+    // `beta` and the density `pdf` per solid angle of the ray's direction.
+    fn random_walk(
+        &self,
+        mut ray: Ray,
+        mut beta: Color,
+        pdf: Float,
+        max_vertices: usize,
+        from_light: bool,
+        path: &mut Vec<Vertex>,
+    ) {
+        let mut pdf_fwd = pdf;
+        for bounce in 0..max_vertices {
+            random::start_dimensions(if from_light {
+                Dimensions::LightBounce(bounce + 1)
+            } else {
+                Dimensions::Bounce(bounce)
+            });
+
+            let prev = path.len() - 1;
+            let rec = match self.world.hit(&ray, T_MIN, Float::INFINITY) {
+                Some(rec) => rec,
+                None => {
+                    // Camera paths end on the background, a light at
+                    // infinity.
+                    if !from_light {
+                        let kind = Kind::Light {
+                            light: self.settings.lights.iter().position(|l| l.is_infinite()),
+                            infinite: true,
+                        };
+                        let p = path[prev].p + ray.direction().unit();
+                        let mut vertex = Vertex::new(kind, p, Vec3::new(0.0, 0.0, 0.0), beta);
+                        vertex.pdf_fwd = pdf_fwd;
+                        path.push(vertex);
+                    }
+                    return;
+                }
+            };
+
+            let (p, n) = (rec.p, rec.normal);
+            let incoming = ray.direction().unit();
+            let mut vertex = Vertex::new(
+                Kind::Surface {
+                    rec: Box::new(rec),
+                    incoming,
+                },
+                p,
+                n,
+                beta,
+            );
+            vertex.pdf_fwd = path[prev].convert_density(pdf_fwd, &vertex);
+            path.push(vertex);
+            if bounce + 1 == max_vertices {
+                return;
+            }
+
+            let current = &path[prev + 1];
+            let rec = match &current.kind {
+                Kind::Surface { rec, .. } => rec,
+                _ => unreachable!(),
+            };
+            let (attenuation, scattered) = match rec.mat.scatter(&ray, rec) {
+                Some(scatter) => scatter,
+                None => return,
+            };
+            let outgoing = scattered.direction().unit();
+            let specular = rec.mat.is_specular();
+            let (fwd, rev) = if specular {
+                (1.0, 1.0)
+            } else {
+                (
+                    rec.mat.pdf(rec, &incoming, &outgoing),
+                    rec.mat.pdf(rec, &-outgoing, &-incoming),
+                )
+            };
+            if fwd <= 0.0 {
+                return;
+            }
+
+            let rev = current.convert_density(rev, &path[prev]);
+            path[prev].pdf_rev = rev;
+            path[prev + 1].delta = specular;
+            beta *= attenuation;
+            pdf_fwd = fwd;
+            ray = scattered;
+        }
+    }
+
+    // Joins light vertex `s` and camera vertex `t`, counting from 1, or for
+    // `s` = 0 takes the camera path as it is.
+    fn connect(&self, light_path: &[Vertex], camera_path: &[Vertex], s: usize, t: usize) -> Color {
+        let pt = &camera_path[t - 1];
+        // Paths that left the scene can only be taken as they are.
+        if s > 0 && pt.is_infinite() {
+            return Color::black();
+        }
+
+        let mut sampled = None;
+        let color = if s == 0 {
+            self.emitted(pt, &camera_path[t - 2]) * pt.beta
+        } else if s == 1 {
+            if !pt.connectible() {
+                return Color::black();
+            }
+            let light = match self.sample_light(pt, t) {
+                Some(light) => light,
+                None => return Color::black(),
+            };
+            let color = pt.beta * pt.f(&light) * light.beta;
+            if color.max_component() <= 0.0 || !self.visible(pt, &light) {
+                return Color::black();
+            }
+            sampled = Some(light);
+            color
+        } else {
+            let qs = &light_path[s - 1];
+            if !qs.connectible() || !pt.connectible() {
+                return Color::black();
+            }
+            let distance_squared = (qs.p - pt.p).length_squared();
+            let color = qs.beta * qs.f(pt) * pt.f(qs) * pt.beta / distance_squared;
+            if color.max_component() <= 0.0 || !self.visible(pt, qs) {
+                return Color::black();
+            }
+            color
+        };
+
+        if color.max_component() <= 0.0 {
+            return Color::black();
+        }
+        color * self.mis_weight(light_path, camera_path, sampled.as_ref(), s, t)
+    }
+
+    // Joins light vertex `s` to a point on the lens, giving where in the
+    // image the light lands.
+    fn connect_camera(&self, light_path: &[Vertex], s: usize) -> Option<((Float, Float), Color)> {
+        let camera = self.camera?;
+        let qs = &light_path[s - 1];
+        if !qs.connectible() {
+            return None;
+        }
+
+        random::start_dimensions(Dimensions::CameraConnection(s - 1));
+        let lens = camera.connect(&qs.p, sample_2d())?;
+        let kind = Kind::Camera;
+        let zero = Vec3::new(0.0, 0.0, 0.0);
+        let sampled = Vertex::new(kind, lens.point, zero, lens.weight * Color::white());
+        let color = qs.beta * qs.f(&sampled) * sampled.beta;
+        if color.max_component() <= 0.0 || !self.visible(qs, &sampled) {
+            return None;
+        }
+
+        let weight = self.mis_weight(light_path, &[], Some(&sampled), s, 1);
+        Some(((lens.s, lens.t), color * weight))
+    }
+
+    // Picks a point on a light to join `pt`, camera vertex `t - 1`, to.
+    fn sample_light(&self, pt: &Vertex, t: usize) -> Option<Vertex> {
+        let lights = &self.settings.lights;
+        if lights.is_empty() {
+            return None;
+        }
+
+        random::start_dimensions(Dimensions::LightConnection(t - 1));
+        let count = lights.len() as Float;
+        let index = ((sample_1d() * count) as usize).min(lights.len() - 1);
+        let light = &lights[index];
+        let incident = light.sample_incident(&pt.p, sample_2d())?;
+        if incident.pdf <= 0.0 {
+            return None;
+        }
+
+        let kind = Kind::Light {
+            light: Some(index),
+            infinite: light.is_infinite(),
+        };
+        let beta = incident.radiance * count / incident.pdf;
+        let mut vertex = Vertex::new(kind, incident.point, incident.normal, beta);
+        vertex.pdf_fwd = self.pdf_light_origin(&vertex, pt);
+        Some(vertex)
+    }
+
+    fn visible(&self, from: &Vertex, to: &Vertex) -> bool {
+        let w = to.p - from.p;
+        let t_max = if to.is_infinite() {
+            Float::INFINITY
+        } else {
+            w.length() - T_MIN
+        };
+        self.world
+            .hit(&Ray::new(&from.p, &w.unit()), T_MIN, t_max)
+            .is_none()
+    }
+
+    // The light `v` sends towards `prev`, where the camera path came from.
+    fn emitted(&self, v: &Vertex, prev: &Vertex) -> Color {
+        match &v.kind {
+            Kind::Surface { rec, .. } => rec.mat.emitted(rec),
+            Kind::Light { infinite: true, .. } => self
+                .settings
+                .background
+                .color(&Ray::new(&prev.p, &(v.p - prev.p))),
+            _ => Color::black(),
+        }
+    }
+
+    fn light_index(&self, v: &Vertex) -> Option<usize> {
+        match &v.kind {
+            Kind::Light { light, .. } => *light,
+            Kind::Surface { rec, .. } => self.settings.lights.iter().position(|l| l.contains(rec)),
+            Kind::Camera => None,
+        }
+    }
+
+    // The density per unit solid angle of light paths from infinity
+    // travelling along `direction`.
+    fn pdf_infinite(&self, p: &Vec3, direction: &Vec3) -> Float {
+        let lights = &self.settings.lights;
+        let choice = 1.0 / lights.len() as Float;
+        lights
+            .iter()
+            .filter(|light| light.is_infinite())
+            .map(|light| light.emission_pdf(p, direction).1 * choice)
+            .sum()
+    }
+
+    // The density of light paths starting at `v`, on a light, on their way
+    // to `next`: per unit area, or per unit solid angle for lights at
+    // infinity. Zero for emitters that no light stands for.
+    fn pdf_light_origin(&self, v: &Vertex, next: &Vertex) -> Float {
+        let index = match self.light_index(v) {
+            Some(index) => index,
+            None => return 0.0,
+        };
+        let w = v.direction_to(next);
+        if v.is_infinite() {
+            return self.pdf_infinite(&next.p, &w);
+        }
+
+        let choice = 1.0 / self.settings.lights.len() as Float;
+        self.settings.lights[index].emission_pdf(&v.p, &w).0 * choice
+    }
+
+    // The density per unit area at `next` of light paths from `v`, on a
+    // light, reaching it first.
+    fn pdf_light(&self, v: &Vertex, next: &Vertex) -> Float {
+        let index = match self.light_index(v) {
+            Some(index) => index,
+            None => return 0.0,
+        };
+        let light = &self.settings.lights[index];
+        let w = next.p - v.p;
+        let distance_squared = w.length_squared();
+        let w = w.unit();
+
+        let mut pdf = if v.is_infinite() {
+            light.emission_pdf(&next.p, &w).0
+        } else {
+            light.emission_pdf(&v.p, &w).1 / distance_squared
+        };
+        if next.on_surface() {
+            pdf *= next.n.dot(&w).abs();
+        }
+        pdf
+    }
+
+    // The density per unit area at `next` of sampling it from `v`, which the
+    // path reached from `prev`.
+    fn pdf(&self, v: &Vertex, prev: Option<&Vertex>, next: &Vertex) -> Float {
+        let outgoing = v.direction_to(next);
+        let pdf = match &v.kind {
+            Kind::Light { .. } => return self.pdf_light(v, next),
+            Kind::Camera => self
+                .camera
+                .and_then(|camera| camera.direction_pdf(&Ray::new(&v.p, &outgoing)))
+                .unwrap_or(0.0),
+            Kind::Surface { rec, .. } => {
+                let prev = prev.expect("surface vertices follow another vertex");
+                let incoming = -v.direction_to(prev);
+                rec.mat.pdf(rec, &incoming, &outgoing)
+            }
+        };
+        v.convert_density(pdf, next)
+    }
+
+    // The weight of joining light vertex `s` to camera vertex `t` among all
+    // the ways of building the same path: the density of this way over the
+    // sum of them all. `sampled` stands in for the vertex picked on a light
+    // (`s` = 1) or the lens (`t` = 1).
+    fn mis_weight(
+        &self,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        sampled: Option<&Vertex>,
+        s: usize,
+        t: usize,
+    ) -> Float {
+        if s + t == 2 {
+            return 1.0;
+        }
+
+        let qs = match s {
+            0 => None,
+            1 => sampled,
+            _ => Some(&light_path[s - 1]),
+        };
+        let pt = if t == 1 {
+            sampled.expect("joining to the camera samples the lens")
+        } else {
+            &camera_path[t - 1]
+        };
+        let qs_minus = if s > 1 {
+            Some(&light_path[s - 2])
+        } else {
+            None
+        };
+        let pt_minus = if t > 1 {
+            Some(&camera_path[t - 2])
+        } else {
+            None
+        };
+
+        // The reverse densities change around the join.
+        let pt_rev = match qs {
+            Some(qs) => self.pdf(qs, qs_minus, pt),
+            None => self.pdf_light_origin(pt, pt_minus.expect("camera paths reach a light")),
+        };
+        let pt_minus_rev = pt_minus.map(|pt_minus| match qs {
+            Some(qs) => self.pdf(pt, Some(qs), pt_minus),
+            None => self.pdf_light(pt, pt_minus),
+        });
+        let qs_rev = qs.map(|qs| self.pdf(pt, pt_minus, qs));
+        let qs_minus_rev = qs_minus.map(|qs_minus| self.pdf(qs.unwrap(), Some(pt), qs_minus));
+
+        // Each step moves the join one vertex along, which changes the
+        // density by the reverse over the forward density of that vertex.
+        // Joins at specular vertices are impossible and left out.
+        let step = |rev: Float, fwd: Float| if fwd > 0.0 { rev / fwd } else { rev };
+        let mut sum = 0.0;
+
+        let mut ratio = 1.0;
+        for i in (1..t).rev() {
+            let v = &camera_path[i];
+            let rev = if i == t - 1 {
+                pt_rev
+            } else if i == t - 2 {
+                pt_minus_rev.unwrap()
+            } else {
+                v.pdf_rev
+            };
+            ratio *= step(rev, v.pdf_fwd);
+            let delta = i != t - 1 && v.delta;
+            if !delta && !camera_path[i - 1].delta {
+                sum += ratio;
+            }
+        }
+
+        let mut ratio = 1.0;
+        for i in (0..s).rev() {
+            let (v, rev) = if i == s - 1 {
+                (qs.unwrap(), qs_rev.unwrap())
+            } else if i == s - 2 {
+                (&light_path[i], qs_minus_rev.unwrap())
+            } else {
+                (&light_path[i], light_path[i].pdf_rev)
+            };
+            ratio *= step(rev, v.pdf_fwd);
+            let delta = i != s - 1 && v.delta;
+            if !(delta || i > 0 && light_path[i - 1].delta) {
+                sum += ratio;
+            }
+        }
+
+        1.0 / (1.0 + sum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::PerspectiveCamera;
+    use crate::geometry::{HittableList, Plane, Quad, Sphere};
+    use crate::integrator::{IntegratorKind, PathIntegrator};
+    use crate::light::AreaLight;
+    use crate::material::{Dielectric, DiffuseLight, Lambertian, Material, Metal};
+    use crate::render::{render, Background, Crop};
+    use crate::sampler::{SamplerKind, SobolSampler};
+    use crate::utils::image::Image;
+    use std::rc::Rc;
+
+    // A floor under a square light facing down, with a glass and a rough
+    // metal sphere.
+    fn scene() -> (HittableList, RenderSettings) {
+        let floor: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let glass: Rc<Box<dyn Material>> = Rc::new(Box::new(Dielectric::new(1.5)));
+        let metal: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Metal::new(Color::new(0.8, 0.6, 0.4), 0.3)));
+        let light: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0))));
+        let (q, u, v) = (
+            Vec3::new(-0.5, 2.0, -0.5),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+
+        let mut world = HittableList::new();
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &floor,
+        )));
+        world.add(Box::new(Sphere::new(Vec3::new(0.0, 0.7, 0.0), 0.5, &glass)));
+        world.add(Box::new(Sphere::new(Vec3::new(1.2, 0.5, 0.3), 0.5, &metal)));
+        world.add(Box::new(Quad::new(q, u, v, &light)));
+
+        let settings = RenderSettings {
+            image_width: 6,
+            image_height: 4,
+            samples_per_pixel: 256,
+            max_depth: 6,
+            roulette_depth: 6,
+            background: Background::Solid(Color::new(0.1, 0.1, 0.1)),
+            crop: None,
+            sampler: SamplerKind::Independent,
+            integrator: IntegratorKind::Bidirectional,
+            lights: vec![Box::new(AreaLight::quad(q, u, v, &light))],
+        };
+        (world, settings)
+    }
+
+    fn mean(image: &Image) -> Float {
+        let pixels = image.pixels();
+        pixels.iter().map(|c| c.g()).sum::<Float>() / pixels.len() as Float
+    }
+
+    #[test]
+    fn agrees_with_path_tracing() {
+        random::seed(1);
+        let (world, settings) = scene();
+        let n = 20000;
+
+        // Onto the floor beside the glass sphere, and onto the metal one
+        // where it reflects the light.
+        let rays = [
+            Ray::new(&Vec3::new(0.0, 1.5, 0.0), &Vec3::new(0.6, -1.0, 0.5)),
+            Ray::new(&Vec3::new(0.8, 1.9, 0.5), &Vec3::new(0.175, -0.959, -0.204)),
+        ];
+        for r in &rays {
+            let mut path = 0.0;
+            let mut bidirectional = 0.0;
+            for _ in 0..n {
+                path += PathIntegrator.li(r, &world, &settings).g();
+                bidirectional += BidirectionalIntegrator.li(r, &world, &settings).g();
+            }
+            let (path, bidirectional) = (path / n as Float, bidirectional / n as Float);
+            assert!(
+                (bidirectional / path - 1.0).abs() < 0.05,
+                "{} vs {}",
+                bidirectional,
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn splats_match_path_tracing() {
+        random::seed(2);
+        let (world, mut settings) = scene();
+        settings.samples_per_pixel = 1024;
+        let camera = PerspectiveCamera::new(
+            Vec3::new(0.0, 1.0, 4.0),
+            Vec3::new(0.0, 0.5, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            settings.image_size(),
+            0.0,
+            4.0,
+        );
+
+        let bidirectional = render(&world, &camera, &settings);
+        settings.integrator = IntegratorKind::Path;
+        let path = render(&world, &camera, &settings);
+        assert!((mean(&bidirectional) / mean(&path) - 1.0).abs() < 0.05);
+
+        // A crop gets only its share of the light paths.
+        settings.integrator = IntegratorKind::Bidirectional;
+        settings.crop = Some(Crop {
+            x0: 0,
+            y0: 0,
+            x1: 3,
+            y1: 4,
+        });
+        let cropped = render(&world, &camera, &settings);
+        let mut half = Image::new(3, 4);
+        for y in 0..4 {
+            for x in 0..3 {
+                half.set(x, y, path.get(x, y));
+            }
+        }
+        assert!((mean(&cropped) / mean(&half) - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn connections_draw_from_the_sampler() {
+        // With every number coming from the sampler, reseeding the generator
+        // between two runs of the same sample changes nothing.
+        let (world, settings) = scene();
+        let camera = PerspectiveCamera::new(
+            Vec3::new(0.0, 1.0, 4.0),
+            Vec3::new(0.0, 0.5, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            settings.image_size(),
+            0.5,
+            4.0,
+        );
+        let r = Ray::new(&Vec3::new(0.0, 1.5, 0.0), &Vec3::new(0.6, -1.0, 0.5));
+        let run = |index: usize, seed: u64| {
+            random::seed(seed);
+            random::start_pixel_sample(0, 0, index);
+            let mut splats = Vec::new();
+            let mut splat = |position, light: Color| splats.push((position, light.g()));
+            let color =
+                BidirectionalIntegrator.li_with_camera(&r, &world, &camera, &settings, &mut splat);
+            (color.g(), splats)
+        };
+
+        random::with_sampler(Box::new(SobolSampler::new(5)), || {
+            for index in 0..64 {
+                assert_eq!(run(index, 1), run(index, 2));
+            }
+        });
+    }
+
+    #[test]
+    fn without_lights_is_path_tracing() {
+        // Only the camera path is traced, and it finds the background.
+        let (world, mut settings) = scene();
+        settings.lights.clear();
+        let r = Ray::new(&Vec3::new(0.0, 5.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        let color = BidirectionalIntegrator.li(&r, &world, &settings);
+        assert!((color.g() - 0.1).abs() < 1e-6);
+    }
+}
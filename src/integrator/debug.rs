@@ -0,0 +1,273 @@
+use super::integrator::Integrator;
+use crate::geometry::{take_test_count, HitRecord, Hittable, Ray};
+use crate::material::MaterialIds;
+use crate::render::RenderSettings;
+use crate::sampler::Dimensions;
+use crate::utils::color::Color;
+use crate::utils::random;
+use crate::utils::Float;
+use std::cell::RefCell;
+
+// The first surface seen along a camera ray.
+fn first_hit(r: &Ray, world: &dyn Hittable) -> Option<HitRecord> {
+    random::start_dimensions(Dimensions::Bounce(0));
+    world.hit(r, 0.001, Float::INFINITY)
+}
+
+/// Shows shading normals, mapped from [-1, 1] to [0, 1] in each channel.
+/// The background is black.
+pub struct NormalsIntegrator;
+
+impl Integrator for NormalsIntegrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, _settings: &RenderSettings) -> Color {
+        first_hit(r, world).map_or(Color::black(), |rec| {
+            Color::from_normal(&rec.shading_normal)
+        })
+    }
+
+    fn measures_light(&self) -> bool {
+        false
+    }
+}
+
+/// Shows the distance from the camera as gray, from black up close to white
+/// at `max_distance` and beyond, including the background.
+pub struct DepthIntegrator {
+    max_distance: Float,
+}
+
+impl DepthIntegrator {
+    pub fn new(max_distance: Float) -> Self {
+        Self { max_distance }
+    }
+}
+
+impl Integrator for DepthIntegrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, _settings: &RenderSettings) -> Color {
+        let distance =
+            first_hit(r, world).map_or(Float::INFINITY, |rec| rec.t * r.direction().length());
+        let gray = (distance / self.max_distance).min(1.0);
+        Color::new(gray, gray, gray)
+    }
+
+    fn measures_light(&self) -> bool {
+        false
+    }
+}
+
+/// Shows texture coordinates, u as red and v as green. The background is
+/// black.
+pub struct UvIntegrator;
+
+impl Integrator for UvIntegrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, _settings: &RenderSettings) -> Color {
+        first_hit(r, world).map_or(Color::black(), |rec| Color::new(rec.u, rec.v, 0.0))
+    }
+
+    fn measures_light(&self) -> bool {
+        false
+    }
+}
+
+/// Gives every material its own color, so that objects sharing a material
+/// show up alike. Materials are numbered in the order they are met, as the
+/// material id AOV numbers them, and the colors follow from the numbers, so
+/// they are the same in every run. The background is black.
+pub struct MaterialIdIntegrator {
+    ids: RefCell<MaterialIds>,
+}
+
+impl MaterialIdIntegrator {
+    pub fn new() -> Self {
+        Self {
+            ids: RefCell::new(MaterialIds::default()),
+        }
+    }
+}
+
+impl Default for MaterialIdIntegrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// An arbitrary but fixed color for material number `id`, with neighbouring
+// numbers far apart.
+pub(crate) fn material_color(id: usize) -> Color {
+    let mut h = id as u64;
+    h = (h ^ (h >> 33)).wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h = (h ^ (h >> 33)).wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    h ^= h >> 33;
+
+    let channel = |shift: u32| 0.2 + 0.8 * ((h >> shift) & 0xff) as Float / 255.0;
+    Color::new(channel(0), channel(8), channel(16))
+}
+
+impl Integrator for MaterialIdIntegrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, _settings: &RenderSettings) -> Color {
+        first_hit(r, world).map_or(Color::black(), |rec| {
+            material_color(self.ids.borrow_mut().id(&rec.mat))
+        })
+    }
+
+    fn measures_light(&self) -> bool {
+        false
+    }
+}
+
+/// Shows how much work finding the first hit takes: the number of bounding
+/// box, grid cell and primitive tests, from blue for none through green and
+/// yellow to red for `max_tests` or more.
+pub struct HeatmapIntegrator {
+    max_tests: u64,
+}
+
+impl HeatmapIntegrator {
+    pub fn new(max_tests: u64) -> Self {
+        Self { max_tests }
+    }
+}
+
+// Blue, cyan, green, yellow, red.
+const HEAT: [(Float, Float, Float); 5] = [
+    (0.0, 0.0, 1.0),
+    (0.0, 1.0, 1.0),
+    (0.0, 1.0, 0.0),
+    (1.0, 1.0, 0.0),
+    (1.0, 0.0, 0.0),
+];
+
+fn heat(x: Float) -> Color {
+    let x = x.clamp(0.0, 1.0) * (HEAT.len() - 1) as Float;
+    let i = (x as usize).min(HEAT.len() - 2);
+    let f = x - i as Float;
+    let (a, b) = (HEAT[i], HEAT[i + 1]);
+    Color::new(
+        a.0 + f * (b.0 - a.0),
+        a.1 + f * (b.1 - a.1),
+        a.2 + f * (b.2 - a.2),
+    )
+}
+
+impl Integrator for HeatmapIntegrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, _settings: &RenderSettings) -> Color {
+        take_test_count();
+        first_hit(r, world);
+        heat(take_test_count() as Float / self.max_tests as Float)
+    }
+
+    fn measures_light(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{HittableList, Sphere};
+    use crate::integrator::IntegratorKind;
+    use crate::material::{Lambertian, Material};
+    use crate::render::Background;
+    use crate::sampler::SamplerKind;
+    use crate::utils::vec3::Vec3;
+    use std::rc::Rc;
+
+    const EPSILON: Float = 0.00001;
+
+    fn settings() -> RenderSettings {
+        RenderSettings {
+            image_width: 1,
+            image_height: 1,
+            samples_per_pixel: 1,
+            max_depth: 1,
+            roulette_depth: 1,
+            background: Background::Sky,
+            crop: None,
+            sampler: SamplerKind::Independent,
+            integrator: IntegratorKind::Normals,
+            lights: Vec::new(),
+        }
+    }
+
+    fn mat() -> Rc<Box<dyn Material>> {
+        Rc::new(Box::new(Lambertian::new(Color::white())))
+    }
+
+    // Two unit spheres side by side, 5 in front of the origin.
+    fn world(left: &Rc<Box<dyn Material>>, right: &Rc<Box<dyn Material>>) -> HittableList {
+        let mut world = HittableList::new();
+        world.add(Box::new(Sphere::new(Vec3::new(-2.0, 0.0, -5.0), 1.0, left)));
+        world.add(Box::new(Sphere::new(Vec3::new(2.0, 0.0, -5.0), 1.0, right)));
+        world
+    }
+
+    fn ray_to(x: Float) -> Ray {
+        Ray::new(&Vec3::new(x, 0.0, 0.0), &Vec3::new(0.0, 0.0, -2.0))
+    }
+
+    #[test]
+    fn normals_and_depth() {
+        let mat = mat();
+        let world = world(&mat, &mat);
+
+        let normal = NormalsIntegrator.li(&ray_to(-2.0), &world, &settings());
+        assert!(
+            (normal - Color::new(0.5, 0.5, 1.0))
+                .map(Float::abs)
+                .max_component()
+                < EPSILON
+        );
+        assert!(NormalsIntegrator
+            .li(&ray_to(0.0), &world, &settings())
+            .is_black());
+
+        let depth = DepthIntegrator::new(8.0);
+        assert!((depth.li(&ray_to(2.0), &world, &settings()).r() - 0.5).abs() < EPSILON);
+        assert_eq!(depth.li(&ray_to(0.0), &world, &settings()).r(), 1.0);
+    }
+
+    #[test]
+    fn uv_at_sphere_front() {
+        let mat = mat();
+        let world = world(&mat, &mat);
+        // The front of a sphere, facing +z, is at u = 1/4 and v = 1/2.
+        let uv = UvIntegrator.li(&ray_to(2.0), &world, &settings());
+        assert!((uv.r() - 0.25).abs() < EPSILON);
+        assert!((uv.g() - 0.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn material_ids() {
+        let (a, b) = (mat(), mat());
+        let shared = world(&a, &a);
+        let separate = world(&a, &b);
+
+        let integrator = MaterialIdIntegrator::new();
+        let id = |world: &HittableList, x| integrator.li(&ray_to(x), world, &settings());
+        assert_eq!(id(&shared, -2.0), id(&shared, 2.0));
+        assert_eq!(id(&shared, -2.0), material_color(1));
+        assert_ne!(id(&separate, -2.0), id(&separate, 2.0));
+        assert_eq!(id(&separate, 2.0), material_color(2));
+        assert!(id(&separate, 0.0).is_black());
+    }
+
+    #[test]
+    fn heatmap_counts_tests() {
+        let mat = mat();
+        let mut world = world(&mat, &mat);
+        let heatmap = HeatmapIntegrator::new(4);
+
+        // Two spheres are two tests, halfway up the scale.
+        assert_eq!(heatmap.li(&ray_to(0.0), &world, &settings()), heat(0.5));
+        assert_eq!(heat(0.5), Color::new(0.0, 1.0, 0.0));
+
+        for i in 0..4 {
+            world.add(Box::new(Sphere::new(
+                Vec3::new(i as Float, 5.0, -5.0),
+                1.0,
+                &mat,
+            )));
+        }
+        assert_eq!(heatmap.li(&ray_to(0.0), &world, &settings()), heat(1.0));
+    }
+}
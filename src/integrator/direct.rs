@@ -0,0 +1,320 @@
+use super::integrator::Integrator;
+use crate::camera::Camera;
+use crate::geometry::{HitRecord, Hittable, Ray};
+use crate::render::RenderSettings;
+use crate::sampler::Dimensions;
+use crate::utils::color::Color;
+use crate::utils::random::{self, sample_1d, sample_2d};
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+const T_MIN: Float = 0.001;
+
+/// Direct lighting only: emitters and the background seen from the camera,
+/// directly or through mirrors and glass, plus their light reflected once
+/// off the first other surface. Useful to tell indirect light apart, and
+/// much less noisy in open scenes.
+///
+/// At that surface the light is found in two ways, by picking a point on
+/// one of the settings' `lights` and by scattering as the material does,
+/// and each is weighted by how likely it is to find the light compared to
+/// the other (multiple importance sampling, with the power heuristic).
+/// Emitters that are not among the lights are only found by scattering.
+pub struct DirectLightingIntegrator;
+
+impl Integrator for DirectLightingIntegrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, settings: &RenderSettings) -> Color {
+        let max_depth = settings.max_depth.max(0) as usize;
+        let mut color = Color::black();
+        let mut throughput = Color::white();
+        let mut ray = Ray::new(r.origin(), r.direction());
+
+        for depth in 0..max_depth {
+            random::start_dimensions(Dimensions::Bounce(depth));
+            let rec = match world.hit(&ray, T_MIN, Float::INFINITY) {
+                Some(rec) => rec,
+                None => return color + throughput * settings.background.color(&ray),
+            };
+
+            color += throughput * rec.mat.emitted(&rec);
+            let (attenuation, scattered) = match rec.mat.scatter(&ray, &rec) {
+                Some(scatter) => scatter,
+                None => break,
+            };
+            if rec.mat.is_specular() {
+                throughput *= attenuation;
+                ray = scattered;
+                continue;
+            }
+
+            if depth + 1 < max_depth {
+                let incoming = ray.direction().unit();
+                color += throughput * sample_light(&rec, &incoming, world, settings, depth);
+                color += throughput
+                    * attenuation
+                    * scattered_light(&rec, &incoming, &scattered, world, settings, depth);
+            }
+            break;
+        }
+
+        color
+    }
+
+    fn li_with_direct(
+        &self,
+        r: &Ray,
+        world: &dyn Hittable,
+        _camera: &dyn Camera,
+        settings: &RenderSettings,
+        _splat: &mut dyn FnMut((Float, Float), Color),
+    ) -> (Color, Option<Color>) {
+        let color = self.li(r, world, settings);
+        (color, Some(color))
+    }
+}
+
+// The light from a point picked on one of the lights, reflected at the
+// surface `depth` towards `incoming`.
+fn sample_light(
+    rec: &HitRecord,
+    incoming: &Vec3,
+    world: &dyn Hittable,
+    settings: &RenderSettings,
+    depth: usize,
+) -> Color {
+    let lights = &settings.lights;
+    if lights.is_empty() {
+        return Color::black();
+    }
+
+    // Counting the camera as vertex 0 of the path, as bidirectional path
+    // tracing does.
+    random::start_dimensions(Dimensions::LightConnection(depth + 1));
+    let count = lights.len() as Float;
+    let index = ((sample_1d() * count) as usize).min(lights.len() - 1);
+    let light = &lights[index];
+    let incident = match light.sample_incident(&rec.p, sample_2d()) {
+        Some(incident) if incident.pdf > 0.0 => incident,
+        _ => return Color::black(),
+    };
+
+    let to_light = incident.point - rec.p;
+    let direction = to_light.unit();
+    let f = rec.mat.eval(rec, incoming, &direction);
+    if f.is_black() {
+        return Color::black();
+    }
+    let t_max = if light.is_infinite() {
+        Float::INFINITY
+    } else {
+        to_light.length() - T_MIN
+    };
+    if world
+        .hit(&Ray::new(&rec.p, &direction), T_MIN, t_max)
+        .is_some()
+    {
+        return Color::black();
+    }
+
+    let pdf = incident.pdf / count;
+    let weight = power_heuristic(pdf, rec.mat.pdf(rec, incoming, &direction));
+    f * incident.radiance * (weight / pdf)
+}
+
+// The light arriving along `scattered`, which the material picked at the
+// surface `depth`, if it comes straight from an emitter or the background.
+fn scattered_light(
+    rec: &HitRecord,
+    incoming: &Vec3,
+    scattered: &Ray,
+    world: &dyn Hittable,
+    settings: &RenderSettings,
+    depth: usize,
+) -> Color {
+    let lights = &settings.lights;
+    let direction = scattered.direction().unit();
+
+    random::start_dimensions(Dimensions::Bounce(depth + 1));
+    // The light and the point on it that `sample_light` could have picked.
+    let (radiance, found) = match world.hit(scattered, T_MIN, Float::INFINITY) {
+        Some(hit) => {
+            let light = lights.iter().find(|light| light.contains(&hit));
+            (hit.mat.emitted(&hit), light.map(|light| (light, hit.p)))
+        }
+        None => {
+            let light = lights.iter().find(|light| light.is_infinite());
+            let point = rec.p + direction;
+            (
+                settings.background.color(scattered),
+                light.map(|light| (light, point)),
+            )
+        }
+    };
+    if radiance.is_black() {
+        return radiance;
+    }
+
+    let weight = match found {
+        Some((light, point)) => {
+            let pdf = light.incident_pdf(&rec.p, &point) / lights.len() as Float;
+            power_heuristic(rec.mat.pdf(rec, incoming, &direction), pdf)
+        }
+        None => 1.0,
+    };
+    radiance * weight
+}
+
+// The weight of a sample drawn with density `pdf` that another strategy
+// would have drawn with density `other`.
+fn power_heuristic(pdf: Float, other: Float) -> Float {
+    let (a, b) = (pdf * pdf, other * other);
+    if a + b > 0.0 {
+        a / (a + b)
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{HittableList, Plane, Quad};
+    use crate::integrator::IntegratorKind;
+    use crate::light::{AreaLight, EnvironmentLight};
+    use crate::material::{DiffuseLight, Lambertian, Material};
+    use crate::render::Background;
+    use crate::sampler::SamplerKind;
+    use std::rc::Rc;
+
+    const EPSILON: Float = 0.00001;
+
+    fn settings() -> RenderSettings {
+        RenderSettings {
+            image_width: 1,
+            image_height: 1,
+            samples_per_pixel: 1,
+            max_depth: 50,
+            roulette_depth: 50,
+            background: Background::Solid(Color::white()),
+            crop: None,
+            sampler: SamplerKind::Independent,
+            integrator: IntegratorKind::DirectLighting,
+            lights: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn light_sampling_agrees_with_scattering() {
+        // A small light above a floor, found far more often by picking a
+        // point on it than by scattering.
+        random::seed(1);
+        let floor: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let emitter: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0))));
+        let (q, u, v) = (
+            Vec3::new(-0.25, 2.0, -0.25),
+            Vec3::new(0.5, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 0.5),
+        );
+        let mut world = HittableList::new();
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &floor,
+        )));
+        world.add(Box::new(Quad::new(q, u, v, &emitter)));
+
+        let mut scattering = settings();
+        scattering.background = Background::Solid(Color::black());
+        let mut lit = settings();
+        lit.background = Background::Solid(Color::black());
+        lit.lights = vec![Box::new(AreaLight::quad(q, u, v, &emitter))];
+
+        let r = Ray::new(&Vec3::new(0.0, 1.0, 0.0), &Vec3::new(0.3, -1.0, 0.2));
+        let n = 50000;
+        let moments = |settings: &RenderSettings| {
+            let (mut sum, mut sum_squares) = (0.0, 0.0);
+            for _ in 0..n {
+                let g = DirectLightingIntegrator.li(&r, &world, settings).g();
+                sum += g;
+                sum_squares += g * g;
+            }
+            let mean = sum / n as Float;
+            (mean, sum_squares / n as Float - mean * mean)
+        };
+        let (mean, variance) = moments(&lit);
+        let (expected, scattering_variance) = moments(&scattering);
+        assert!(
+            (mean / expected - 1.0).abs() < 0.05,
+            "{} {}",
+            mean,
+            expected
+        );
+        assert!(variance < 0.1 * scattering_variance);
+    }
+
+    #[test]
+    fn environment_light_is_weighted() {
+        // The sky of `ignores_interreflection` as a light: the average is
+        // unchanged.
+        random::seed(2);
+        let mat: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let mut world = HittableList::new();
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &mat,
+        )));
+        let settings = RenderSettings {
+            lights: vec![Box::new(EnvironmentLight::new(
+                Background::Solid(Color::white()),
+                Vec3::new(0.0, 0.0, 0.0),
+                10.0,
+            ))],
+            ..settings()
+        };
+
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 0.0), &Vec3::new(0.3, -1.0, 0.2));
+        let n = 20000;
+        let sum: Float = (0..n)
+            .map(|_| DirectLightingIntegrator.li(&r, &world, &settings).g())
+            .sum();
+        assert!((sum / n as Float - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn ignores_interreflection() {
+        // Between two parallel diffuse planes every path bounces on and on.
+        // With only one bounce, the light is the albedo times the sky seen
+        // straight past the first plane, which here is none.
+        let mat: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let mut world = HittableList::new();
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &mat,
+        )));
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            &mat,
+        )));
+
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 0.0), &Vec3::new(0.3, -1.0, 0.2));
+        let color = DirectLightingIntegrator.li(&r, &world, &settings());
+        assert!(color.max_component() < EPSILON);
+
+        // With the upper plane gone, the sky lights the lower one directly.
+        let mut world = HittableList::new();
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &mat,
+        )));
+        let color = DirectLightingIntegrator.li(&r, &world, &settings());
+        assert!((color.g() - 0.5).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,128 @@
+use super::{
+    AmbientOcclusionIntegrator, BidirectionalIntegrator, DepthIntegrator, DirectLightingIntegrator,
+    HeatmapIntegrator, MaterialIdIntegrator, MetropolisIntegrator, NormalsIntegrator,
+    PathIntegrator, PhotonMappingIntegrator, UvIntegrator,
+};
+use crate::camera::Camera;
+use crate::geometry::{Hittable, Ray};
+use crate::render::RenderSettings;
+use crate::utils::color::Color;
+use crate::utils::Float;
+
+/// Turns a camera ray into the color of one pixel sample: a light transport
+/// algorithm, or a view of some property of the scene.
+pub trait Integrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, settings: &RenderSettings) -> Color;
+
+    /// Like `li`, with the camera at hand for integrators that connect
+    /// paths to it. Light they carry to other pixels goes to `splat`, at the
+    /// image coordinates `Camera::get_ray` takes; the renderer adds it up
+    /// over all samples and divides by the samples per pixel.
+    fn li_with_camera(
+        &self,
+        r: &Ray,
+        world: &dyn Hittable,
+        _camera: &dyn Camera,
+        settings: &RenderSettings,
+        _splat: &mut dyn FnMut((Float, Float), Color),
+    ) -> Color {
+        self.li(r, world, settings)
+    }
+
+    /// Like `li_with_camera`, also returning the part of the same light that
+    /// comes from emitters and the background seen directly or after one
+    /// bounce, for `Aov::Direct`, or `None` if the integrator does not tell
+    /// it apart.
+    fn li_with_direct(
+        &self,
+        r: &Ray,
+        world: &dyn Hittable,
+        camera: &dyn Camera,
+        settings: &RenderSettings,
+        splat: &mut dyn FnMut((Float, Float), Color),
+    ) -> (Color, Option<Color>) {
+        (self.li_with_camera(r, world, camera, settings, splat), None)
+    }
+
+    /// The number of passes the renderer splits the samples of each pixel
+    /// into, in order, calling `start_pass` before each.
+    fn passes(&self, _settings: &RenderSettings) -> usize {
+        1
+    }
+
+    /// Prepares pass `pass`, for integrators that trace light into the
+    /// scene before following camera rays.
+    fn start_pass(&mut self, _pass: usize, _world: &dyn Hittable, _settings: &RenderSettings) {}
+
+    /// Whether the result is light, which the camera's exposure scales.
+    /// Debug views return false so that they come out the same with any
+    /// camera.
+    fn measures_light(&self) -> bool {
+        true
+    }
+}
+
+/// The integrators that `RenderSettings` can select.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum IntegratorKind {
+    /// Full path tracing; see `PathIntegrator`.
+    #[default]
+    Path,
+    /// Paths traced from both the camera and the lights; see
+    /// `BidirectionalIntegrator`.
+    Bidirectional,
+    /// Camera rays gathering `photons` photons traced from the lights, within
+    /// `radius` of where they land; see `PhotonMappingIntegrator`.
+    PhotonMapping { photons: usize, radius: Float },
+    /// Photon mapping with new photons for every sample per pixel, gathered
+    /// within a radius shrinking from `radius`, which converges.
+    ProgressivePhotonMapping { photons: usize, radius: Float },
+    /// Path tracing driven by `chains` Markov chains that mutate the paths
+    /// they find, started from the brightest of `bootstrap` path samples;
+    /// see `MetropolisIntegrator`.
+    Metropolis { bootstrap: usize, chains: usize },
+    /// Light that reaches the camera after at most one diffuse or glossy
+    /// bounce, sampling the lights; see `DirectLightingIntegrator`.
+    DirectLighting,
+    /// How open the surroundings of each point are, up to `distance`.
+    AmbientOcclusion { distance: Float },
+    /// Shading normals, mapped from [-1, 1] to [0, 1].
+    Normals,
+    /// Distance from the camera, black up close and white from
+    /// `max_distance` on.
+    Depth { max_distance: Float },
+    /// Texture coordinates as red and green.
+    Uv,
+    /// A distinct color for each material.
+    MaterialId,
+    /// Intersection tests made to find the first hit, from blue for none to
+    /// red for `max_tests` or more.
+    Heatmap { max_tests: u64 },
+}
+
+impl IntegratorKind {
+    pub fn build(&self) -> Box<dyn Integrator> {
+        match *self {
+            IntegratorKind::Path => Box::new(PathIntegrator),
+            IntegratorKind::Bidirectional => Box::new(BidirectionalIntegrator),
+            IntegratorKind::PhotonMapping { photons, radius } => {
+                Box::new(PhotonMappingIntegrator::new(photons, radius))
+            }
+            IntegratorKind::ProgressivePhotonMapping { photons, radius } => {
+                Box::new(PhotonMappingIntegrator::progressive(photons, radius))
+            }
+            IntegratorKind::Metropolis { bootstrap, chains } => {
+                Box::new(MetropolisIntegrator::new(bootstrap, chains))
+            }
+            IntegratorKind::DirectLighting => Box::new(DirectLightingIntegrator),
+            IntegratorKind::AmbientOcclusion { distance } => {
+                Box::new(AmbientOcclusionIntegrator::new(distance))
+            }
+            IntegratorKind::Normals => Box::new(NormalsIntegrator),
+            IntegratorKind::Depth { max_distance } => Box::new(DepthIntegrator::new(max_distance)),
+            IntegratorKind::Uv => Box::new(UvIntegrator),
+            IntegratorKind::MaterialId => Box::new(MaterialIdIntegrator::new()),
+            IntegratorKind::Heatmap { max_tests } => Box::new(HeatmapIntegrator::new(max_tests)),
+        }
+    }
+}
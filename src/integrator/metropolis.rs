@@ -0,0 +1,312 @@
+use super::integrator::Integrator;
+use crate::camera::Camera;
+use crate::geometry::{Hittable, Ray};
+use crate::render::{ray_color, RenderSettings};
+use crate::sampler::{Dimensions, ReplaySampler};
+use crate::utils::color::Color;
+use crate::utils::random::{self, random_float, sample_2d};
+use crate::utils::Float;
+use std::cell::RefCell;
+
+// The standard deviation of small steps, in primary sample space.
+const SIGMA: Float = 0.01;
+// How often a mutation draws a whole new path instead of perturbing one.
+const LARGE_STEP_PROBABILITY: Float = 0.3;
+
+/// Metropolis light transport in primary sample space (Kelemen et al.):
+/// paths are traced as in path tracing, from random numbers that a
+/// `ReplaySampler` keeps. Markov chains then mutate those numbers, mostly by
+/// small perturbations that stay near a path once one carrying light is
+/// found, and keep each mutation with a probability that makes them visit
+/// paths in proportion to their brightness. That finds light that only
+/// narrow openings let through, which path tracing reaches only by chance.
+///
+/// Each sample of a pixel advances one of `chains` chains by one mutation,
+/// wherever in the image the chain is; the light goes to the pixels through
+/// `Camera::get_ray`'s image coordinates. The chains start from paths
+/// picked among `bootstrap` ordinary path samples by their brightness,
+/// which also fix the overall brightness of the image. Without a camera to
+/// pick pixels with, `li` traces the ray it is given as path tracing does.
+pub struct MetropolisIntegrator {
+    bootstrap: usize,
+    chains: usize,
+    // Set up on the first sample.
+    state: RefCell<Option<Chains>>,
+}
+
+struct Chains {
+    chains: Vec<Chain>,
+    // The chain the next sample advances.
+    next: usize,
+    // The mean brightness of the image, from the bootstrap samples.
+    brightness: Float,
+}
+
+struct Chain {
+    sampler: ReplaySampler,
+    current: PathSample,
+}
+
+struct PathSample {
+    // The image coordinates the path starts from.
+    position: (Float, Float),
+    color: Color,
+    // The brightness the chains follow.
+    contribution: Float,
+}
+
+impl MetropolisIntegrator {
+    pub fn new(bootstrap: usize, chains: usize) -> Self {
+        Self {
+            bootstrap,
+            chains,
+            state: RefCell::new(None),
+        }
+    }
+
+    // Picks the starting path of each chain from its share of the bootstrap
+    // samples, with probability proportional to brightness.
+    fn start_chains(
+        &self,
+        world: &dyn Hittable,
+        camera: &dyn Camera,
+        settings: &RenderSettings,
+    ) -> Chains {
+        let count = self.chains.max(1);
+        let per_chain = (self.bootstrap / count).max(1);
+        let mut chains = Vec::new();
+        let mut total = 0.0;
+
+        for _ in 0..count {
+            let mut weights = 0.0;
+            let mut chosen = None;
+            for _ in 0..per_chain {
+                let sampler = ReplaySampler::new(SIGMA, LARGE_STEP_PROBABILITY);
+                let sample = trace(&sampler, world, camera, settings);
+                let contribution = sample.contribution;
+                total += contribution;
+                weights += contribution;
+                if contribution > 0.0 && random_float() * weights < contribution {
+                    chosen = Some(Chain {
+                        sampler,
+                        current: sample,
+                    });
+                }
+            }
+            chains.extend(chosen);
+        }
+
+        Chains {
+            chains,
+            next: 0,
+            brightness: total / (count * per_chain) as Float,
+        }
+    }
+}
+
+// Traces the path that `sampler`'s current sample stands for.
+fn trace(
+    sampler: &ReplaySampler,
+    world: &dyn Hittable,
+    camera: &dyn Camera,
+    settings: &RenderSettings,
+) -> PathSample {
+    random::with_sampler(Box::new(sampler.clone()), || {
+        random::start_dimensions(Dimensions::Pixel);
+        let position = sample_2d();
+        random::start_dimensions(Dimensions::Lens);
+        let r = camera.get_ray(position.0, position.1);
+        let color = ray_color(
+            &r,
+            world,
+            &settings.background,
+            settings.max_depth,
+            settings.roulette_depth,
+        );
+        PathSample {
+            position,
+            color,
+            contribution: color.luminance(),
+        }
+    })
+}
+
+impl Integrator for MetropolisIntegrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, settings: &RenderSettings) -> Color {
+        ray_color(
+            r,
+            world,
+            &settings.background,
+            settings.max_depth,
+            settings.roulette_depth,
+        )
+    }
+
+    fn li_with_camera(
+        &self,
+        _r: &Ray,
+        world: &dyn Hittable,
+        camera: &dyn Camera,
+        settings: &RenderSettings,
+        splat: &mut dyn FnMut((Float, Float), Color),
+    ) -> Color {
+        let mut state = self.state.borrow_mut();
+        let state = state.get_or_insert_with(|| self.start_chains(world, camera, settings));
+        if state.chains.is_empty() {
+            return Color::black();
+        }
+
+        let index = state.next;
+        state.next = (index + 1) % state.chains.len();
+        let brightness = state.brightness;
+        let chain = &mut state.chains[index];
+
+        chain.sampler.start_iteration();
+        let proposed = trace(&chain.sampler, world, camera, settings);
+        let current = &chain.current;
+        let accept = (proposed.contribution / current.contribution).min(1.0);
+
+        // Both paths get the share of the light that the chance of moving
+        // gives them, rather than only the one the chain ends up on.
+        if proposed.contribution > 0.0 {
+            let weight = accept * brightness / proposed.contribution;
+            splat(proposed.position, proposed.color * weight);
+        }
+        let weight = (1.0 - accept) * brightness / current.contribution;
+        splat(current.position, current.color * weight);
+
+        if random_float() < accept {
+            chain.sampler.accept();
+            chain.current = proposed;
+        } else {
+            chain.sampler.reject();
+        }
+
+        Color::black()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::View;
+    use crate::geometry::{HittableList, Plane, Quad};
+    use crate::integrator::IntegratorKind;
+    use crate::material::{DiffuseLight, Lambertian, Material};
+    use crate::render::{render, Background};
+    use crate::sampler::SamplerKind;
+    use crate::utils::image::Image;
+    use crate::utils::vec3::Vec3;
+    use std::rc::Rc;
+
+    // A floor lit by a square light, with a ceiling in between that has a
+    // slot cut in it when `keyhole` is set, and is left out otherwise.
+    fn scene(keyhole: bool) -> (HittableList, RenderSettings) {
+        let grey: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let emitter: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0))));
+
+        let mut world = HittableList::new();
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &grey,
+        )));
+        world.add(Box::new(Quad::new(
+            Vec3::new(-0.5, 2.0, -0.5),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            &emitter,
+        )));
+        if keyhole {
+            // Two halves of a ceiling at height 1.8, with a slot 0.1 wide
+            // along z between them.
+            for &(x0, width) in &[(-5.0, 4.95), (0.05, 4.95)] {
+                world.add(Box::new(Quad::new(
+                    Vec3::new(x0, 1.8, -5.0),
+                    Vec3::new(width, 0.0, 0.0),
+                    Vec3::new(0.0, 0.0, 10.0),
+                    &grey,
+                )));
+            }
+        }
+
+        let settings = RenderSettings {
+            image_width: 6,
+            image_height: 6,
+            samples_per_pixel: 1024,
+            max_depth: 4,
+            roulette_depth: 4,
+            background: Background::Solid(Color::black()),
+            crop: None,
+            sampler: SamplerKind::Independent,
+            integrator: IntegratorKind::Path,
+            lights: Vec::new(),
+        };
+        (world, settings)
+    }
+
+    fn render_floor(world: &HittableList, settings: &RenderSettings) -> Image {
+        let view = View::new(
+            Vec3::new(0.0, 1.5, 0.1),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            60.0,
+            settings.image_size(),
+        );
+        render(world, &view.perspective(0.0, 1.0), settings)
+    }
+
+    // The green channel summed over blocks of 3 by 3 pixels.
+    fn blocks(image: &Image) -> Vec<Float> {
+        let mut sums = vec![0.0; 4];
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                sums[(y / 3) * 2 + x / 3] += image.get(x, y).g();
+            }
+        }
+        sums
+    }
+
+    fn assert_converges(keyhole: bool, seed: u64) {
+        random::seed(seed);
+        let (world, mut settings) = scene(keyhole);
+        settings.samples_per_pixel = 16384;
+        let path = blocks(&render_floor(&world, &settings));
+
+        settings.samples_per_pixel = 16384;
+        settings.integrator = IntegratorKind::Metropolis {
+            bootstrap: 500000,
+            chains: 256,
+        };
+        let metropolis = blocks(&render_floor(&world, &settings));
+
+        for (m, p) in metropolis.iter().zip(path.iter()) {
+            assert!((m / p - 1.0).abs() < 0.15, "{:?} {:?}", metropolis, path);
+        }
+    }
+
+    #[test]
+    fn converges_to_path_tracing() {
+        assert_converges(false, 1);
+    }
+
+    #[test]
+    fn converges_through_a_keyhole() {
+        assert_converges(true, 2);
+    }
+
+    #[test]
+    fn without_camera_is_path_tracing() {
+        random::seed(3);
+        let (world, settings) = scene(false);
+        let r = Ray::new(&Vec3::new(0.0, 1.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let integrator = MetropolisIntegrator::new(100, 4);
+        random::seed(4);
+        let metropolis = integrator.li(&r, &world, &settings);
+        random::seed(4);
+        let path = ray_color(&r, &world, &settings.background, 4, 4);
+        assert_eq!(metropolis.g(), path.g());
+    }
+}
@@ -0,0 +1,41 @@
+use super::integrator::Integrator;
+use crate::camera::Camera;
+use crate::geometry::{Hittable, Ray};
+use crate::render::{ray_color, ray_color_split, RenderSettings};
+use crate::utils::color::Color;
+use crate::utils::Float;
+
+/// The path tracer of the book: follows each path from the camera as the
+/// materials scatter it, up to `max_depth` surfaces, with Russian roulette
+/// from `roulette_depth` on.
+pub struct PathIntegrator;
+
+impl Integrator for PathIntegrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, settings: &RenderSettings) -> Color {
+        ray_color(
+            r,
+            world,
+            &settings.background,
+            settings.max_depth,
+            settings.roulette_depth,
+        )
+    }
+
+    fn li_with_direct(
+        &self,
+        r: &Ray,
+        world: &dyn Hittable,
+        _camera: &dyn Camera,
+        settings: &RenderSettings,
+        _splat: &mut dyn FnMut((Float, Float), Color),
+    ) -> (Color, Option<Color>) {
+        let (direct, indirect) = ray_color_split(
+            r,
+            world,
+            &settings.background,
+            settings.max_depth,
+            settings.roulette_depth,
+        );
+        (direct + indirect, Some(direct))
+    }
+}
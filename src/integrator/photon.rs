@@ -0,0 +1,364 @@
+use super::integrator::Integrator;
+use crate::geometry::{HitRecord, Hittable, Ray};
+use crate::render::RenderSettings;
+use crate::sampler::Dimensions;
+use crate::utils::color::Color;
+use crate::utils::consts::PI;
+use crate::utils::kd_tree::KdTree;
+use crate::utils::random::{self, random_float};
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+// Keeps rays from hitting the surface they leave, as in `ray_color`.
+const T_MIN: Float = 0.001;
+
+// How much of the photons found so far the progressive variant keeps with
+// each pass, between 0 and 1. Lower values shrink the radius faster.
+const ALPHA: Float = 2.0 / 3.0;
+
+/// Photon mapping: before rendering, traces `photons` photons from
+/// `RenderSettings::lights` and stores them where they land on diffuse
+/// surfaces. Camera rays follow specular surfaces such as glass and mirrors
+/// up to the first diffuse one, and take the light there from the photons
+/// within `radius` of it. Caustics come out smooth where path tracing finds
+/// them only by chance, at the cost of blurring the lighting by `radius`.
+///
+/// The progressive variant traces a new set of photons for each sample per
+/// pixel, shrinking the radius from pass to pass (Knaus and Zwicker's
+/// probabilistic progressive photon mapping), so that the blur fades and the
+/// image converges as the samples go up.
+///
+/// Only the registered lights cast photons; other emitters, and the
+/// background when no light stands for it, are seen directly or through
+/// specular surfaces but light nothing, and surfaces no photon can reach,
+/// such as ground outside the beam of an `EnvironmentLight`, stay dark.
+/// Photons and camera rays meet at most `max_depth` surfaces, and photons
+/// may be ended by Russian roulette from `roulette_depth` on.
+pub struct PhotonMappingIntegrator {
+    photons: usize,
+    radius: Float,
+    progressive: bool,
+    // The photons of the current pass, and the radius to gather them in.
+    map: KdTree<Photon>,
+    pass_radius: Float,
+}
+
+struct Photon {
+    // The unit vector along which the photon arrived.
+    incoming: Vec3,
+    // The geometric normal of the surface, facing the photon.
+    normal: Vec3,
+    power: Color,
+}
+
+impl PhotonMappingIntegrator {
+    pub fn new(photons: usize, radius: Float) -> Self {
+        Self {
+            photons,
+            radius,
+            progressive: false,
+            map: KdTree::new(Vec::new()),
+            pass_radius: radius,
+        }
+    }
+
+    pub fn progressive(photons: usize, radius: Float) -> Self {
+        Self {
+            progressive: true,
+            ..Self::new(photons, radius)
+        }
+    }
+
+    // The radius of pass `pass`, whose square shrinks by (i + alpha) / (i + 1)
+    // from each pass i to the next.
+    fn radius_for(&self, pass: usize) -> Float {
+        if !self.progressive {
+            return self.radius;
+        }
+        let shrink: Float = (1..=pass)
+            .map(|i| (i as Float + ALPHA) / (i as Float + 1.0))
+            .product();
+        self.radius * shrink.sqrt()
+    }
+
+    // Traces the photons of one pass, each carrying its share of the power of
+    // the lights.
+    fn trace_photons(
+        &self,
+        world: &dyn Hittable,
+        settings: &RenderSettings,
+    ) -> Vec<(Vec3, Photon)> {
+        let lights = &settings.lights;
+        let mut photons = Vec::new();
+        if lights.is_empty() || self.photons == 0 {
+            return photons;
+        }
+
+        let choice = 1.0 / lights.len() as Float;
+        for _ in 0..self.photons {
+            let index = ((random_float() * lights.len() as Float) as usize).min(lights.len() - 1);
+            let emission = match lights[index].sample_emission(
+                (random_float(), random_float()),
+                (random_float(), random_float()),
+            ) {
+                Some(emission) if emission.pdf_position > 0.0 && emission.pdf_direction > 0.0 => {
+                    emission
+                }
+                _ => continue,
+            };
+
+            let cosine = emission.normal.dot(emission.ray.direction()).abs();
+            let mut power = emission.radiance * cosine
+                / (choice * emission.pdf_position * emission.pdf_direction * self.photons as Float);
+            let mut ray = emission.ray;
+            for depth in 0..settings.max_depth {
+                let rec = match world.hit(&ray, T_MIN, Float::INFINITY) {
+                    Some(rec) => rec,
+                    None => break,
+                };
+
+                if !rec.mat.is_specular() {
+                    let photon = Photon {
+                        incoming: ray.direction().unit(),
+                        normal: rec.normal,
+                        power,
+                    };
+                    photons.push((rec.p, photon));
+                }
+
+                let (attenuation, scattered) = match rec.mat.scatter(&ray, &rec) {
+                    Some(scatter) => scatter,
+                    None => break,
+                };
+                power *= attenuation;
+                ray = scattered;
+
+                // As in `ray_color`, but on how much of the photon's power
+                // each bounce keeps, so the photons stay equally bright.
+                if depth + 1 >= settings.roulette_depth {
+                    let survival = attenuation.max_component().min(1.0);
+                    if random_float() >= survival {
+                        break;
+                    }
+                    power /= survival;
+                }
+            }
+        }
+
+        photons
+    }
+
+    // The light leaving `rec` against the unit vector `incoming`, from the
+    // density of the photons around it.
+    fn estimate(&self, rec: &HitRecord, incoming: &Vec3) -> Color {
+        let outgoing = -*incoming;
+        // `Material::eval` includes the cosine towards the camera, which the
+        // density of photons already accounts for.
+        let cosine = outgoing.dot(&rec.shading_normal).abs();
+        if cosine <= 0.0 {
+            return Color::black();
+        }
+
+        let mut sum = Color::black();
+        self.map
+            .for_each_within(&rec.p, self.pass_radius, |_, photon| {
+                // Photons on the other side of a thin surface light only that
+                // side.
+                if photon.normal.dot(&rec.normal) > 0.0 {
+                    sum += rec.mat.eval(rec, &photon.incoming, &outgoing) * photon.power;
+                }
+            });
+        sum / (cosine * PI * self.pass_radius * self.pass_radius)
+    }
+}
+
+impl Integrator for PhotonMappingIntegrator {
+    fn li(&self, r: &Ray, world: &dyn Hittable, settings: &RenderSettings) -> Color {
+        let mut color = Color::black();
+        let mut throughput = Color::white();
+        let mut ray = Ray::new(r.origin(), r.direction());
+
+        for depth in 0..settings.max_depth {
+            random::start_dimensions(Dimensions::Bounce(depth as usize));
+            let rec = match world.hit(&ray, T_MIN, Float::INFINITY) {
+                Some(rec) => rec,
+                None => {
+                    color += throughput * settings.background.color(&ray);
+                    break;
+                }
+            };
+
+            color += throughput * rec.mat.emitted(&rec);
+            if !rec.mat.is_specular() {
+                color += throughput * self.estimate(&rec, &ray.direction().unit());
+                break;
+            }
+
+            let (attenuation, scattered) = match rec.mat.scatter(&ray, &rec) {
+                Some(scatter) => scatter,
+                None => break,
+            };
+            throughput *= attenuation;
+            ray = scattered;
+        }
+
+        color
+    }
+
+    fn passes(&self, settings: &RenderSettings) -> usize {
+        if self.progressive {
+            settings.samples_per_pixel.max(1) as usize
+        } else {
+            1
+        }
+    }
+
+    fn start_pass(&mut self, pass: usize, world: &dyn Hittable, settings: &RenderSettings) {
+        self.map = KdTree::new(self.trace_photons(world, settings));
+        self.pass_radius = self.radius_for(pass);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::View;
+    use crate::geometry::{HittableList, Plane, Quad, Sphere};
+    use crate::integrator::{IntegratorKind, PathIntegrator};
+    use crate::light::AreaLight;
+    use crate::material::{Dielectric, DiffuseLight, Lambertian, Material};
+    use crate::render::{render, Background};
+    use crate::sampler::SamplerKind;
+    use crate::utils::image::Image;
+    use std::rc::Rc;
+
+    // A floor lit from above by a square light.
+    fn scene() -> (HittableList, RenderSettings) {
+        let floor: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let emitter: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0))));
+        let (q, u, v) = (
+            Vec3::new(-0.5, 2.0, -0.5),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+
+        let mut world = HittableList::new();
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &floor,
+        )));
+        world.add(Box::new(Quad::new(q, u, v, &emitter)));
+
+        let settings = RenderSettings {
+            image_width: 4,
+            image_height: 4,
+            samples_per_pixel: 64,
+            max_depth: 4,
+            roulette_depth: 4,
+            background: Background::Solid(Color::black()),
+            crop: None,
+            sampler: SamplerKind::Independent,
+            integrator: IntegratorKind::Path,
+            lights: vec![Box::new(AreaLight::quad(q, u, v, &emitter))],
+        };
+        (world, settings)
+    }
+
+    fn mean(image: &Image) -> Float {
+        let mut sum = 0.0;
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                sum += image.get(x, y).g();
+            }
+        }
+        sum / (image.width() * image.height()) as Float
+    }
+
+    fn render_floor(settings: &RenderSettings, world: &HittableList) -> Image {
+        let view = View::new(
+            Vec3::new(0.0, 1.5, 0.1),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            settings.image_size(),
+        );
+        render(world, &view.perspective(0.0, 1.0), settings)
+    }
+
+    #[test]
+    fn agrees_with_bidirectional() {
+        // Bidirectional path tracing joins the floor to the light directly,
+        // which makes for a quieter reference than path tracing.
+        random::seed(1);
+        let (world, mut settings) = scene();
+        settings.integrator = IntegratorKind::Bidirectional;
+        let bidirectional = mean(&render_floor(&settings, &world));
+
+        settings.integrator = IntegratorKind::PhotonMapping {
+            photons: 50000,
+            radius: 0.1,
+        };
+        let photons = mean(&render_floor(&settings, &world));
+        assert!(
+            (photons / bidirectional - 1.0).abs() < 0.03,
+            "{} {}",
+            photons,
+            bidirectional
+        );
+
+        settings.integrator = IntegratorKind::ProgressivePhotonMapping {
+            photons: 5000,
+            radius: 0.1,
+        };
+        let progressive = mean(&render_floor(&settings, &world));
+        assert!(
+            (progressive / bidirectional - 1.0).abs() < 0.03,
+            "{} {}",
+            progressive,
+            bidirectional
+        );
+    }
+
+    #[test]
+    fn glass_focuses_photons() {
+        // A glass ball between the light and the floor focuses its light
+        // onto the floor under it.
+        random::seed(2);
+        let (open, settings) = scene();
+        let (mut world, _) = scene();
+        let glass: Rc<Box<dyn Material>> = Rc::new(Box::new(Dielectric::new(1.5)));
+        world.add(Box::new(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 0.4, &glass)));
+
+        let r = Ray::new(&Vec3::new(0.0, 0.5, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let photons = |world: &HittableList| {
+            let mut integrator = PhotonMappingIntegrator::new(200000, 0.1);
+            integrator.start_pass(0, world, &settings);
+            integrator.li(&r, world, &settings).g()
+        };
+        let focused = photons(&world);
+        let unfocused = photons(&open);
+
+        let n = 200000;
+        let path = (0..n)
+            .map(|_| PathIntegrator.li(&r, &world, &settings).g())
+            .sum::<Float>()
+            / n as Float;
+        assert!((focused / path - 1.0).abs() < 0.1, "{} {}", focused, path);
+        assert!(focused > 1.5 * unfocused, "{} {}", focused, unfocused);
+    }
+
+    #[test]
+    fn radius_shrinks_when_progressive() {
+        let fixed = PhotonMappingIntegrator::new(1, 0.5);
+        assert_eq!(fixed.radius_for(10), 0.5);
+
+        let progressive = PhotonMappingIntegrator::progressive(1, 0.5);
+        assert_eq!(progressive.radius_for(0), 0.5);
+        let radii: Vec<Float> = (0..100).map(|i| progressive.radius_for(i)).collect();
+        assert!(radii.windows(2).all(|w| w[1] < w[0]));
+        assert!(radii[99] < 0.25);
+    }
+}
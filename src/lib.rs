@@ -1,4 +1,14 @@
+pub mod animation;
+pub mod aov;
 pub mod camera;
+pub mod denoise;
 pub mod geometry;
+pub mod integrator;
+pub mod light;
 pub mod material;
+pub mod render;
+pub mod sampler;
+pub mod scenes;
+pub mod sdf;
+pub mod texture;
 pub mod utils;
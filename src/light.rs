@@ -0,0 +1,8 @@
+mod area;
+mod environment;
+#[allow(clippy::module_inception)]
+mod light;
+
+pub use area::AreaLight;
+pub use environment::EnvironmentLight;
+pub use light::{Emission, Incident, Light};
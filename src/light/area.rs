@@ -0,0 +1,204 @@
+use super::light::{Emission, Incident, Light};
+use crate::geometry::{sphere_uv, HitRecord, Ray};
+use crate::material::Material;
+use crate::utils::color::Color;
+use crate::utils::consts::PI;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+// How far from the shape, relative to its size, a hit still counts as on it.
+const TOLERANCE: Float = 1e-4;
+
+enum Shape {
+    Quad { q: Vec3, u: Vec3, v: Vec3 },
+    Sphere { center: Vec3, radius: Float },
+}
+
+/// A quad or sphere of emitting material, standing for the same shape in
+/// the world. Light is sent out from its front, the side its normal faces.
+pub struct AreaLight {
+    shape: Shape,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl AreaLight {
+    /// The light of `Quad::new(q, u, v, mat)`.
+    pub fn quad(q: Vec3, u: Vec3, v: Vec3, mat: &Rc<Box<dyn Material>>) -> Self {
+        Self {
+            shape: Shape::Quad { q, u, v },
+            mat: Rc::clone(mat),
+        }
+    }
+
+    /// The light of `Sphere::new(center, radius, mat)`.
+    pub fn sphere(center: Vec3, radius: Float, mat: &Rc<Box<dyn Material>>) -> Self {
+        Self {
+            shape: Shape::Sphere { center, radius },
+            mat: Rc::clone(mat),
+        }
+    }
+
+    fn area(&self) -> Float {
+        match &self.shape {
+            Shape::Quad { u, v, .. } => u.cross(v).length(),
+            Shape::Sphere { radius, .. } => 4.0 * PI * radius * radius,
+        }
+    }
+
+    fn normal_at(&self, p: &Vec3) -> Vec3 {
+        match &self.shape {
+            Shape::Quad { u, v, .. } => u.cross(v).unit(),
+            Shape::Sphere { center, radius } => (*p - *center) / *radius,
+        }
+    }
+
+    // A uniform point on the shape, with its surface coordinates.
+    fn sample_point(&self, (s, t): (Float, Float)) -> (Vec3, (Float, Float)) {
+        match &self.shape {
+            Shape::Quad { q, u, v } => (*q + s * *u + t * *v, (s, t)),
+            Shape::Sphere { center, radius } => {
+                let n = Vec3::uniform_on_sphere((s, t));
+                (*center + *radius * n, sphere_uv(&n))
+            }
+        }
+    }
+
+    // The light the material sends from `p` along `direction`.
+    fn radiance(&self, p: &Vec3, uv: (Float, Float), direction: &Vec3) -> Color {
+        let r = Ray::new(&(*p + *direction), &-*direction);
+        let rec = HitRecord::new(*p, 1.0, uv, &r, &self.normal_at(p), &self.mat);
+        self.mat.emitted(&rec)
+    }
+}
+
+impl Light for AreaLight {
+    fn sample_emission(
+        &self,
+        u_position: (Float, Float),
+        u_direction: (Float, Float),
+    ) -> Option<Emission> {
+        let (p, uv) = self.sample_point(u_position);
+        let normal = self.normal_at(&p);
+
+        // Cosine-distributed about the normal, as Lambertian scatters.
+        let mut direction = normal + Vec3::uniform_on_sphere(u_direction);
+        if direction.near_zero() {
+            direction = normal;
+        }
+        let direction = direction.unit();
+
+        Some(Emission {
+            ray: Ray::new(&p, &direction),
+            normal,
+            radiance: self.radiance(&p, uv, &direction),
+            pdf_position: 1.0 / self.area(),
+            pdf_direction: direction.dot(&normal).max(0.0) / PI,
+        })
+    }
+
+    fn emission_pdf(&self, p: &Vec3, direction: &Vec3) -> (Float, Float) {
+        let cosine = direction.dot(&self.normal_at(p)).max(0.0);
+        (1.0 / self.area(), cosine / PI)
+    }
+
+    fn sample_incident(&self, p: &Vec3, u: (Float, Float)) -> Option<Incident> {
+        let (point, uv) = self.sample_point(u);
+        let normal = self.normal_at(&point);
+        let to_light = point - *p;
+        let distance_squared = to_light.length_squared();
+        let direction = to_light.unit();
+
+        let cosine = -direction.dot(&normal);
+        if cosine <= 0.0 {
+            return None;
+        }
+
+        Some(Incident {
+            point,
+            normal,
+            radiance: self.radiance(&point, uv, &-direction),
+            pdf: distance_squared / (cosine * self.area()),
+        })
+    }
+
+    fn incident_pdf(&self, p: &Vec3, point: &Vec3) -> Float {
+        let to_light = *point - *p;
+        let cosine = -to_light.unit().dot(&self.normal_at(point));
+        if cosine <= 0.0 {
+            return 0.0;
+        }
+        to_light.length_squared() / (cosine * self.area())
+    }
+
+    fn contains(&self, rec: &HitRecord) -> bool {
+        match &self.shape {
+            Shape::Quad { q, u, v } => {
+                let n = u.cross(v);
+                let w = n / n.dot(&n);
+                let planar = rec.p - *q;
+                let alpha = w.dot(&planar.cross(v));
+                let beta = w.dot(&u.cross(&planar));
+                let inside = -TOLERANCE..=1.0 + TOLERANCE;
+                let off_plane = planar.dot(&n.unit()).abs();
+
+                off_plane < TOLERANCE * (u.length() + v.length())
+                    && inside.contains(&alpha)
+                    && inside.contains(&beta)
+            }
+            Shape::Sphere { center, radius } => {
+                ((rec.p - *center).length() - radius).abs() < TOLERANCE * radius
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Hittable, Quad};
+    use crate::material::DiffuseLight;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn incident_pdf_matches_emission_pdf() {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(DiffuseLight::new(Color::white())));
+        let (q, u, v) = (
+            Vec3::new(-1.0, 2.0, -1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+        );
+        let light = AreaLight::quad(q, u, v, &mat);
+        let quad = Quad::new(q, u, v, &mat);
+        let p = Vec3::new(0.3, 0.0, 0.2);
+
+        let incident = light.sample_incident(&p, (0.25, 0.75)).unwrap();
+        assert!((incident.radiance.g() - 1.0).abs() < EPSILON);
+
+        // The density per solid angle is the one per area, times the
+        // distance squared over the cosine at the light.
+        let to_light = incident.point - p;
+        let (pdf_position, _) = light.emission_pdf(&incident.point, &-to_light.unit());
+        let cosine = to_light.unit().dot(&incident.normal).abs();
+        let expected = pdf_position * to_light.length_squared() / cosine;
+        assert!((incident.pdf - expected).abs() < EPSILON);
+        assert!((light.incident_pdf(&p, &incident.point) - incident.pdf).abs() < EPSILON);
+
+        // The quad in the world is found to be the light.
+        let rec = quad
+            .hit(&Ray::new(&p, &to_light), 0.001, Float::INFINITY)
+            .unwrap();
+        assert!(light.contains(&rec));
+        assert!(!light.contains(&HitRecord { p, ..rec }));
+    }
+
+    #[test]
+    fn back_emits_nothing() {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(DiffuseLight::new(Color::white())));
+        let light = AreaLight::sphere(Vec3::new(0.0, 0.0, 0.0), 1.0, &mat);
+        assert!(light
+            .sample_incident(&Vec3::new(0.0, 0.5, 0.0), (0.5, 0.5))
+            .is_none());
+    }
+}
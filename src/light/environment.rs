@@ -0,0 +1,85 @@
+use super::light::{Emission, Incident, Light};
+use crate::geometry::Ray;
+use crate::render::Background;
+use crate::utils::consts::PI;
+use crate::utils::onb::Onb;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// The background as a light, shining on a scene that fits in the sphere
+/// of `radius` around `center`. Rays of light start on a disk across the
+/// sphere, so a tight fit wastes fewer of them; anything outside it is lit
+/// only as the camera finds it. `background` must be the one the scene is
+/// rendered with.
+pub struct EnvironmentLight {
+    background: Background,
+    center: Vec3,
+    radius: Float,
+}
+
+impl EnvironmentLight {
+    pub fn new(background: Background, center: Vec3, radius: Float) -> Self {
+        Self {
+            background,
+            center,
+            radius,
+        }
+    }
+
+    fn pdf_disk(&self) -> Float {
+        1.0 / (PI * self.radius * self.radius)
+    }
+}
+
+impl Light for EnvironmentLight {
+    fn sample_emission(
+        &self,
+        u_position: (Float, Float),
+        u_direction: (Float, Float),
+    ) -> Option<Emission> {
+        // Towards the background, and a point on the disk facing it.
+        let away = Vec3::uniform_on_sphere(u_direction);
+        let frame = Onb::from_w(&away);
+        let disk = Vec3::uniform_in_disk(u_position);
+        let origin =
+            self.center + self.radius * (away + disk.x() * *frame.u() + disk.y() * *frame.v());
+
+        Some(Emission {
+            ray: Ray::new(&origin, &-away),
+            normal: -away,
+            radiance: self.background.color(&Ray::new(&self.center, &away)),
+            pdf_position: self.pdf_disk(),
+            pdf_direction: 1.0 / (4.0 * PI),
+        })
+    }
+
+    fn emission_pdf(&self, p: &Vec3, direction: &Vec3) -> (Float, Float) {
+        // Rays along `direction` cover the cylinder of the disk.
+        let offset = *p - self.center;
+        let across = offset - offset.dot(direction) * *direction;
+        let pdf_position = if across.length_squared() <= self.radius * self.radius {
+            self.pdf_disk()
+        } else {
+            0.0
+        };
+        (pdf_position, 1.0 / (4.0 * PI))
+    }
+
+    fn sample_incident(&self, p: &Vec3, u: (Float, Float)) -> Option<Incident> {
+        let away = Vec3::uniform_on_sphere(u);
+        Some(Incident {
+            point: *p + away,
+            normal: Vec3::new(0.0, 0.0, 0.0),
+            radiance: self.background.color(&Ray::new(p, &away)),
+            pdf: 1.0 / (4.0 * PI),
+        })
+    }
+
+    fn incident_pdf(&self, _p: &Vec3, _point: &Vec3) -> Float {
+        1.0 / (4.0 * PI)
+    }
+
+    fn is_infinite(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,67 @@
+use crate::geometry::{HitRecord, Ray};
+use crate::utils::color::Color;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// A ray of light leaving a light, as `Light::sample_emission` starts it.
+pub struct Emission {
+    /// Starts on the light, with a unit direction.
+    pub ray: Ray,
+    pub normal: Vec3,
+    pub radiance: Color,
+    /// The density of the origin per unit area.
+    pub pdf_position: Float,
+    /// The density of the direction per unit solid angle.
+    pub pdf_direction: Float,
+}
+
+/// Light reaching a point from a light, as `Light::sample_incident` picks
+/// it.
+pub struct Incident {
+    /// The point on the light. For lights at infinity, the point one unit
+    /// away in their direction.
+    pub point: Vec3,
+    /// The normal of the light at `point`, zero for lights at infinity.
+    pub normal: Vec3,
+    pub radiance: Color,
+    /// The density of the direction per unit solid angle at the receiving
+    /// point.
+    pub pdf: Float,
+}
+
+/// A source of light that paths can be started from, for integrators that
+/// trace light forwards. Emitting surfaces still need an emitting material
+/// in the world; the light describes how to pick points on them.
+pub trait Light {
+    /// Starts a ray of light, with `u_position` and `u_direction` uniform
+    /// in the unit square.
+    fn sample_emission(
+        &self,
+        u_position: (Float, Float),
+        u_direction: (Float, Float),
+    ) -> Option<Emission>;
+
+    /// The densities with which `sample_emission` starts a ray at `p` along
+    /// the unit vector `direction`. For lights at infinity, `p` is any point
+    /// the ray passes instead.
+    fn emission_pdf(&self, p: &Vec3, direction: &Vec3) -> (Float, Float);
+
+    /// Picks light arriving at `p`, with `u` uniform in the unit square.
+    fn sample_incident(&self, p: &Vec3, u: (Float, Float)) -> Option<Incident>;
+
+    /// The density per unit solid angle with which `sample_incident` picks
+    /// the light from `point` on the light arriving at `p`. For lights at
+    /// infinity, `point` is any point away from `p` in their direction.
+    fn incident_pdf(&self, p: &Vec3, point: &Vec3) -> Float;
+
+    /// Whether the light surrounds the scene from infinitely far away, and
+    /// is seen where rays leave the scene.
+    fn is_infinite(&self) -> bool {
+        false
+    }
+
+    /// Whether the surface hit in `rec` is this light.
+    fn contains(&self, _rec: &HitRecord) -> bool {
+        false
+    }
+}
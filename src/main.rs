@@ -1,102 +1,67 @@
-use rand::prelude::random;
-use raytracing_in_one_weekend::camera::Camera;
-use raytracing_in_one_weekend::geometry::{Hittable, HittableList, Ray, Sphere};
-use raytracing_in_one_weekend::material::{Dielectric, Lambertian, Material, Metal};
-use raytracing_in_one_weekend::utils::color::{write_color, Color};
+use raytracing_in_one_weekend::aov::Aov;
+use raytracing_in_one_weekend::camera::View;
+use raytracing_in_one_weekend::denoise::DenoiseSettings;
+use raytracing_in_one_weekend::integrator::IntegratorKind;
+use raytracing_in_one_weekend::render::{render_with_aovs, Background, RenderSettings};
+use raytracing_in_one_weekend::sampler::SamplerKind;
+use raytracing_in_one_weekend::scenes::random_scene;
 use raytracing_in_one_weekend::utils::vec3::Vec3;
+use std::env;
 use std::io::{self, BufWriter};
-use std::rc::Rc;
 
-fn ray_color<T: Hittable>(r: &Ray, world: &T, depth: i32) -> Color {
-    // If we've exceeded the ray bounce limit, no more light is gathered.
-    if depth <= 0 {
-        return Color::new(0.0, 0.0, 0.0);
-    }
-
-    if let Some(rec) = world.hit(r, 0.001, f64::INFINITY) {
-        return if let Some((attenuation, scattered)) = rec.mat.as_ref().scatter(r, &rec) {
-            attenuation * ray_color(&scattered, world, depth - 1)
-        } else {
-            Color::new(0.0, 0.0, 0.0)
-        };
-    }
+const USAGE: &str = "usage: raytracing_in_one_weekend [--samples N] [--denoise] > out.ppm";
 
-    let unit_direction = r.direction().unit();
-    let t = 0.5 * (unit_direction.y() + 1.0);
-    (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0)
+struct Options {
+    samples_per_pixel: i32,
+    // Whether to render the albedo, normal and variance AOVs and denoise
+    // the image with them.
+    denoise: bool,
 }
 
-fn random_scene() -> HittableList {
-    let mut world = HittableList::new();
-
-    let ground_material: Rc<Box<dyn Material>> =
-        Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
-    world.add(Box::new(Sphere::new(
-        Vec3::new(0.0, -1000.0, 0.0),
-        1000.0,
-        &ground_material,
-    )));
-
-    for a in -11..11 {
-        for b in -11..11 {
-            let choose_mat: f64 = random();
-            let center = Vec3::new(
-                (a as f64) + 0.9 * random::<f64>(),
-                0.2,
-                (b as f64) + 0.9 * random::<f64>(),
-            );
-
-            let sphere_material: Rc<Box<dyn Material>> = if choose_mat < 0.8 {
-                // diffuse
-                let albedo = Color::new_random(0.0, 1.0) * Color::new_random(0.0, 1.0);
-                Rc::new(Box::new(Lambertian::new(albedo)))
-            } else if choose_mat < 0.95 {
-                // metal
-                let albedo = Color::new_random(0.5, 1.0);
-                let fuzz = 0.5 * random::<f64>();
-                Rc::new(Box::new(Metal::new(albedo, fuzz)))
-            } else {
-                // grass
-                Rc::new(Box::new(Dielectric::new(1.5)))
-            };
-
-            world.add(Box::new(Sphere::new(center, 0.2, &sphere_material)));
+fn parse_options() -> io::Result<Options> {
+    let mut options = Options {
+        samples_per_pixel: 500,
+        denoise: false,
+    };
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--samples" => {
+                let value = args.next().unwrap_or_default();
+                options.samples_per_pixel = match value.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(invalid(format!("invalid sample count '{}'", value))),
+                };
+            }
+            "--denoise" => options.denoise = true,
+            _ => return Err(invalid(format!("unknown argument '{}'\n{}", arg, USAGE))),
         }
     }
 
-    let material1: Rc<Box<dyn Material>> = Rc::new(Box::new(Dielectric::new(1.5)));
-    world.add(Box::new(Sphere::new(
-        Vec3::new(0.0, 1.0, 0.0),
-        1.0,
-        &material1,
-    )));
-
-    let material2: Rc<Box<dyn Material>> =
-        Rc::new(Box::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))));
-    world.add(Box::new(Sphere::new(
-        Vec3::new(-4.0, 1.0, 0.0),
-        1.0,
-        &material2,
-    )));
-
-    let material3: Rc<Box<dyn Material>> =
-        Rc::new(Box::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)));
-    world.add(Box::new(Sphere::new(
-        Vec3::new(4.0, 1.0, 0.0),
-        1.0,
-        &material3,
-    )));
-
-    world
+    Ok(options)
 }
 
 fn main() -> io::Result<()> {
+    let options = parse_options()?;
+
     // Image
     let aspect_ratio = 3.0 / 2.0;
     let image_width: usize = 1200;
-    let image_height: usize = (image_width as f64 / aspect_ratio) as usize;
-    let samples_per_pixel = 500;
-    let max_depth = 50;
+    let image_height = RenderSettings::height_for(image_width, aspect_ratio);
+    let settings = RenderSettings {
+        image_width,
+        image_height,
+        samples_per_pixel: options.samples_per_pixel,
+        max_depth: 50,
+        roulette_depth: 5,
+        background: Background::Sky,
+        crop: None,
+        sampler: SamplerKind::Independent,
+        integrator: IntegratorKind::Path,
+        lights: Vec::new(),
+    };
 
     // World
     let world = random_scene();
@@ -104,44 +69,36 @@ fn main() -> io::Result<()> {
     // Camera
     let lookfrom = Vec3::new(13.0, 2.0, 3.0);
     let lookat = Vec3::new(0.0, 0.0, 0.0);
-    let dist_to_focus = 10.0;
     let aperture = 0.1;
 
-    let camera = Camera::new(
+    let view = View::new(
         lookfrom,
         lookat,
         Vec3::new(0.0, 1.0, 0.0),
         20.0,
-        aspect_ratio,
-        aperture,
-        dist_to_focus,
+        settings.image_size(),
     );
+    let camera = view.perspective(aperture, view.focus_on(&lookat));
 
     // Render
-    println!("P3");
-    println!("{} {}", image_width, image_height);
-    println!("255");
-
-    let mut writer = BufWriter::new(io::stdout());
-
-    for h in (0..image_height).rev() {
+    let aovs: &[Aov] = if options.denoise {
+        &[Aov::Albedo, Aov::Normal, Aov::Variance]
+    } else {
+        &[]
+    };
+    let layers = render_with_aovs(&world, &camera, &settings, aovs, |h| {
         eprintln!("Scan lines remaining: {}", h);
-
-        for w in 0..image_width {
-            let mut pixel_color = Color::new(0.0, 0.0, 0.0);
-
-            for _ in 0..samples_per_pixel {
-                let u = (w as f64 + random::<f64>()) / (image_width - 1) as f64;
-                let v = (h as f64 + random::<f64>()) / (image_height - 1) as f64;
-
-                let r = camera.get_ray(u, v);
-
-                pixel_color += ray_color(&r, &world, max_depth);
-            }
-
-            write_color(&mut writer, &pixel_color, samples_per_pixel)?;
+    });
+    let image = match layers.denoised(&DenoiseSettings::default()) {
+        Some(denoised) => {
+            eprintln!("Denoised.");
+            denoised
         }
-    }
+        None => layers.color,
+    };
+
+    let mut writer = BufWriter::new(io::stdout());
+    image.write_ppm(&mut writer)?;
 
     eprintln!("Done.");
 
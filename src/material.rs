@@ -1,9 +1,17 @@
+mod bump_map;
 mod dielectric;
+mod diffuse_light;
 mod lambertian;
+#[allow(clippy::module_inception)]
 mod material;
 mod metal;
+mod normal_map;
 
+pub use bump_map::BumpMap;
 pub use dielectric::Dielectric;
+pub use diffuse_light::DiffuseLight;
 pub use lambertian::Lambertian;
 pub use material::Material;
+pub(crate) use material::MaterialIds;
 pub use metal::Metal;
+pub use normal_map::NormalMap;
@@ -0,0 +1,149 @@
+use super::material::Material;
+use crate::geometry::HitRecord;
+use crate::geometry::Ray;
+use crate::texture::Texture;
+use crate::utils::color::Color;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+// Step used to differentiate the height texture.
+const DELTA: Float = 1e-3;
+
+/// Wraps a material, tilting the shading normal by the slope of a height
+/// texture before scattering.
+///
+/// The height is the luminance of the texture, scaled by `scale`. It is
+/// differentiated by stepping `DELTA` along `u` and `v` and, for procedural
+/// textures, the matching distance along `dp/du` and `dp/dv` in world space.
+/// As in pbrt, the slopes are divided by the lengths of those derivatives,
+/// so that a bump tilts the normal by the same amount however the surface is
+/// parametrized.
+pub struct BumpMap {
+    height: Rc<Box<dyn Texture>>,
+    scale: Float,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl BumpMap {
+    pub fn new(height: &Rc<Box<dyn Texture>>, scale: Float, mat: &Rc<Box<dyn Material>>) -> Self {
+        Self {
+            height: Rc::clone(height),
+            scale,
+            mat: Rc::clone(mat),
+        }
+    }
+
+    fn perturb(&self, rec: &HitRecord) -> HitRecord {
+        let (tangent, bitangent) = rec.tangents();
+        // Surfaces without derivatives take one unit of `u` or `v` to be one
+        // unit of distance.
+        let dpdu = if rec.dpdu.near_zero() {
+            tangent
+        } else {
+            rec.dpdu
+        };
+        let dpdv = if rec.dpdv.near_zero() {
+            bitangent
+        } else {
+            rec.dpdv
+        };
+        let height = |du: Float, dv: Float| {
+            let p = rec.p + du * dpdu + dv * dpdv;
+            self.scale * self.height.value(rec.u + du, rec.v + dv, &p).luminance()
+        };
+
+        let h = height(0.0, 0.0);
+        // Slopes per unit of distance along the surface.
+        let slope_u = (height(DELTA, 0.0) - h) / DELTA / dpdu.length();
+        let slope_v = (height(0.0, DELTA) - h) / DELTA / dpdv.length();
+
+        let n = (rec.shading_normal - slope_u * tangent - slope_v * bitangent).unit();
+        let outward = if rec.front_face { n } else { -n };
+        rec.clone().with_shading_normal(&outward)
+    }
+}
+
+impl Material for BumpMap {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        self.mat.scatter(r_in, &self.perturb(rec))
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        self.mat.emitted(rec)
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.mat.albedo(rec)
+    }
+
+    fn eval(&self, rec: &HitRecord, incoming: &Vec3, outgoing: &Vec3) -> Color {
+        self.mat.eval(&self.perturb(rec), incoming, outgoing)
+    }
+
+    fn pdf(&self, rec: &HitRecord, incoming: &Vec3, outgoing: &Vec3) -> Float {
+        self.mat.pdf(&self.perturb(rec), incoming, outgoing)
+    }
+
+    fn is_specular(&self) -> bool {
+        self.mat.is_specular()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Metal;
+    use crate::utils::vec3::Vec3;
+
+    const EPSILON: Float = 0.0001;
+
+    // Height rising along u, by the given amount per unit.
+    struct Ramp(Float);
+
+    impl Texture for Ramp {
+        fn value(&self, u: Float, _v: Float, _p: &Vec3) -> Color {
+            let h = self.0 * u;
+            Color::new(h, h, h)
+        }
+    }
+
+    // Mirrors a ray straight down onto the xy plane, bumped by `ramp` and
+    // with `dp/du` along x, returning the reflected direction.
+    fn reflect_off(ramp: Ramp, dpdu: Float) -> Vec3 {
+        let mirror: Rc<Box<dyn Material>> = Rc::new(Box::new(Metal::new(Color::white(), 0.0)));
+        let ramp: Rc<Box<dyn Texture>> = Rc::new(Box::new(ramp));
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(BumpMap::new(&ramp, 0.5, &mirror)));
+
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 1.0), &Vec3::new(0.0, 0.0, -1.0));
+        let rec = HitRecord::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            1.0,
+            (0.5, 0.5),
+            &r,
+            &Vec3::new(0.0, 0.0, 1.0),
+            &mat,
+        )
+        .with_tangents(&Vec3::new(dpdu, 0.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+
+        let (_, scattered) = mat.scatter(&r, &rec).unwrap();
+        scattered.direction().unit()
+    }
+
+    #[test]
+    fn slope_tilts_normal() {
+        // A slope of 0.5 along +x turns the normal to (-0.5, 0, 1), which
+        // mirrors the ray to (-0.8, 0, 0.6).
+        let d = reflect_off(Ramp(1.0), 1.0);
+        assert!((d - Vec3::new(-0.8, 0.0, 0.6)).length() < EPSILON);
+    }
+
+    #[test]
+    fn slope_ignores_parametrization() {
+        // Both bumps rise by one per unit of x, once over a u that follows x
+        // and once over a u that runs at half the speed.
+        let d = reflect_off(Ramp(2.0), 2.0);
+        assert!((d - reflect_off(Ramp(1.0), 1.0)).length() < EPSILON);
+        assert!((d - Vec3::new(-0.8, 0.0, 0.6)).length() < EPSILON);
+    }
+}
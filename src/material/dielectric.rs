@@ -1,22 +1,46 @@
 use super::material::Material;
 use crate::geometry::{HitRecord, Ray};
 use crate::utils::color::Color;
-use rand::random;
+use crate::utils::random::sample_1d;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
 
 pub struct Dielectric {
-    ir: f64, // Index of Refraction
+    ir: Float, // Index of Refraction
 }
 
 impl Dielectric {
-    pub fn new(ir: f64) -> Self {
+    pub fn new(ir: Float) -> Self {
         Dielectric { ir }
     }
 
-    fn reflectance(&self, cosine: f64, ref_idx: f64) -> f64 {
+    fn reflectance(&self, cosine: Float, ref_idx: Float) -> Float {
         // Use Schlick's approximation for reflectance.
         let r0 = ((1.0 - ref_idx) / (1.0 + ref_idx)).powi(2);
         r0 + (1.0 - r0) * ((1.0 - cosine).powi(5))
     }
+
+    // Reflects or refracts about `normal`, choosing reflection when `sample`
+    // falls below the Fresnel reflectance. Returns the direction and whether
+    // it was a reflection.
+    fn scatter_about(
+        &self,
+        unit_direction: &Vec3,
+        normal: &Vec3,
+        refraction_ratio: Float,
+        sample: Float,
+    ) -> (Vec3, bool) {
+        let cos_theta = (-unit_direction.dot(normal)).min(1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+        let cannot_refract = refraction_ratio * sin_theta > 1.0;
+
+        if cannot_refract || self.reflectance(cos_theta, refraction_ratio) > sample {
+            (unit_direction.reflect(normal), true)
+        } else {
+            (unit_direction.refract(normal, refraction_ratio), false)
+        }
+    }
 }
 
 impl Material for Dielectric {
@@ -28,18 +52,29 @@ impl Material for Dielectric {
         };
 
         let unit_direction = r_in.direction().unit();
-        let cos_theta = (-unit_direction.dot(&rec.normal)).min(1.0);
-        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
-
-        let cannot_refract = refraction_ratio * sin_theta > 1.0;
+        let sample = sample_1d();
+        let (mut direction, reflected) = self.scatter_about(
+            &unit_direction,
+            &rec.shading_normal,
+            refraction_ratio,
+            sample,
+        );
 
-        let direction =
-            if cannot_refract || self.reflectance(cos_theta, refraction_ratio) > random() {
-                unit_direction.reflect(&rec.normal)
-            } else {
-                unit_direction.refract(&rec.normal, refraction_ratio)
-            };
+        // A reflection must stay on the incoming side of the geometric surface
+        // and a refraction must cross it. Otherwise the shading normal would
+        // leak light, so fall back to the geometric normal.
+        let outgoing = direction.dot(&rec.normal);
+        if (reflected && outgoing <= 0.0) || (!reflected && outgoing >= 0.0) {
+            direction = self
+                .scatter_about(&unit_direction, &rec.normal, refraction_ratio, sample)
+                .0;
+        }
 
         Some((Color::new(1.0, 1.0, 1.0), Ray::new(&rec.p, &direction)))
     }
+
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        // Clear glass loses no light, between reflection and refraction.
+        Color::white()
+    }
 }
@@ -0,0 +1,29 @@
+use super::material::Material;
+use crate::geometry::HitRecord;
+use crate::geometry::Ray;
+use crate::utils::color::Color;
+
+pub struct DiffuseLight {
+    emit: Color,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord) -> Option<(Color, Ray)> {
+        None
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        // Only the front face emits light.
+        if rec.front_face {
+            self.emit
+        } else {
+            Color::black()
+        }
+    }
+}
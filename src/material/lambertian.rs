@@ -2,7 +2,10 @@ use super::material::Material;
 use crate::geometry::HitRecord;
 use crate::geometry::Ray;
 use crate::utils::color::Color;
+use crate::utils::consts::PI;
+use crate::utils::random::sample_2d;
 use crate::utils::vec3::Vec3;
+use crate::utils::Float;
 
 pub struct Lambertian {
     albedo: Color,
@@ -16,14 +19,111 @@ impl Lambertian {
 
 impl Material for Lambertian {
     fn scatter(&self, _r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
-        let mut scatter_direction = rec.normal + Vec3::new_random_unit_vector();
+        let mut scatter_direction = rec.shading_normal + Vec3::uniform_on_sphere(sample_2d());
 
         // Catch degenerate scatter direction
         if scatter_direction.near_zero() {
-            scatter_direction = rec.normal;
+            scatter_direction = rec.shading_normal;
+        }
+
+        // A shading normal tilted away from the geometric one can send the
+        // ray into the surface, which would leak light through it. Mirror
+        // such rays back out instead of absorbing them, so the facets do not
+        // turn black.
+        let below = scatter_direction.dot(&rec.normal);
+        if below <= 0.0 {
+            scatter_direction = scatter_direction - 2.0 * below * rec.normal;
         }
 
         let scattered = Ray::new(&rec.p, &scatter_direction);
         Some((self.albedo, scattered))
     }
+
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        self.albedo
+    }
+
+    fn eval(&self, rec: &HitRecord, incoming: &Vec3, outgoing: &Vec3) -> Color {
+        // `scatter` returns the albedo, so the density carries the cosine.
+        self.pdf(rec, incoming, outgoing) * self.albedo
+    }
+
+    fn pdf(&self, rec: &HitRecord, _incoming: &Vec3, outgoing: &Vec3) -> Float {
+        let above = outgoing.dot(&rec.normal);
+        if above <= 0.0 {
+            return 0.0;
+        }
+
+        // Cosine-distributed about the shading normal, with the directions
+        // that fell below the surface mirrored back up onto `outgoing`.
+        let mirrored = *outgoing - 2.0 * above * rec.normal;
+        let cosine = outgoing.dot(&rec.shading_normal).max(0.0);
+        let cosine_mirrored = mirrored.dot(&rec.shading_normal).max(0.0);
+        (cosine + cosine_mirrored) / PI
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Hittable, Plane};
+    use std::rc::Rc;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn eval_over_pdf_is_attenuation() {
+        let albedo = Color::new(0.2, 0.4, 0.6);
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(albedo)));
+        let plane = Plane::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &mat);
+        let r = Ray::new(&Vec3::new(0.0, 1.0, 0.0), &Vec3::new(0.3, -1.0, 0.0));
+        let rec = plane.hit(&r, 0.001, Float::INFINITY).unwrap();
+        let tilted = rec
+            .clone()
+            .with_shading_normal(&Vec3::new(0.5, 1.0, 0.0).unit());
+
+        let incoming = r.direction().unit();
+        for rec in &[rec, tilted] {
+            for _ in 0..100 {
+                let (attenuation, scattered) = mat.scatter(&r, rec).unwrap();
+                let outgoing = scattered.direction().unit();
+                let pdf = mat.pdf(rec, &incoming, &outgoing);
+                assert!(pdf > 0.0);
+                let ratio = mat.eval(rec, &incoming, &outgoing) / pdf;
+                assert!((ratio.r() - attenuation.r()).abs() < EPSILON);
+                assert!((ratio.b() - attenuation.b()).abs() < EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn pdf_integrates_to_one() {
+        // Even with the shading normal tilted, so that part of the lobe is
+        // mirrored back above the surface.
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        let plane = Plane::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &mat);
+        let r = Ray::new(&Vec3::new(0.0, 1.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let rec = plane
+            .hit(&r, 0.001, Float::INFINITY)
+            .unwrap()
+            .with_shading_normal(&Vec3::new(0.7, 1.0, 0.0).unit());
+
+        let n = 200;
+        let mut total = 0.0;
+        for i in 0..n {
+            for j in 0..n {
+                let u = (
+                    (i as Float + 0.5) / n as Float,
+                    (j as Float + 0.5) / n as Float,
+                );
+                let w = Vec3::uniform_on_sphere(u);
+                total += mat.pdf(&rec, r.direction(), &w) * 4.0 * PI;
+            }
+        }
+        assert!((total / (n * n) as Float - 1.0).abs() < 0.01);
+    }
 }
@@ -1,7 +1,61 @@
 use crate::geometry::HitRecord;
 use crate::geometry::Ray;
 use crate::utils::color::Color;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::collections::HashMap;
+use std::rc::Rc;
 
 pub trait Material {
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)>;
+
+    fn emitted(&self, _rec: &HitRecord) -> Color {
+        Color::black()
+    }
+
+    /// The color of the surface at `rec`, whatever the directions: the
+    /// fraction of light it reflects or lets through overall, for feature
+    /// buffers such as the albedo AOV. Surfaces that only emit are black.
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        Color::black()
+    }
+
+    /// The fraction of light arriving along the unit vector `incoming` that
+    /// leaves along `outgoing`, per unit solid angle: the BSDF times the
+    /// cosine of `outgoing`. The attenuation `scatter` returns is this
+    /// divided by `pdf`.
+    fn eval(&self, _rec: &HitRecord, _incoming: &Vec3, _outgoing: &Vec3) -> Color {
+        Color::black()
+    }
+
+    /// The density per unit solid angle with which `scatter`, for a ray
+    /// arriving along `incoming`, picks `outgoing`.
+    fn pdf(&self, _rec: &HitRecord, _incoming: &Vec3, _outgoing: &Vec3) -> Float {
+        0.0
+    }
+
+    /// Whether `scatter` picks from a few exact directions, like a mirror,
+    /// so that `eval` and `pdf` are of no use. Materials that do not
+    /// implement them must say so too, which is the default.
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+/// Numbers materials from 1 in the order they are first met, so that views
+/// telling materials apart, such as the material id AOV and integrator,
+/// agree with each other and from one run to the next.
+#[derive(Default)]
+pub(crate) struct MaterialIds {
+    // The numbers given so far, by the material's address, which changes
+    // between runs and so is never shown.
+    ids: HashMap<usize, usize>,
+}
+
+impl MaterialIds {
+    pub(crate) fn id(&mut self, mat: &Rc<Box<dyn Material>>) -> usize {
+        let next = self.ids.len() + 1;
+        let address = Rc::as_ptr(mat) as *const u8 as usize;
+        *self.ids.entry(address).or_insert(next)
+    }
 }
@@ -2,26 +2,43 @@ use super::material::Material;
 use crate::geometry::HitRecord;
 use crate::geometry::Ray;
 use crate::utils::color::Color;
+use crate::utils::consts::PI;
+use crate::utils::random::{sample_1d, sample_2d};
 use crate::utils::vec3::Vec3;
+use crate::utils::Float;
 
 pub struct Metal {
     albedo: Color,
-    fuzz: f64,
+    fuzz: Float,
 }
 
 impl Metal {
-    pub fn new(albedo: Color, fuzz: f64) -> Self {
+    pub fn new(albedo: Color, fuzz: Float) -> Self {
         Self { albedo, fuzz }
     }
+
+    // The mirror direction, about the geometric normal where the shading
+    // normal would send it below the surface.
+    fn reflected(incoming: &Vec3, rec: &HitRecord) -> Vec3 {
+        let reflected = incoming.reflect(&rec.shading_normal);
+        if reflected.dot(&rec.normal) <= 0.0 {
+            incoming.reflect(&rec.normal)
+        } else {
+            reflected
+        }
+    }
 }
 
 impl Material for Metal {
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
-        let reflected = r_in.direction().unit().reflect(&rec.normal);
-        let scattered = Ray::new(
-            &rec.p,
-            &(reflected + self.fuzz * Vec3::new_random_in_unit_sphere()),
-        );
+        let unit_direction = r_in.direction().unit();
+        let mut reflected = unit_direction.reflect(&rec.shading_normal);
+        if reflected.dot(&rec.normal) <= 0.0 {
+            // The shading normal would reflect into the surface.
+            reflected = unit_direction.reflect(&rec.normal);
+        }
+        let fuzz = Vec3::uniform_in_ball(sample_2d(), sample_1d());
+        let scattered = Ray::new(&rec.p, &(reflected + self.fuzz * fuzz));
 
         if scattered.direction().dot(&rec.normal) > 0.0 {
             Some((self.albedo, scattered))
@@ -29,4 +46,87 @@ impl Material for Metal {
             None
         }
     }
+
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        self.albedo
+    }
+
+    fn eval(&self, rec: &HitRecord, incoming: &Vec3, outgoing: &Vec3) -> Color {
+        // `scatter` returns the albedo, so the density carries the cosine.
+        self.pdf(rec, incoming, outgoing) * self.albedo
+    }
+
+    fn pdf(&self, rec: &HitRecord, incoming: &Vec3, outgoing: &Vec3) -> Float {
+        if outgoing.dot(&rec.normal) <= 0.0 {
+            return 0.0;
+        }
+
+        // The scattered direction points from the origin to a uniform point
+        // in the ball of radius `fuzz` around the unit mirror direction.
+        // Its density is the volume of the cone of directions around
+        // `outgoing` within the ball, per unit solid angle, over the volume
+        // of the ball: the integral of t^2 along the chord [t0, t1].
+        let along = outgoing.dot(&Self::reflected(incoming, rec));
+        let discriminant = along * along - (1.0 - self.fuzz * self.fuzz);
+        if discriminant <= 0.0 {
+            return 0.0;
+        }
+        let half = discriminant.sqrt();
+        let t0 = (along - half).max(0.0);
+        let t1 = (along + half).max(0.0);
+
+        (t1.powi(3) - t0.powi(3)) / (4.0 * PI * self.fuzz.powi(3))
+    }
+
+    fn is_specular(&self) -> bool {
+        self.fuzz <= 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Hittable, Plane};
+    use crate::material::Material;
+    use std::rc::Rc;
+
+    #[test]
+    fn pdf_integrates_to_one() {
+        // For fuzz below and above 1, where the ball around the mirror
+        // direction holds the origin. Directions scattered below the surface
+        // are absorbed, which takes part of the density with them.
+        let r = Ray::new(&Vec3::new(0.0, 1.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        for &fuzz in &[0.3, 1.5] {
+            let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Metal::new(Color::white(), fuzz)));
+            let plane = Plane::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &mat);
+            let rec = plane.hit(&r, 0.001, Float::INFINITY).unwrap();
+
+            let n = 400;
+            let mut total = 0.0;
+            for i in 0..n {
+                for j in 0..n {
+                    let u = (
+                        (i as Float + 0.5) / n as Float,
+                        (j as Float + 0.5) / n as Float,
+                    );
+                    let w = Vec3::uniform_on_sphere(u);
+                    total += mat.pdf(&rec, r.direction(), &w) * 4.0 * PI;
+                }
+            }
+            let integral = total / (n * n) as Float;
+
+            let trials = 20000;
+            let kept = (0..trials)
+                .filter(|_| mat.scatter(&r, &rec).is_some())
+                .count();
+            let expected = kept as Float / trials as Float;
+            assert!(
+                (integral - expected).abs() < 0.02,
+                "fuzz {}: {} vs {}",
+                fuzz,
+                integral,
+                expected
+            );
+        }
+    }
 }
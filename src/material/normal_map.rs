@@ -0,0 +1,114 @@
+use super::material::Material;
+use crate::geometry::HitRecord;
+use crate::geometry::Ray;
+use crate::texture::Texture;
+use crate::utils::color::Color;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// Wraps a material, replacing the shading normal with one read from a
+/// tangent-space normal map before scattering.
+///
+/// Each texel encodes a unit vector in the `(tangent, bitangent, normal)`
+/// frame of the hit as `0.5 * (n + 1)`, the usual convention for normal map
+/// images, so a flat map is `(0.5, 0.5, 1.0)`.
+pub struct NormalMap {
+    map: Rc<Box<dyn Texture>>,
+    mat: Rc<Box<dyn Material>>,
+}
+
+impl NormalMap {
+    pub fn new(map: &Rc<Box<dyn Texture>>, mat: &Rc<Box<dyn Material>>) -> Self {
+        Self {
+            map: Rc::clone(map),
+            mat: Rc::clone(mat),
+        }
+    }
+
+    fn perturb(&self, rec: &HitRecord) -> HitRecord {
+        let c = self.map.value(rec.u, rec.v, &rec.p);
+        let local = Vec3::new(2.0 * c.r() - 1.0, 2.0 * c.g() - 1.0, 2.0 * c.b() - 1.0);
+        let (tangent, bitangent) = rec.tangents();
+        let n = local.x() * tangent + local.y() * bitangent + local.z() * rec.shading_normal;
+        if n.near_zero() {
+            return rec.clone();
+        }
+
+        let outward = if rec.front_face { n.unit() } else { -n.unit() };
+        rec.clone().with_shading_normal(&outward)
+    }
+}
+
+impl Material for NormalMap {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+        self.mat.scatter(r_in, &self.perturb(rec))
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        self.mat.emitted(rec)
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.mat.albedo(rec)
+    }
+
+    fn eval(&self, rec: &HitRecord, incoming: &Vec3, outgoing: &Vec3) -> Color {
+        self.mat.eval(&self.perturb(rec), incoming, outgoing)
+    }
+
+    fn pdf(&self, rec: &HitRecord, incoming: &Vec3, outgoing: &Vec3) -> Float {
+        self.mat.pdf(&self.perturb(rec), incoming, outgoing)
+    }
+
+    fn is_specular(&self) -> bool {
+        self.mat.is_specular()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Metal;
+    use crate::texture::SolidColor;
+    use crate::utils::Float;
+
+    const EPSILON: Float = 0.00001;
+
+    // The direction a mirror wrapped in a normal map of constant `c`
+    // reflects a ray arriving straight down onto the z = 0 plane, whose
+    // tangents are x and y.
+    fn reflect(c: Color) -> Vec3 {
+        let mirror: Rc<Box<dyn Material>> = Rc::new(Box::new(Metal::new(Color::white(), 0.0)));
+        let map: Rc<Box<dyn Texture>> = Rc::new(Box::new(SolidColor::new(c)));
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(NormalMap::new(&map, &mirror)));
+
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 1.0), &Vec3::new(0.0, 0.0, -1.0));
+        let rec = HitRecord::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            1.0,
+            (0.5, 0.5),
+            &r,
+            &Vec3::new(0.0, 0.0, 1.0),
+            &mat,
+        )
+        .with_tangents(&Vec3::new(1.0, 0.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+
+        let (_, scattered) = mat.scatter(&r, &rec).unwrap();
+        scattered.direction().unit()
+    }
+
+    #[test]
+    fn flat_map_keeps_normal() {
+        let d = reflect(Color::new(0.5, 0.5, 1.0));
+        assert!((d - Vec3::new(0.0, 0.0, 1.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn map_tilts_towards_tangent() {
+        // A normal tilted 45 degrees towards +x mirrors the ray along +x.
+        let s = 0.5 * (0.5 as Float).sqrt();
+        let d = reflect(Color::new(0.5 + s, 0.5, 0.5 + s));
+        assert!((d - Vec3::new(1.0, 0.0, 0.0)).length() < EPSILON);
+    }
+}
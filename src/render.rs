@@ -0,0 +1,480 @@
+use crate::aov::{Aov, AovBuffers, Layers};
+use crate::camera::Camera;
+use crate::geometry::{Hittable, Ray};
+use crate::integrator::IntegratorKind;
+use crate::light::Light;
+use crate::sampler::{Dimensions, SamplerKind};
+use crate::utils::color::Color;
+use crate::utils::image::Image;
+use crate::utils::random::{self, sample_1d, sample_2d};
+use crate::utils::Float;
+
+/// What a ray sees when it leaves the scene without hitting anything.
+#[derive(Copy, Clone, Debug)]
+pub enum Background {
+    /// The white-to-blue gradient of the book.
+    Sky,
+    Solid(Color),
+}
+
+impl Background {
+    pub fn color(&self, r: &Ray) -> Color {
+        match self {
+            Background::Sky => {
+                let unit_direction = r.direction().unit();
+                let t = 0.5 * (unit_direction.y() + 1.0);
+                (1.0 - t) * Color::white() + t * Color::new(0.5, 0.7, 1.0)
+            }
+            Background::Solid(color) => *color,
+        }
+    }
+}
+
+/// A sub-rectangle of the image, in pixels from the top-left corner. The
+/// right and bottom edges `x1` and `y1` are exclusive.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Crop {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl Crop {
+    pub fn width(&self) -> usize {
+        self.x1 - self.x0
+    }
+
+    pub fn height(&self) -> usize {
+        self.y1 - self.y0
+    }
+}
+
+pub struct RenderSettings {
+    pub image_width: usize,
+    pub image_height: usize,
+    pub samples_per_pixel: i32,
+    /// The most surfaces a path may meet.
+    pub max_depth: i32,
+    /// The number of bounces after which paths may be ended early by
+    /// Russian roulette. Paths that carry little light are likely to be
+    /// ended, and the survivors are weighted up so the image stays the same
+    /// on average.
+    pub roulette_depth: i32,
+    pub background: Background,
+    /// Renders only this part of the image. The result has the size of the
+    /// crop and matches the same pixels of the full image.
+    pub crop: Option<Crop>,
+    pub sampler: SamplerKind,
+    pub integrator: IntegratorKind,
+    /// The lights that integrators tracing light forwards start from. Their
+    /// emitting surfaces must be in the world as well.
+    pub lights: Vec<Box<dyn Light>>,
+}
+
+impl RenderSettings {
+    /// The height of an image `width` pixels wide at `aspect_ratio`,
+    /// rounded to the nearest pixel.
+    pub fn height_for(width: usize, aspect_ratio: Float) -> usize {
+        ((width as Float / aspect_ratio).round() as usize).max(1)
+    }
+
+    /// The width and height of the whole image, which cameras are built
+    /// from so that pixels come out square.
+    pub fn image_size(&self) -> (usize, usize) {
+        (self.image_width, self.image_height)
+    }
+
+    /// The part of the image that is rendered: the crop, or else the whole
+    /// image. Panics if the crop is empty or reaches outside the image.
+    pub fn crop_window(&self) -> Crop {
+        let crop = self.crop.unwrap_or(Crop {
+            x0: 0,
+            y0: 0,
+            x1: self.image_width,
+            y1: self.image_height,
+        });
+        assert!(
+            crop.x0 < crop.x1 && crop.x1 <= self.image_width,
+            "crop columns {}..{} outside the image",
+            crop.x0,
+            crop.x1
+        );
+        assert!(
+            crop.y0 < crop.y1 && crop.y1 <= self.image_height,
+            "crop rows {}..{} outside the image",
+            crop.y0,
+            crop.y1
+        );
+        crop
+    }
+}
+
+/// The light arriving along `r`, following the path it starts through at
+/// most `max_depth` surfaces. From `roulette_depth` bounces on, the path may
+/// be ended at random (see `RenderSettings::roulette_depth`).
+pub fn ray_color<T: Hittable + ?Sized>(
+    r: &Ray,
+    world: &T,
+    background: &Background,
+    max_depth: i32,
+    roulette_depth: i32,
+) -> Color {
+    trace_path(r, world, background, max_depth, roulette_depth).0
+}
+
+/// Like `ray_color`, split into direct light, which comes from emitters and
+/// the background seen along `r` or after one bounce, and the indirect light
+/// of the longer paths.
+pub fn ray_color_split<T: Hittable + ?Sized>(
+    r: &Ray,
+    world: &T,
+    background: &Background,
+    max_depth: i32,
+    roulette_depth: i32,
+) -> (Color, Color) {
+    let (color, direct) = trace_path(r, world, background, max_depth, roulette_depth);
+    (direct, color - direct)
+}
+
+// The light along `r` for `ray_color`, and the part of it gathered before the
+// path meets its third surface.
+fn trace_path<T: Hittable + ?Sized>(
+    r: &Ray,
+    world: &T,
+    background: &Background,
+    max_depth: i32,
+    roulette_depth: i32,
+) -> (Color, Color) {
+    let mut color = Color::black();
+    let mut direct = None;
+    // The fraction of light at the current vertex that reaches the camera.
+    let mut throughput = Color::white();
+    let mut ray = Ray::new(r.origin(), r.direction());
+
+    // Past the bounce limit, no more light is gathered.
+    for depth in 0..max_depth {
+        if depth == 2 {
+            direct = Some(color);
+        }
+        random::start_dimensions(Dimensions::Bounce(depth as usize));
+        let rec = match world.hit(&ray, 0.001, Float::INFINITY) {
+            Some(rec) => rec,
+            None => {
+                color += throughput * background.color(&ray);
+                break;
+            }
+        };
+
+        color += throughput * rec.mat.emitted(&rec);
+        let (attenuation, scattered) = match rec.mat.scatter(&ray, &rec) {
+            Some(scatter) => scatter,
+            None => break,
+        };
+        throughput *= attenuation;
+        ray = scattered;
+
+        // Continue with probability equal to the throughput, up to 1, and
+        // divide by it, so that the expected contribution is unchanged.
+        if depth + 1 >= roulette_depth {
+            let survival = throughput.max_component().min(1.0);
+            if sample_1d() >= survival {
+                break;
+            }
+            throughput /= survival;
+        }
+    }
+
+    (color, direct.unwrap_or(color))
+}
+
+pub fn render<T: Hittable, C: Camera>(world: &T, camera: &C, settings: &RenderSettings) -> Image {
+    render_with_progress(world, camera, settings, |_| {})
+}
+
+/// Like `render`, calling `on_scanline` with the number of scan lines
+/// remaining before each one is traced.
+pub fn render_with_progress<T, C, F>(
+    world: &T,
+    camera: &C,
+    settings: &RenderSettings,
+    on_scanline: F,
+) -> Image
+where
+    T: Hittable,
+    C: Camera,
+    F: FnMut(usize),
+{
+    render_with_aovs(world, camera, settings, &[], on_scanline).color
+}
+
+/// Like `render_with_progress`, rendering the AOVs in `aovs` as well, each
+/// the size of the image.
+pub fn render_with_aovs<T, C, F>(
+    world: &T,
+    camera: &C,
+    settings: &RenderSettings,
+    aovs: &[Aov],
+    mut on_scanline: F,
+) -> Layers
+where
+    T: Hittable,
+    C: Camera,
+    F: FnMut(usize),
+{
+    let image_width = settings.image_width;
+    let image_height = settings.image_height;
+    let crop = settings.crop_window();
+    let mut image = Image::new(crop.width(), crop.height());
+    // The sum of the samples of each pixel.
+    let mut sums = vec![Color::black(); crop.width() * crop.height()];
+    // Light that integrators carry to pixels other than the one sampled.
+    let mut splats = vec![Color::black(); crop.width() * crop.height()];
+    let mut aov_buffers = AovBuffers::new(aovs, crop.width() * crop.height());
+    let sampler = settings
+        .sampler
+        .build(settings.samples_per_pixel.max(1) as usize);
+    let mut integrator = settings.integrator.build();
+    let exposure = if integrator.measures_light() {
+        camera.exposure()
+    } else {
+        1.0
+    };
+    let samples_per_pixel = settings.samples_per_pixel.max(0) as usize;
+    let passes = integrator.passes(settings).max(1);
+
+    random::with_sampler(sampler, || {
+        for pass in 0..passes {
+            random::without_sampler(|| integrator.start_pass(pass, world, settings));
+
+            // Each pass takes the next share of the samples of every pixel.
+            let samples =
+                pass * samples_per_pixel / passes..(pass + 1) * samples_per_pixel / passes;
+            for y in crop.y0..crop.y1 {
+                on_scanline((passes - 1 - pass) * crop.height() + crop.y1 - 1 - y);
+
+                // Rows count up from the bottom of the image in camera coordinates.
+                let h = image_height - 1 - y;
+                for x in crop.x0..crop.x1 {
+                    let pixel = (y - crop.y0) * crop.width() + x - crop.x0;
+                    let mut pixel_color = Color::black();
+
+                    for s in samples.clone() {
+                        random::start_pixel_sample(x, y, s);
+
+                        // Pixel `x` covers [x, x + 1) / width, so its center is at
+                        // (x + 0.5) / width.
+                        random::start_dimensions(Dimensions::Pixel);
+                        let (du, dv) = sample_2d();
+                        let u = (x as Float + du) / image_width as Float;
+                        let v = (h as Float + dv) / image_height as Float;
+
+                        random::start_dimensions(Dimensions::Lens);
+                        let r = camera.get_ray(u, v);
+
+                        let (color, direct) = integrator.li_with_direct(
+                            &r,
+                            world,
+                            camera,
+                            settings,
+                            &mut |(s, t), color| {
+                                let (x, y) = splat_pixel(s, t, image_width, image_height);
+                                if (crop.x0..crop.x1).contains(&x)
+                                    && (crop.y0..crop.y1).contains(&y)
+                                {
+                                    splats[(y - crop.y0) * crop.width() + x - crop.x0] += color;
+                                }
+                            },
+                        );
+                        pixel_color += color;
+                        aov_buffers.add_sample(pixel, s, &r, world, color, direct);
+                    }
+
+                    sums[pixel] += pixel_color;
+                }
+            }
+        }
+    });
+
+    // Each sample may splat anywhere in the image, so a crop gets fewer
+    // splats than its share of the whole.
+    let samples = settings.samples_per_pixel as Float;
+    let share = (crop.width() * crop.height()) as Float / (image_width * image_height) as Float;
+    for y in 0..crop.height() {
+        for x in 0..crop.width() {
+            let i = y * crop.width() + x;
+            let color = sums[i] / samples + splats[i] / (samples * share);
+            image.set(x, y, color * exposure);
+        }
+    }
+
+    Layers {
+        color: image,
+        aovs: aov_buffers.finish(crop.width(), crop.height(), samples, exposure),
+    }
+}
+
+// The pixel at image coordinates `s` and `t`, as `Camera::get_ray` takes
+// them. Both may round up to 1 just inside the image, which still counts as
+// the last pixel.
+fn splat_pixel(s: Float, t: Float, width: usize, height: usize) -> (usize, usize) {
+    let x = ((s * width as Float) as usize).min(width - 1);
+    let y = height - 1 - ((t * height as Float) as usize).min(height - 1);
+    (x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{HittableList, Plane, Quad};
+    use crate::material::{Material, Metal};
+    use crate::utils::vec3::Vec3;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const EPSILON: Float = 0.00001;
+
+    // Records the range of image coordinates it is asked for.
+    struct RecordingCamera {
+        range: RefCell<(Float, Float, Float, Float)>,
+    }
+
+    impl RecordingCamera {
+        fn new() -> Self {
+            Self {
+                range: RefCell::new((
+                    Float::INFINITY,
+                    Float::NEG_INFINITY,
+                    Float::INFINITY,
+                    Float::NEG_INFINITY,
+                )),
+            }
+        }
+    }
+
+    impl Camera for RecordingCamera {
+        fn get_ray(&self, s: Float, t: Float) -> Ray {
+            let mut range = self.range.borrow_mut();
+            *range = (
+                range.0.min(s),
+                range.1.max(s),
+                range.2.min(t),
+                range.3.max(t),
+            );
+            Ray::new(&Vec3::new(0.0, 0.0, 0.0), &Vec3::new(0.0, 0.0, -1.0))
+        }
+    }
+
+    fn settings(crop: Option<Crop>) -> RenderSettings {
+        RenderSettings {
+            image_width: 4,
+            image_height: 2,
+            samples_per_pixel: 64,
+            max_depth: 1,
+            roulette_depth: 1,
+            background: Background::Sky,
+            crop,
+            sampler: SamplerKind::Independent,
+            integrator: IntegratorKind::Path,
+            lights: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn samples_cover_pixels_exactly() {
+        let camera = RecordingCamera::new();
+        let image = render(&HittableList::new(), &camera, &settings(None));
+        assert_eq!((image.width(), image.height()), (4, 2));
+
+        let (s0, s1, t0, t1) = *camera.range.borrow();
+        assert!((0.0..0.05).contains(&s0) && (0.95..=1.0).contains(&s1));
+        assert!((0.0..0.05).contains(&t0) && (0.95..=1.0).contains(&t1));
+    }
+
+    #[test]
+    fn splats_at_the_edges_stay_in_the_image() {
+        assert_eq!(splat_pixel(0.0, 0.0, 4, 2), (0, 1));
+        assert_eq!(splat_pixel(1.0, 1.0, 4, 2), (3, 0));
+        assert_eq!(splat_pixel(0.5, 0.49, 4, 2), (2, 1));
+    }
+
+    #[test]
+    fn crop_renders_sub_rectangle() {
+        let camera = RecordingCamera::new();
+        let crop = Crop {
+            x0: 1,
+            y0: 0,
+            x1: 3,
+            y1: 1,
+        };
+        let image = render(&HittableList::new(), &camera, &settings(Some(crop)));
+        assert_eq!((image.width(), image.height()), (2, 1));
+
+        // The middle two columns of the top row.
+        let (s0, s1, t0, t1) = *camera.range.borrow();
+        assert!(s0 >= 0.25 && s1 <= 0.75);
+        assert!(t0 >= 0.5 && t1 <= 1.0);
+    }
+
+    #[test]
+    fn deep_paths_do_not_overflow_the_stack() {
+        // A ray trapped between two perfect mirrors never escapes.
+        let mirror: Rc<Box<dyn Material>> = Rc::new(Box::new(Metal::new(Color::white(), 0.0)));
+        let mut world = HittableList::new();
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            &mirror,
+        )));
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            &mirror,
+        )));
+
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 0.0), &Vec3::new(0.0, 0.0, 1.0));
+        let max_depth = 1_000_000;
+        let color = ray_color(&r, &world, &Background::Sky, max_depth, max_depth);
+        assert!(color.is_black());
+    }
+
+    #[test]
+    fn split_light_adds_up() {
+        // Mirrors that bounce the ray three times before it reaches the sky.
+        let mirror: Rc<Box<dyn Material>> =
+            Rc::new(Box::new(Metal::new(Color::new(0.5, 0.5, 0.5), 0.0)));
+        let mut world = HittableList::new();
+        world.add(Box::new(Plane::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &mirror,
+        )));
+        world.add(Box::new(Quad::new(
+            Vec3::new(0.0, 1.0, -1.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            &mirror,
+        )));
+        let background = Background::Solid(Color::white());
+
+        let r = Ray::new(&Vec3::new(-0.25, 0.5, 0.0), &Vec3::new(1.0, -1.0, 0.0));
+        let (direct, indirect) = ray_color_split(&r, &world, &background, 10, 10);
+        let color = ray_color(&r, &world, &background, 10, 10);
+        assert!(direct.is_black());
+        assert!((indirect.g() - 0.125).abs() < EPSILON);
+        assert!((color.g() - indirect.g()).abs() < EPSILON);
+
+        // Straight up to the sky.
+        let r = Ray::new(&Vec3::new(-0.25, 0.5, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        let (direct, indirect) = ray_color_split(&r, &world, &background, 10, 10);
+        assert!((direct.g() - 1.0).abs() < EPSILON);
+        assert!(indirect.is_black());
+    }
+
+    #[test]
+    fn height_for_rounds() {
+        assert_eq!(RenderSettings::height_for(1200, 3.0 / 2.0), 800);
+        assert_eq!(RenderSettings::height_for(400, 16.0 / 9.0), 225);
+        assert_eq!(RenderSettings::height_for(100, 2.39), 42);
+    }
+}
@@ -0,0 +1,17 @@
+mod blue_noise;
+mod halton;
+mod independent;
+mod lowdiscrepancy;
+mod replay;
+#[allow(clippy::module_inception)]
+mod sampler;
+mod sobol;
+mod stratified;
+
+pub use blue_noise::BlueNoiseSampler;
+pub use halton::HaltonSampler;
+pub use independent::IndependentSampler;
+pub use replay::ReplaySampler;
+pub use sampler::{Dimensions, Sampler, SamplerKind};
+pub use sobol::SobolSampler;
+pub use stratified::StratifiedSampler;
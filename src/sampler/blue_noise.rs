@@ -0,0 +1,200 @@
+use super::lowdiscrepancy::{hash, rotate, to_float};
+use super::sampler::Sampler;
+use super::sobol::scrambled_2d;
+use crate::utils::Float;
+use std::sync::OnceLock;
+
+// The blue-noise mask tiles the image in squares of this many pixels.
+const SIZE: usize = 64;
+// Width of the Gaussian that measures how crowded a spot of the mask is.
+const SIGMA: f64 = 1.5;
+
+/// Screen-space blue noise. Every pixel uses the same scrambled Sobol
+/// points, shifted in each dimension by a blue-noise mask, so neighboring
+/// pixels make opposite errors and what noise remains is fine-grained rather
+/// than blotchy (Georgiev and Fajardo, "Blue-noise Dithered Sampling").
+pub struct BlueNoiseSampler {
+    seed: u64,
+    x: usize,
+    y: usize,
+    index: u32,
+}
+
+impl BlueNoiseSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            x: 0,
+            y: 0,
+            index: 0,
+        }
+    }
+
+    // The mask value at this pixel, offset differently for each use so that
+    // dimensions do not share a pattern.
+    fn shift(&self, dimension: usize, component: u64) -> Float {
+        let h = hash(&[self.seed, dimension as u64, component]);
+        let x = (self.x + h as usize % SIZE) % SIZE;
+        let y = (self.y + (h >> 32) as usize % SIZE) % SIZE;
+        mask()[y * SIZE + x]
+    }
+}
+
+impl Sampler for BlueNoiseSampler {
+    fn start_pixel_sample(&mut self, x: usize, y: usize, index: usize) {
+        self.x = x;
+        self.y = y;
+        self.index = index as u32;
+    }
+
+    fn get_1d(&mut self, dimension: usize) -> Float {
+        let (v, _) = scrambled_2d(self.index, dimension, self.seed);
+        rotate(v, self.shift(dimension, 0))
+    }
+
+    fn get_2d(&mut self, dimension: usize) -> (Float, Float) {
+        let (a, b) = scrambled_2d(self.index, dimension, self.seed);
+        (
+            rotate(a, self.shift(dimension, 0)),
+            rotate(b, self.shift(dimension, 1)),
+        )
+    }
+}
+
+// The mask is built once and shared by every sampler.
+fn mask() -> &'static [Float] {
+    static MASK: OnceLock<Vec<Float>> = OnceLock::new();
+    MASK.get_or_init(void_and_cluster)
+}
+
+// A set of mask pixels with the crowding ("energy") that they cause at
+// every pixel, on a torus so that the mask tiles seamlessly.
+#[derive(Clone)]
+struct Pattern {
+    on: Vec<bool>,
+    energy: Vec<f64>,
+}
+
+impl Pattern {
+    fn toggle(&mut self, p: usize, kernel: &[f64]) {
+        self.on[p] = !self.on[p];
+        let sign = if self.on[p] { 1.0 } else { -1.0 };
+        let (px, py) = (p % SIZE, p / SIZE);
+        for (q, energy) in self.energy.iter_mut().enumerate() {
+            let dx = (q % SIZE + SIZE - px) % SIZE;
+            let dy = (q / SIZE + SIZE - py) % SIZE;
+            *energy += sign * kernel[dy * SIZE + dx];
+        }
+    }
+
+    // The most crowded pixel that is on.
+    fn tightest_cluster(&self) -> usize {
+        (0..self.on.len())
+            .filter(|&p| self.on[p])
+            .max_by(|&a, &b| self.energy[a].total_cmp(&self.energy[b]))
+            .unwrap()
+    }
+
+    // The emptiest pixel that is off.
+    fn largest_void(&self) -> usize {
+        (0..self.on.len())
+            .filter(|&p| !self.on[p])
+            .min_by(|&a, &b| self.energy[a].total_cmp(&self.energy[b]))
+            .unwrap()
+    }
+}
+
+// Ulichney's void-and-cluster method: ranks every pixel by the order in
+// which it joins an ever denser, evenly spread pattern. Thresholding the
+// ranks at any level gives a blue-noise pattern.
+fn void_and_cluster() -> Vec<Float> {
+    let n = SIZE * SIZE;
+    let kernel: Vec<f64> = (0..n)
+        .map(|i| {
+            let dx = (i % SIZE).min(SIZE - i % SIZE) as f64;
+            let dy = (i / SIZE).min(SIZE - i / SIZE) as f64;
+            (-(dx * dx + dy * dy) / (2.0 * SIGMA * SIGMA)).exp()
+        })
+        .collect();
+
+    // Start from a sparse random pattern...
+    let mut pattern = Pattern {
+        on: vec![false; n],
+        energy: vec![0.0; n],
+    };
+    let mut ones = 0;
+    for i in 0.. {
+        if ones == n / 10 {
+            break;
+        }
+        let p = hash(&[i]) as usize % n;
+        if !pattern.on[p] {
+            pattern.toggle(p, &kernel);
+            ones += 1;
+        }
+    }
+
+    // ...and even it out by moving points from clusters into voids.
+    loop {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster, &kernel);
+        let void = pattern.largest_void();
+        pattern.toggle(void, &kernel);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut rank = vec![0; n];
+
+    // Points of the initial pattern are ranked by removing clusters...
+    let mut thinning = pattern.clone();
+    for r in (0..ones).rev() {
+        let cluster = thinning.tightest_cluster();
+        thinning.toggle(cluster, &kernel);
+        rank[cluster] = r;
+    }
+
+    // ...and the rest by filling voids.
+    for r in ones..n {
+        let void = pattern.largest_void();
+        pattern.toggle(void, &kernel);
+        rank[void] = r;
+    }
+
+    rank.iter()
+        .map(|&r| to_float((((r as f64 + 0.5) / n as f64) * 4294967296.0) as u32))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_is_a_permutation_of_levels() {
+        let mut ranks: Vec<usize> = mask()
+            .iter()
+            .map(|&v| (v * (SIZE * SIZE) as Float) as usize)
+            .collect();
+        ranks.sort_unstable();
+        assert!(ranks.iter().enumerate().all(|(i, &r)| i == r));
+    }
+
+    #[test]
+    fn neighbors_differ_more_than_white_noise() {
+        // Blue noise lacks low frequencies, so adjacent values are further
+        // apart than the 1/3 expected of independent uniform values.
+        let mask = mask();
+        let mut sum = 0.0;
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let v = mask[y * SIZE + x];
+                sum += (v - mask[y * SIZE + (x + 1) % SIZE]).abs();
+                sum += (v - mask[(y + 1) % SIZE * SIZE + x]).abs();
+            }
+        }
+        let mean = sum / (2 * SIZE * SIZE) as Float;
+        assert!(mean > 0.4, "mean neighbor difference {}", mean);
+    }
+}
@@ -0,0 +1,81 @@
+use super::lowdiscrepancy::{hash, scrambled_radical_inverse};
+use super::sampler::Sampler;
+use crate::utils::random::random_float;
+use crate::utils::Float;
+
+// Dimensions beyond this many fall back to uniform random numbers, since
+// Halton points in very large prime bases are poorly spread.
+const DIMENSIONS: usize = 256;
+
+/// The Halton sequence: dimension `d` of sample `i` is `i` written in the
+/// `d`th prime base with its digits mirrored about the point. Each pixel
+/// scrambles the digits of every dimension with its own permutations.
+pub struct HaltonSampler {
+    primes: Vec<u32>,
+    seed: u64,
+    pixel: u64,
+    index: u64,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> Self {
+        let mut primes: Vec<u32> = Vec::with_capacity(DIMENSIONS);
+        let mut candidate = 2;
+        while primes.len() < DIMENSIONS {
+            if primes
+                .iter()
+                .take_while(|&&p| p * p <= candidate)
+                .all(|&p| candidate % p != 0)
+            {
+                primes.push(candidate);
+            }
+            candidate += 1;
+        }
+
+        Self {
+            primes,
+            seed,
+            pixel: 0,
+            index: 0,
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, x: usize, y: usize, index: usize) {
+        self.pixel = hash(&[self.seed, x as u64, y as u64]);
+        self.index = index as u64;
+    }
+
+    fn get_1d(&mut self, dimension: usize) -> Float {
+        match self.primes.get(dimension) {
+            Some(&base) => {
+                scrambled_radical_inverse(base, self.index, hash(&[self.pixel, dimension as u64]))
+            }
+            None => random_float(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primes() {
+        let sampler = HaltonSampler::new(0);
+        assert_eq!(&sampler.primes[..6], &[2, 3, 5, 7, 11, 13]);
+        assert_eq!(sampler.primes.len(), DIMENSIONS);
+    }
+
+    #[test]
+    fn base_two_is_stratified() {
+        let mut sampler = HaltonSampler::new(1);
+        let mut strata = [0; 8];
+        for index in 0..8 {
+            sampler.start_pixel_sample(4, 4, index);
+            strata[(sampler.get_1d(0) * 8.0) as usize] += 1;
+        }
+        assert!(strata.iter().all(|&c| c == 1));
+    }
+}
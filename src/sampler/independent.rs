@@ -0,0 +1,15 @@
+use super::sampler::Sampler;
+use crate::utils::random::random_float;
+use crate::utils::Float;
+
+/// Plain uniform random numbers: every dimension of every sample is drawn
+/// independently with `random_float`.
+pub struct IndependentSampler;
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, _x: usize, _y: usize, _index: usize) {}
+
+    fn get_1d(&mut self, _dimension: usize) -> Float {
+        random_float()
+    }
+}
@@ -0,0 +1,172 @@
+//! Hashing and sequence building blocks shared by the samplers.
+
+use crate::utils::Float;
+
+/// The largest `Float` below 1.
+pub(super) const ONE_MINUS_EPSILON: Float = 1.0 - Float::EPSILON / 2.0;
+
+// The SplitMix64 finalizer.
+fn mix(mut h: u64) -> u64 {
+    h ^= h >> 30;
+    h = h.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h ^= h >> 27;
+    h = h.wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
+
+/// Hashes a few integers into well-mixed bits, e.g. a seed, a pixel and a
+/// dimension into the seed for that dimension of that pixel.
+pub(super) fn hash(values: &[u64]) -> u64 {
+    values.iter().fold(0x9e37_79b9_7f4a_7c15, |h, &v| {
+        mix(h ^ v).wrapping_add(0x9e37_79b9_7f4a_7c15)
+    })
+}
+
+/// Turns the bits of `v`, read as a binary fraction, into a `Float` in `[0, 1)`.
+pub(super) fn to_float(v: u32) -> Float {
+    (v >> 8) as Float / 16777216.0
+}
+
+/// Adds `shift` to `v` modulo 1 (a Cranley-Patterson rotation).
+pub(super) fn rotate(v: Float, shift: Float) -> Float {
+    let r = v + shift;
+    (if r >= 1.0 { r - 1.0 } else { r }).min(ONE_MINUS_EPSILON)
+}
+
+/// Element `i` of a random permutation of `0..l` chosen by `p`, without
+/// building the permutation (Kensler, "Correlated Multi-Jittered Sampling").
+pub(super) fn permutation_element(mut i: u32, l: u32, p: u32) -> u32 {
+    let mut w = l - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    // Cycle-walk until the permuted value falls inside `0..l`.
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < l {
+            break;
+        }
+    }
+
+    (i.wrapping_add(p)) % l
+}
+
+/// An Owen scramble of the bits of `v`: each bit is flipped depending on the
+/// bits above it, so that nets stay nets (Burley, "Practical Hash-based Owen
+/// Scrambling").
+pub(super) fn owen_scramble(v: u32, seed: u32) -> u32 {
+    let mut x = v.reverse_bits();
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50_b47c);
+    x ^= x.wrapping_mul(0xb82f_1e52);
+    x ^= x.wrapping_mul(0xc7af_e638);
+    x ^= x.wrapping_mul(0x8d22_f6e6);
+    x.reverse_bits()
+}
+
+/// Point `index` of the first (`second == false`) or second dimension of the
+/// Sobol sequence, as a binary fraction. Together they form a (0, 2)-sequence:
+/// every aligned run of 2^m points puts one point in each of any 2^m equal
+/// boxes that tile the unit square.
+pub(super) fn sobol(index: u32, second: bool) -> u32 {
+    if !second {
+        return index.reverse_bits();
+    }
+
+    let mut result = 0;
+    let mut v = 1 << 31;
+    let mut index = index;
+    while index != 0 {
+        if index & 1 != 0 {
+            result ^= v;
+        }
+        index >>= 1;
+        v ^= v >> 1;
+    }
+    result
+}
+
+/// Digit reversal of `index` in `base`, the `base` dimension of the Halton
+/// sequence, with each digit position put through its own random
+/// permutation chosen by `seed`. Unlike a shift, this keeps small sample
+/// counts in large bases spread over the whole interval.
+pub(super) fn scrambled_radical_inverse(base: u32, mut index: u64, seed: u64) -> Float {
+    let inv_base = 1.0 / base as f64;
+    let mut inv = 1.0;
+    let mut result = 0.0;
+    let mut position = 0;
+
+    // Leading zero digits are permuted too, up to the precision kept.
+    while inv > 1e-8 {
+        inv *= inv_base;
+        let digit = (index % base as u64) as u32;
+        let p = hash(&[seed, position]) as u32;
+        result += permutation_element(digit, base, p) as f64 * inv;
+        index /= base as u64;
+        position += 1;
+    }
+    (result as Float).min(ONE_MINUS_EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permutation_is_bijective() {
+        for &l in &[1, 5, 16, 37] {
+            let mut seen = vec![false; l as usize];
+            for i in 0..l {
+                let j = permutation_element(i, l, 0x1234_5678);
+                assert!(!seen[j as usize]);
+                seen[j as usize] = true;
+            }
+        }
+    }
+
+    #[test]
+    fn scrambled_sobol_is_a_net() {
+        // 16 points: one in each cell of the 4x4, 2x8 and 8x2 grids.
+        for &(nx, ny) in &[(4, 4), (2, 8), (8, 2), (16, 1)] {
+            let mut cells = [0; 16];
+            for i in 0..16 {
+                let shuffled = owen_scramble(i, 7);
+                let x = to_float(owen_scramble(sobol(shuffled, false), 11));
+                let y = to_float(owen_scramble(sobol(shuffled, true), 13));
+                cells[(y * ny as Float) as usize * nx + (x * nx as Float) as usize] += 1;
+            }
+            assert!(cells.iter().all(|&c| c == 1), "{}x{}: {:?}", nx, ny, cells);
+        }
+    }
+
+    #[test]
+    fn scrambled_radical_inverse_is_stratified() {
+        // Nine points in base 3 land one in each ninth.
+        let mut strata = [0; 9];
+        for index in 0..9 {
+            let v = scrambled_radical_inverse(3, index, 17);
+            strata[(v * 9.0) as usize] += 1;
+        }
+        assert!(strata.iter().all(|&c| c == 1));
+    }
+}
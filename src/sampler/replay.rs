@@ -0,0 +1,224 @@
+use super::lowdiscrepancy::ONE_MINUS_EPSILON;
+use super::sampler::Sampler;
+use crate::utils::consts::PI;
+use crate::utils::random::random_float;
+use crate::utils::Float;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A sample that can be replayed and mutated, for Markov chains in primary
+/// sample space: every dimension keeps its value until the sampler moves on
+/// with `start_iteration`, which perturbs all of them a little (a small
+/// step) or draws them afresh (a large step). `reject` then takes the move
+/// back. Dimensions are mutated lazily, when they are next asked for, so
+/// paths can use as many as they like.
+///
+/// Clones share their sample, so that one can be installed with
+/// `random::with_sampler` while the other decides on the mutations. The
+/// sampler ignores the pixel it is moved to; the sample includes the
+/// position in the image.
+#[derive(Clone)]
+pub struct ReplaySampler {
+    state: Rc<RefCell<State>>,
+}
+
+struct State {
+    // The standard deviation of small steps.
+    sigma: Float,
+    large_step_probability: Float,
+    samples: Vec<PrimarySample>,
+    iteration: u64,
+    large_step: bool,
+    last_large_step: u64,
+}
+
+struct PrimarySample {
+    value: Float,
+    // The iteration the value was last brought up to date in.
+    modified: u64,
+    // The value and iteration before that, for `reject`.
+    value_backup: Float,
+    modified_backup: u64,
+}
+
+impl ReplaySampler {
+    /// A sample drawn afresh, which small steps perturb with standard
+    /// deviation `sigma` and large steps replace with probability
+    /// `large_step_probability`.
+    pub fn new(sigma: Float, large_step_probability: Float) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(State {
+                sigma,
+                large_step_probability,
+                samples: Vec::new(),
+                iteration: 0,
+                large_step: true,
+                last_large_step: 0,
+            })),
+        }
+    }
+
+    /// Proposes a mutation of the sample, by a small or a large step.
+    pub fn start_iteration(&self) {
+        let mut state = self.state.borrow_mut();
+        state.iteration += 1;
+        state.large_step = random_float() < state.large_step_probability;
+    }
+
+    /// Whether the current mutation draws every dimension afresh.
+    pub fn is_large_step(&self) -> bool {
+        self.state.borrow().large_step
+    }
+
+    /// Keeps the mutation.
+    pub fn accept(&self) {
+        let mut state = self.state.borrow_mut();
+        if state.large_step {
+            state.last_large_step = state.iteration;
+        }
+    }
+
+    /// Goes back to the sample before the mutation.
+    pub fn reject(&self) {
+        let mut state = self.state.borrow_mut();
+        let iteration = state.iteration;
+        for sample in state.samples.iter_mut() {
+            if sample.modified == iteration {
+                sample.value = sample.value_backup;
+                sample.modified = sample.modified_backup;
+            }
+        }
+        state.iteration -= 1;
+    }
+}
+
+impl State {
+    // Brings dimension `dimension` up to the current iteration, applying the
+    // steps it missed while no path asked for it.
+    fn update(&mut self, dimension: usize) -> Float {
+        let (iteration, last_large_step) = (self.iteration, self.last_large_step);
+        // Dimensions no path has used yet are drawn afresh, and stay if the
+        // mutation is rejected.
+        while self.samples.len() <= dimension {
+            let value = random_float();
+            self.samples.push(PrimarySample {
+                value,
+                modified: iteration,
+                value_backup: value,
+                modified_backup: iteration.saturating_sub(1),
+            });
+        }
+        let sample = &mut self.samples[dimension];
+
+        // Left behind by a large step since it was last used.
+        if sample.modified < last_large_step {
+            sample.value = random_float();
+            sample.modified = last_large_step;
+        }
+        if sample.modified == iteration {
+            return sample.value;
+        }
+
+        sample.value_backup = sample.value;
+        sample.modified_backup = sample.modified;
+        if self.large_step {
+            sample.value = random_float();
+        } else {
+            // The small steps it missed add up to one with their combined
+            // spread, wrapped around the unit interval.
+            let steps = (iteration - sample.modified) as Float;
+            let sigma = self.sigma * steps.sqrt();
+            sample.value += sigma * standard_normal();
+            sample.value = (sample.value - sample.value.floor()).min(ONE_MINUS_EPSILON);
+        }
+        sample.modified = iteration;
+        sample.value
+    }
+}
+
+// A normally distributed number with mean 0 and standard deviation 1, by the
+// Box-Muller transform.
+fn standard_normal() -> Float {
+    let u1 = 1.0 - random_float();
+    let u2 = random_float();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+impl Sampler for ReplaySampler {
+    fn start_pixel_sample(&mut self, _x: usize, _y: usize, _index: usize) {}
+
+    fn get_1d(&mut self, dimension: usize) -> Float {
+        self.state.borrow_mut().update(dimension)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::random;
+
+    fn values(sampler: &ReplaySampler, count: usize) -> Vec<Float> {
+        let mut sampler = sampler.clone();
+        (0..count).map(|d| sampler.get_1d(d)).collect()
+    }
+
+    #[test]
+    fn replays_until_mutated() {
+        random::seed(1);
+        let sampler = ReplaySampler::new(0.01, 0.3);
+        let first = values(&sampler, 20);
+        assert_eq!(values(&sampler, 20), first);
+        assert!(first.iter().all(|v| (0.0..1.0).contains(v)));
+
+        sampler.start_iteration();
+        assert_ne!(values(&sampler, 20), first);
+    }
+
+    #[test]
+    fn reject_restores_the_sample() {
+        random::seed(2);
+        let sampler = ReplaySampler::new(0.01, 0.3);
+        let first = values(&sampler, 20);
+        for _ in 0..10 {
+            sampler.start_iteration();
+            // Only some dimensions are mutated before the rejection.
+            values(&sampler, 5);
+            sampler.reject();
+            assert_eq!(values(&sampler, 20), first);
+        }
+    }
+
+    #[test]
+    fn small_steps_stay_close() {
+        random::seed(3);
+        let sampler = ReplaySampler::new(0.01, 0.0);
+        let mut previous = values(&sampler, 20);
+        for _ in 0..10 {
+            sampler.start_iteration();
+            let next = values(&sampler, 20);
+            for (a, b) in previous.iter().zip(next.iter()) {
+                // Allowing for wrapping around.
+                let distance = (a - b).abs().min(1.0 - (a - b).abs());
+                assert!(distance > 0.0 && distance < 0.1, "{} {}", a, b);
+            }
+            sampler.accept();
+            previous = next;
+        }
+    }
+
+    #[test]
+    fn large_steps_draw_afresh_even_when_caught_up_later() {
+        random::seed(4);
+        let sampler = ReplaySampler::new(0.0, 1.0);
+        let first = values(&sampler, 20);
+        sampler.start_iteration();
+        assert!(sampler.is_large_step());
+        // Only the first half is used before the step is accepted.
+        values(&sampler, 10);
+        sampler.accept();
+
+        let second = values(&sampler, 20);
+        assert!(first.iter().zip(second.iter()).all(|(a, b)| a != b));
+        assert_eq!(values(&sampler, 20), second);
+    }
+}
@@ -0,0 +1,171 @@
+use super::{BlueNoiseSampler, HaltonSampler, IndependentSampler, SobolSampler, StratifiedSampler};
+use crate::utils::random::random_float;
+use crate::utils::Float;
+
+/// A source of sample points for the pixels of an image. Each sample of a
+/// pixel is a point in many dimensions, handed out in `Dimensions` blocks
+/// for the stages of a path; a good sampler spreads the samples of a pixel
+/// evenly over each dimension and each pair of dimensions.
+pub trait Sampler {
+    /// Moves to sample `index` of pixel (`x`, `y`).
+    fn start_pixel_sample(&mut self, x: usize, y: usize, index: usize);
+
+    /// Dimension `dimension` of the current sample, in `[0, 1)`.
+    fn get_1d(&mut self, dimension: usize) -> Float;
+
+    /// Dimensions `dimension` and `dimension + 1` of the current sample.
+    fn get_2d(&mut self, dimension: usize) -> (Float, Float) {
+        (self.get_1d(dimension), self.get_1d(dimension + 1))
+    }
+}
+
+/// The blocks of dimensions that make up one sample, in the order a path
+/// uses them.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Dimensions {
+    /// The position within the pixel.
+    Pixel,
+    /// The point on the lens, plus one more for apertures that pick a part
+    /// of the opening first.
+    Lens,
+    /// The moment within the shutter interval.
+    Time,
+    /// Everything drawn at the `n`th surface a path meets, counting from 0:
+    /// a stochastic cut-out test, the material's scattering and whether the
+    /// path survives Russian roulette.
+    Bounce(usize),
+    /// The same for paths traced from the lights, where stage 0 picks the
+    /// light and the point and direction it emits from, and stage `n + 1`
+    /// is the `n`th surface.
+    LightBounce(usize),
+    /// The point on the lens that vertex `n` of a light path, counting from
+    /// 0, is joined to.
+    CameraConnection(usize),
+    /// The light, and the point on it, that vertex `n` of a camera path is
+    /// joined to.
+    LightConnection(usize),
+}
+
+const BOUNCE_DIMENSIONS: usize = 5;
+const CAMERA_CONNECTION_DIMENSIONS: usize = 2;
+const LIGHT_CONNECTION_DIMENSIONS: usize = 3;
+// The dimensions of one stage: a bounce of each path and the connections
+// made at its vertices.
+const STAGE_DIMENSIONS: usize =
+    2 * BOUNCE_DIMENSIONS + CAMERA_CONNECTION_DIMENSIONS + LIGHT_CONNECTION_DIMENSIONS;
+
+impl Dimensions {
+    pub fn first(&self) -> usize {
+        match self {
+            Dimensions::Pixel => 0,
+            Dimensions::Lens => 2,
+            Dimensions::Time => 5,
+            // Camera and light paths take turns, so that both get low
+            // dimensions for their first bounces and connections.
+            Dimensions::Bounce(n) => 6 + n * STAGE_DIMENSIONS,
+            Dimensions::LightBounce(n) => 6 + n * STAGE_DIMENSIONS + BOUNCE_DIMENSIONS,
+            Dimensions::CameraConnection(n) => 6 + n * STAGE_DIMENSIONS + 2 * BOUNCE_DIMENSIONS,
+            Dimensions::LightConnection(n) => {
+                6 + n * STAGE_DIMENSIONS + 2 * BOUNCE_DIMENSIONS + CAMERA_CONNECTION_DIMENSIONS
+            }
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        match self {
+            Dimensions::Pixel => 2,
+            Dimensions::Lens => 3,
+            Dimensions::Time => 1,
+            Dimensions::Bounce(_) | Dimensions::LightBounce(_) => BOUNCE_DIMENSIONS,
+            Dimensions::CameraConnection(_) => CAMERA_CONNECTION_DIMENSIONS,
+            Dimensions::LightConnection(_) => LIGHT_CONNECTION_DIMENSIONS,
+        }
+    }
+}
+
+/// The samplers that `RenderSettings` can select.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum SamplerKind {
+    /// Uniform random numbers, as drawn by `random_float`.
+    #[default]
+    Independent,
+    /// Jittered strata, randomly assigned to each dimension.
+    Stratified,
+    /// The Halton sequence, randomly shifted in each pixel.
+    Halton,
+    /// The Owen-scrambled Sobol sequence.
+    Sobol,
+    /// Sobol points shifted by a blue-noise mask, so that the remaining
+    /// error is spread out as fine-grained noise across the image.
+    BlueNoise,
+}
+
+impl SamplerKind {
+    /// Builds a sampler for `samples_per_pixel` samples in each pixel.
+    /// Samplers other than `Independent` draw their scrambling seed from
+    /// `random_float`, so seeding the generator reproduces them.
+    pub fn build(&self, samples_per_pixel: usize) -> Box<dyn Sampler> {
+        if *self == SamplerKind::Independent {
+            return Box::new(IndependentSampler);
+        }
+
+        let seed = (random_float() * 4294967296.0) as u64;
+        match self {
+            SamplerKind::Independent => unreachable!(),
+            SamplerKind::Stratified => Box::new(StratifiedSampler::new(samples_per_pixel, seed)),
+            SamplerKind::Halton => Box::new(HaltonSampler::new(seed)),
+            SamplerKind::Sobol => Box::new(SobolSampler::new(seed)),
+            SamplerKind::BlueNoise => Box::new(BlueNoiseSampler::new(seed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_do_not_overlap() {
+        let blocks = [
+            Dimensions::Pixel,
+            Dimensions::Lens,
+            Dimensions::Time,
+            Dimensions::Bounce(0),
+            Dimensions::LightBounce(0),
+            Dimensions::CameraConnection(0),
+            Dimensions::LightConnection(0),
+            Dimensions::Bounce(1),
+            Dimensions::LightBounce(1),
+            Dimensions::CameraConnection(1),
+            Dimensions::LightConnection(1),
+            Dimensions::Bounce(2),
+        ];
+        for pair in blocks.windows(2) {
+            assert_eq!(pair[0].first() + pair[0].count(), pair[1].first());
+        }
+    }
+
+    #[test]
+    fn samples_in_unit_interval() {
+        let kinds = [
+            SamplerKind::Independent,
+            SamplerKind::Stratified,
+            SamplerKind::Halton,
+            SamplerKind::Sobol,
+            SamplerKind::BlueNoise,
+        ];
+        for kind in &kinds {
+            let mut sampler = kind.build(16);
+            for index in 0..16 {
+                sampler.start_pixel_sample(3, 5, index);
+                for dimension in 0..40 {
+                    let u = sampler.get_1d(dimension);
+                    let (a, b) = sampler.get_2d(dimension);
+                    for &v in &[u, a, b] {
+                        assert!((0.0..1.0).contains(&v), "{:?} gave {}", kind, v);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use super::lowdiscrepancy::{hash, owen_scramble, sobol, to_float};
+use super::sampler::Sampler;
+use crate::utils::Float;
+
+/// Owen-scrambled Sobol points. Each dimension, or pair of dimensions for
+/// `get_2d`, uses the first two Sobol dimensions with the sample order
+/// shuffled and the bits scrambled by a hash of the pixel and dimension, so
+/// that every power-of-two count of samples is well stratified.
+pub struct SobolSampler {
+    seed: u64,
+    pixel: u64,
+    index: u32,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            pixel: 0,
+            index: 0,
+        }
+    }
+}
+
+/// Dimension `dimension` (and the next one) of sample `index`, scrambled by
+/// `seed`. Shared with `BlueNoiseSampler`, which uses the same points in
+/// every pixel.
+pub(super) fn scrambled_2d(index: u32, dimension: usize, seed: u64) -> (Float, Float) {
+    let h = hash(&[seed, dimension as u64]);
+    let shuffled = owen_scramble(index, h as u32);
+    (
+        to_float(owen_scramble(sobol(shuffled, false), (h >> 32) as u32)),
+        to_float(owen_scramble(sobol(shuffled, true), hash(&[h]) as u32)),
+    )
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, x: usize, y: usize, index: usize) {
+        self.pixel = hash(&[self.seed, x as u64, y as u64]);
+        self.index = index as u32;
+    }
+
+    fn get_1d(&mut self, dimension: usize) -> Float {
+        scrambled_2d(self.index, dimension, self.pixel).0
+    }
+
+    fn get_2d(&mut self, dimension: usize) -> (Float, Float) {
+        scrambled_2d(self.index, dimension, self.pixel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_are_stratified() {
+        let mut sampler = SobolSampler::new(9);
+        let mut cells = [0; 16];
+        for index in 0..16 {
+            sampler.start_pixel_sample(7, 1, index);
+            let (x, y) = sampler.get_2d(6);
+            cells[(y * 4.0) as usize * 4 + (x * 4.0) as usize] += 1;
+        }
+        assert!(cells.iter().all(|&c| c == 1));
+    }
+}
@@ -0,0 +1,73 @@
+use super::lowdiscrepancy::{hash, permutation_element, ONE_MINUS_EPSILON};
+use super::sampler::Sampler;
+use crate::utils::random::random_float;
+use crate::utils::Float;
+
+/// Jittered stratified sampling. Each dimension is cut into one stratum per
+/// sample, and each pair of dimensions into a grid of about as many cells;
+/// the samples of a pixel visit the strata in a random order and take a
+/// random point within each.
+pub struct StratifiedSampler {
+    samples: u32,
+    seed: u64,
+    pixel: u64,
+    index: u32,
+}
+
+impl StratifiedSampler {
+    pub fn new(samples_per_pixel: usize, seed: u64) -> Self {
+        Self {
+            samples: samples_per_pixel.max(1) as u32,
+            seed,
+            pixel: 0,
+            index: 0,
+        }
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, x: usize, y: usize, index: usize) {
+        self.pixel = hash(&[self.seed, x as u64, y as u64]);
+        self.index = index as u32 % self.samples;
+    }
+
+    fn get_1d(&mut self, dimension: usize) -> Float {
+        let p = hash(&[self.pixel, dimension as u64]) as u32;
+        let stratum = permutation_element(self.index, self.samples, p);
+        ((stratum as Float + random_float()) / self.samples as Float).min(ONE_MINUS_EPSILON)
+    }
+
+    fn get_2d(&mut self, dimension: usize) -> (Float, Float) {
+        // The smallest grid with a cell for every sample; when the count is
+        // not a square, a few cells go unvisited in each pixel.
+        let nx = (self.samples as Float).sqrt().ceil() as u32;
+        let ny = self.samples.div_ceil(nx);
+
+        let p = hash(&[self.pixel, dimension as u64]) as u32;
+        let cell = permutation_element(self.index, nx * ny, p);
+        (
+            (((cell % nx) as Float + random_float()) / nx as Float).min(ONE_MINUS_EPSILON),
+            (((cell / nx) as Float + random_float()) / ny as Float).min(ONE_MINUS_EPSILON),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_sample_per_stratum() {
+        let mut sampler = StratifiedSampler::new(9, 5);
+        let mut strata = [0; 9];
+        let mut cells = [0; 9];
+        for index in 0..9 {
+            sampler.start_pixel_sample(1, 2, index);
+            strata[(sampler.get_1d(4) * 9.0) as usize] += 1;
+            let (x, y) = sampler.get_2d(0);
+            cells[(y * 3.0) as usize * 3 + (x * 3.0) as usize] += 1;
+        }
+        assert!(strata.iter().all(|&c| c == 1));
+        assert!(cells.iter().all(|&c| c == 1));
+    }
+}
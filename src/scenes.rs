@@ -0,0 +1,229 @@
+use crate::geometry::{HittableList, Plane, Quad, Sphere};
+use crate::light::{AreaLight, EnvironmentLight, Light};
+use crate::material::{Dielectric, DiffuseLight, Lambertian, Material, Metal};
+use crate::render::Background;
+use crate::utils::color::Color;
+use crate::utils::random::random_f64;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// The final scene of the book: a large field of small random spheres
+/// around three big ones.
+pub fn random_scene() -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground_material: Rc<Box<dyn Material>> =
+        Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    world.add(Box::new(Plane::new(
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        &ground_material,
+    )));
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_mat = random_f64();
+            let center = Vec3::new(
+                (a as Float) + 0.9 * layout_range(0.0, 1.0),
+                0.2,
+                (b as Float) + 0.9 * layout_range(0.0, 1.0),
+            );
+
+            let sphere_material: Rc<Box<dyn Material>> = if choose_mat < 0.8 {
+                // diffuse
+                let albedo = layout_color(0.0, 1.0) * layout_color(0.0, 1.0);
+                Rc::new(Box::new(Lambertian::new(albedo)))
+            } else if choose_mat < 0.95 {
+                // metal
+                let albedo = layout_color(0.5, 1.0);
+                let fuzz = 0.5 * layout_range(0.0, 1.0);
+                Rc::new(Box::new(Metal::new(albedo, fuzz)))
+            } else {
+                // grass
+                Rc::new(Box::new(Dielectric::new(1.5)))
+            };
+
+            world.add(Box::new(Sphere::new(center, 0.2, &sphere_material)));
+        }
+    }
+
+    let material1: Rc<Box<dyn Material>> = Rc::new(Box::new(Dielectric::new(1.5)));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, 1.0, 0.0),
+        1.0,
+        &material1,
+    )));
+
+    let material2: Rc<Box<dyn Material>> =
+        Rc::new(Box::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(-4.0, 1.0, 0.0),
+        1.0,
+        &material2,
+    )));
+
+    let material3: Rc<Box<dyn Material>> =
+        Rc::new(Box::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(4.0, 1.0, 0.0),
+        1.0,
+        &material3,
+    )));
+
+    world
+}
+
+// Numbers for the layout of `random_scene`, drawn at double precision so
+// that the scene is the same with the `f32` feature.
+fn layout_range(min: f64, max: f64) -> Float {
+    (min + (max - min) * random_f64()) as Float
+}
+
+fn layout_color(min: f64, max: f64) -> Color {
+    Color::new(
+        layout_range(min, max),
+        layout_range(min, max),
+        layout_range(min, max),
+    )
+}
+
+/// The sky lighting `random_scene`, for integrators that trace light from
+/// the lights. It covers the spheres but not the far ground.
+pub fn random_scene_lights() -> Vec<Box<dyn Light>> {
+    vec![Box::new(EnvironmentLight::new(
+        Background::Sky,
+        Vec3::new(0.0, 1.0, 0.0),
+        17.0,
+    ))]
+}
+
+/// Ground, a diffuse sphere flanked by a hollow glass sphere and a metal one.
+pub fn three_spheres() -> HittableList {
+    let mut world = HittableList::new();
+
+    let material_ground: Rc<Box<dyn Material>> =
+        Rc::new(Box::new(Lambertian::new(Color::new(0.8, 0.8, 0.0))));
+    let material_center: Rc<Box<dyn Material>> =
+        Rc::new(Box::new(Lambertian::new(Color::new(0.1, 0.2, 0.5))));
+    let material_left: Rc<Box<dyn Material>> = Rc::new(Box::new(Dielectric::new(1.5)));
+    let material_right: Rc<Box<dyn Material>> =
+        Rc::new(Box::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.0)));
+
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, -100.5, -1.0),
+        100.0,
+        &material_ground,
+    )));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, 0.0, -1.0),
+        0.5,
+        &material_center,
+    )));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(-1.0, 0.0, -1.0),
+        0.5,
+        &material_left,
+    )));
+    // A negative radius flips the normals, making the glass sphere hollow.
+    world.add(Box::new(Sphere::new(
+        Vec3::new(-1.0, 0.0, -1.0),
+        -0.4,
+        &material_left,
+    )));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(1.0, 0.0, -1.0),
+        0.5,
+        &material_right,
+    )));
+
+    world
+}
+
+/// A 555-unit Cornell box lit by a ceiling light, holding a glass sphere and
+/// a metal sphere. Meant to be rendered with a black background.
+pub fn cornell_box() -> HittableList {
+    let mut world = HittableList::new();
+
+    let red: Rc<Box<dyn Material>> =
+        Rc::new(Box::new(Lambertian::new(Color::new(0.65, 0.05, 0.05))));
+    let white: Rc<Box<dyn Material>> =
+        Rc::new(Box::new(Lambertian::new(Color::new(0.73, 0.73, 0.73))));
+    let green: Rc<Box<dyn Material>> =
+        Rc::new(Box::new(Lambertian::new(Color::new(0.12, 0.45, 0.15))));
+    let light = cornell_light_material();
+    let glass: Rc<Box<dyn Material>> = Rc::new(Box::new(Dielectric::new(1.5)));
+    let aluminium: Rc<Box<dyn Material>> =
+        Rc::new(Box::new(Metal::new(Color::new(0.8, 0.85, 0.88), 0.0)));
+
+    world.add(Box::new(Quad::new(
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        &green,
+    )));
+    world.add(Box::new(Quad::new(
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        &red,
+    )));
+    let (q, u, v) = cornell_light();
+    world.add(Box::new(Quad::new(q, u, v, &light)));
+    world.add(Box::new(Quad::new(
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        &white,
+    )));
+    world.add(Box::new(Quad::new(
+        Vec3::new(555.0, 555.0, 555.0),
+        Vec3::new(-555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -555.0),
+        &white,
+    )));
+    world.add(Box::new(Quad::new(
+        Vec3::new(0.0, 0.0, 555.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        &white,
+    )));
+
+    world.add(Box::new(Sphere::new(
+        Vec3::new(190.0, 90.0, 190.0),
+        90.0,
+        &glass,
+    )));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(370.0, 120.0, 370.0),
+        120.0,
+        &aluminium,
+    )));
+
+    world
+}
+
+// The corner and sides of the ceiling light of `cornell_box`.
+fn cornell_light() -> (Vec3, Vec3, Vec3) {
+    (
+        Vec3::new(343.0, 554.0, 332.0),
+        Vec3::new(-130.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -105.0),
+    )
+}
+
+fn cornell_light_material() -> Rc<Box<dyn Material>> {
+    Rc::new(Box::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0))))
+}
+
+/// The ceiling light of `cornell_box`, for integrators that trace light
+/// from the lights.
+pub fn cornell_box_lights() -> Vec<Box<dyn Light>> {
+    let (q, u, v) = cornell_light();
+    vec![Box::new(AreaLight::quad(
+        q,
+        u,
+        v,
+        &cornell_light_material(),
+    ))]
+}
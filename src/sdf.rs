@@ -0,0 +1,12 @@
+mod combinators;
+mod primitives;
+#[allow(clippy::module_inception)]
+mod sdf;
+mod sdf_object;
+
+pub use combinators::{
+    smooth_min, Intersection, Repeat, SmoothUnion, Subtraction, Translate, Twist, Union,
+};
+pub use primitives::{Mandelbulb, RoundBox, SdfSphere, SdfTorus};
+pub use sdf::Sdf;
+pub use sdf_object::SdfObject;
@@ -0,0 +1,189 @@
+use super::sdf::Sdf;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+pub struct Union<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A: Sdf, B: Sdf> Union<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Union<A, B> {
+    fn distance(&self, p: &Vec3) -> Float {
+        self.a.distance(p).min(self.b.distance(p))
+    }
+}
+
+pub struct Intersection<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A: Sdf, B: Sdf> Intersection<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Intersection<A, B> {
+    fn distance(&self, p: &Vec3) -> Float {
+        self.a.distance(p).max(self.b.distance(p))
+    }
+}
+
+/// `a` with `b` carved out of it.
+pub struct Subtraction<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A: Sdf, B: Sdf> Subtraction<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Subtraction<A, B> {
+    fn distance(&self, p: &Vec3) -> Float {
+        self.a.distance(p).max(-self.b.distance(p))
+    }
+}
+
+/// A union that blends the two shapes over a distance of about `k`, using
+/// the polynomial smooth minimum.
+pub struct SmoothUnion<A, B> {
+    a: A,
+    b: B,
+    k: Float,
+}
+
+impl<A: Sdf, B: Sdf> SmoothUnion<A, B> {
+    pub fn new(a: A, b: B, k: Float) -> Self {
+        Self { a, b, k }
+    }
+}
+
+pub fn smooth_min(a: Float, b: Float, k: Float) -> Float {
+    if k <= 0.0 {
+        return a.min(b);
+    }
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    b + (a - b) * h - k * h * (1.0 - h)
+}
+
+impl<A: Sdf, B: Sdf> Sdf for SmoothUnion<A, B> {
+    fn distance(&self, p: &Vec3) -> Float {
+        smooth_min(self.a.distance(p), self.b.distance(p), self.k)
+    }
+}
+
+pub struct Translate<S> {
+    sdf: S,
+    offset: Vec3,
+}
+
+impl<S: Sdf> Translate<S> {
+    pub fn new(sdf: S, offset: Vec3) -> Self {
+        Self { sdf, offset }
+    }
+}
+
+impl<S: Sdf> Sdf for Translate<S> {
+    fn distance(&self, p: &Vec3) -> Float {
+        self.sdf.distance(&(*p - self.offset))
+    }
+}
+
+/// Twists space around the Y axis by `rate` radians per unit of height.
+///
+/// Twisting stretches distances, so the result is scaled down to remain a
+/// lower bound.
+pub struct Twist<S> {
+    sdf: S,
+    rate: Float,
+}
+
+impl<S: Sdf> Twist<S> {
+    pub fn new(sdf: S, rate: Float) -> Self {
+        Self { sdf, rate }
+    }
+}
+
+impl<S: Sdf> Sdf for Twist<S> {
+    fn distance(&self, p: &Vec3) -> Float {
+        let angle = self.rate * p.y();
+        let (sin, cos) = angle.sin_cos();
+        let q = Vec3::new(cos * p.x() - sin * p.z(), p.y(), sin * p.x() + cos * p.z());
+
+        let radius = p.x().hypot(p.z());
+        self.sdf.distance(&q) / (1.0 + (self.rate * radius).powi(2)).sqrt()
+    }
+}
+
+/// Repeats the shape infinitely on a grid with the given cell size. Each
+/// axis with a period of zero is left unrepeated.
+pub struct Repeat<S> {
+    sdf: S,
+    period: Vec3,
+}
+
+impl<S: Sdf> Repeat<S> {
+    pub fn new(sdf: S, period: Vec3) -> Self {
+        Self { sdf, period }
+    }
+}
+
+fn wrap(x: Float, period: Float) -> Float {
+    if period <= 0.0 {
+        x
+    } else {
+        x - period * (x / period).round()
+    }
+}
+
+impl<S: Sdf> Sdf for Repeat<S> {
+    fn distance(&self, p: &Vec3) -> Float {
+        let q = Vec3::new(
+            wrap(p.x(), self.period.x()),
+            wrap(p.y(), self.period.y()),
+            wrap(p.z(), self.period.z()),
+        );
+        self.sdf.distance(&q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdf::SdfSphere;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn smooth_min_blends_near_equal_values() {
+        assert!((smooth_min(1.0, 3.0, 0.5) - 1.0).abs() < EPSILON);
+        assert!(smooth_min(1.0, 1.0, 0.5) < 1.0);
+        assert!((smooth_min(1.0, 1.2, 0.0) - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn subtraction_carves_hole() {
+        let s = Subtraction::new(SdfSphere::new(1.0), SdfSphere::new(0.5));
+
+        assert!(s.distance(&Vec3::new(0.0, 0.0, 0.0)) > 0.0);
+        assert!(s.distance(&Vec3::new(0.75, 0.0, 0.0)) < 0.0);
+    }
+
+    #[test]
+    fn repeat_wraps_into_cell() {
+        let s = Repeat::new(SdfSphere::new(0.5), Vec3::new(2.0, 0.0, 0.0));
+
+        assert!((s.distance(&Vec3::new(4.0, 0.0, 0.0)) + 0.5).abs() < EPSILON);
+        assert!((s.distance(&Vec3::new(4.0, 3.0, 0.0)) - 2.5).abs() < EPSILON);
+    }
+}
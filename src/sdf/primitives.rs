@@ -0,0 +1,125 @@
+use super::sdf::Sdf;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+pub struct SdfSphere {
+    radius: Float,
+}
+
+impl SdfSphere {
+    pub fn new(radius: Float) -> Self {
+        Self { radius }
+    }
+}
+
+impl Sdf for SdfSphere {
+    fn distance(&self, p: &Vec3) -> Float {
+        p.length() - self.radius
+    }
+}
+
+/// A box with half extents `half_size` whose edges are rounded off by
+/// `radius`.
+pub struct RoundBox {
+    half_size: Vec3,
+    radius: Float,
+}
+
+impl RoundBox {
+    pub fn new(half_size: Vec3, radius: Float) -> Self {
+        Self { half_size, radius }
+    }
+}
+
+impl Sdf for RoundBox {
+    fn distance(&self, p: &Vec3) -> Float {
+        let q = Vec3::new(
+            p.x().abs() - self.half_size.x() + self.radius,
+            p.y().abs() - self.half_size.y() + self.radius,
+            p.z().abs() - self.half_size.z() + self.radius,
+        );
+        let outside = Vec3::new(q.x().max(0.0), q.y().max(0.0), q.z().max(0.0));
+        let inside = q.x().max(q.y()).max(q.z()).min(0.0);
+
+        outside.length() + inside - self.radius
+    }
+}
+
+/// A torus around the Y axis.
+pub struct SdfTorus {
+    major_radius: Float,
+    minor_radius: Float,
+}
+
+impl SdfTorus {
+    pub fn new(major_radius: Float, minor_radius: Float) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Sdf for SdfTorus {
+    fn distance(&self, p: &Vec3) -> Float {
+        let ring = p.x().hypot(p.z()) - self.major_radius;
+        ring.hypot(p.y()) - self.minor_radius
+    }
+}
+
+/// The Mandelbulb fractal of the given power (8 is the classic shape),
+/// contained in a sphere of radius 1.25. Uses the usual distance
+/// estimator, which is a bound rather than an exact distance.
+pub struct Mandelbulb {
+    power: Float,
+    iterations: usize,
+}
+
+impl Mandelbulb {
+    pub fn new(power: Float, iterations: usize) -> Self {
+        Self { power, iterations }
+    }
+}
+
+impl Sdf for Mandelbulb {
+    fn distance(&self, p: &Vec3) -> Float {
+        const BAILOUT: Float = 2.0;
+        const BOUNDING_RADIUS: Float = 1.25;
+
+        // The estimator overshoots far from the set; use the bounding sphere
+        // there instead.
+        let to_bound = p.length() - BOUNDING_RADIUS;
+        if to_bound > 0.1 {
+            return to_bound;
+        }
+
+        let mut z = *p;
+        let mut dr = 1.0;
+        let mut r = 0.0;
+
+        for _ in 0..self.iterations {
+            r = z.length();
+            if r > BAILOUT {
+                break;
+            }
+
+            // Raise z to `power` in spherical coordinates and add p.
+            let theta = (z.z() / r).acos() * self.power;
+            let phi = z.y().atan2(z.x()) * self.power;
+            dr = r.powf(self.power - 1.0) * self.power * dr + 1.0;
+
+            let zr = r.powf(self.power);
+            z =
+                zr * Vec3::new(
+                    theta.sin() * phi.cos(),
+                    phi.sin() * theta.sin(),
+                    theta.cos(),
+                ) + *p;
+        }
+
+        if r == 0.0 {
+            return 0.0;
+        }
+        0.5 * r.ln() * r / dr
+    }
+}
@@ -0,0 +1,16 @@
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// A signed distance field: negative inside the shape, positive outside.
+///
+/// The value must never overestimate the distance to the surface, or sphere
+/// tracing may step through it.
+pub trait Sdf {
+    fn distance(&self, p: &Vec3) -> Float;
+}
+
+impl<S: Sdf + ?Sized> Sdf for Box<S> {
+    fn distance(&self, p: &Vec3) -> Float {
+        self.as_ref().distance(p)
+    }
+}
@@ -0,0 +1,145 @@
+use super::sdf::Sdf;
+use crate::geometry::{HitRecord, Hittable, Ray};
+use crate::material::Material;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+use std::rc::Rc;
+
+/// Renders a signed distance field by sphere tracing.
+///
+/// A hit is reported once the field drops below `epsilon`; the march gives
+/// up after `max_steps` steps or beyond `max_distance` along the ray.
+/// Normals are the field's gradient, estimated by finite differences.
+pub struct SdfObject<S> {
+    sdf: S,
+    mat: Rc<Box<dyn Material>>,
+    epsilon: Float,
+    max_steps: usize,
+    max_distance: Float,
+}
+
+impl<S: Sdf> SdfObject<S> {
+    pub fn new(sdf: S, mat: &Rc<Box<dyn Material>>) -> Self {
+        Self {
+            sdf,
+            mat: Rc::clone(mat),
+            epsilon: 1e-4,
+            max_steps: 256,
+            max_distance: 1e3,
+        }
+    }
+
+    pub fn with_epsilon(mut self, epsilon: Float) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn with_max_distance(mut self, max_distance: Float) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    fn normal(&self, p: &Vec3) -> Vec3 {
+        // Tetrahedral central differences: four samples instead of six.
+        let h = self.epsilon;
+        let k = [
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ];
+
+        let gradient = k.iter().fold(Vec3::new(0.0, 0.0, 0.0), |sum, k| {
+            sum + self.sdf.distance(&(*p + h * *k)) * *k
+        });
+        gradient.unit()
+    }
+}
+
+impl<S: Sdf> Hittable for SdfObject<S> {
+    fn hit(&self, r: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let length = r.direction().length();
+        let t_max = t_max.min(self.max_distance / length);
+
+        // Rays that start inside the shape march on the negated field. On
+        // the surface itself, the direction tells which side it heads into.
+        let start = r.at(t_min);
+        let d0 = self.sdf.distance(&start);
+        let side = if d0.abs() < self.epsilon {
+            self.normal(&start).dot(r.direction()).signum()
+        } else {
+            d0.signum()
+        };
+
+        let mut t = t_min;
+        for _ in 0..self.max_steps {
+            let p = r.at(t);
+            let d = side * self.sdf.distance(&p);
+
+            // Only accept a hit after moving off the starting surface.
+            if d.abs() < self.epsilon && t > t_min {
+                let outward_normal = self.normal(&p);
+                return Some(HitRecord::new(
+                    p,
+                    t,
+                    (0.0, 0.0),
+                    r,
+                    &outward_normal,
+                    &self.mat,
+                ));
+            }
+
+            t += d.max(self.epsilon) / length;
+            if t > t_max {
+                return None;
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::sdf::SdfSphere;
+    use crate::utils::color::Color;
+
+    const EPSILON: Float = 0.001;
+
+    fn sphere() -> SdfObject<SdfSphere> {
+        let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::white())));
+        SdfObject::new(SdfSphere::new(1.0), &mat)
+    }
+
+    #[test]
+    fn hit_from_outside() {
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 5.0), &Vec3::new(0.0, 0.0, -2.0));
+        let rec = sphere().hit(&r, 0.001, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 2.0).abs() < EPSILON);
+        assert!((rec.normal.z() - 1.0).abs() < EPSILON);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn hit_from_inside() {
+        let r = Ray::new(&Vec3::new(0.0, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        let rec = sphere().hit(&r, 0.001, Float::INFINITY).unwrap();
+
+        assert!((rec.t - 1.0).abs() < EPSILON);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn miss() {
+        let r = Ray::new(&Vec3::new(0.0, 2.0, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+        assert!(sphere().hit(&r, 0.001, Float::INFINITY).is_none());
+    }
+}
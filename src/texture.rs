@@ -0,0 +1,10 @@
+mod image_texture;
+mod noise_texture;
+mod solid_color;
+#[allow(clippy::module_inception)]
+mod texture;
+
+pub use image_texture::ImageTexture;
+pub use noise_texture::NoiseTexture;
+pub use solid_color::SolidColor;
+pub use texture::Texture;
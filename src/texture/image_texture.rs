@@ -0,0 +1,134 @@
+use super::texture::Texture;
+use crate::utils::color::Color;
+use crate::utils::image::Image;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// An image wrapped over `(u, v)`, with `v = 0` at the bottom row. Lookups
+/// are bilinearly filtered and repeat outside [0, 1].
+pub struct ImageTexture {
+    image: Image,
+    // Opacity as gray, read linearly, if the image came with it.
+    alpha: Option<Image>,
+}
+
+impl ImageTexture {
+    /// For color images, whose pixels are already linear after reading.
+    pub fn new(image: Image) -> Self {
+        Self { image, alpha: None }
+    }
+
+    /// For color images with an alpha channel, as read by
+    /// `Image::read_png_with_alpha`. The alpha is looked up like the color.
+    pub fn with_alpha(image: Image, alpha: Image) -> Self {
+        Self {
+            image,
+            alpha: Some(alpha),
+        }
+    }
+
+    /// For normal and height maps. Their pixels store plain values rather
+    /// than colors, so the gamma decoding applied when reading is undone.
+    pub fn data(image: Image) -> Self {
+        let mut data = Image::new(image.width(), image.height());
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                data.set(x, y, image.get(x, y).map(Float::sqrt));
+            }
+        }
+
+        Self {
+            image: data,
+            alpha: None,
+        }
+    }
+}
+
+fn texel(image: &Image, x: i64, y: i64) -> Color {
+    let (width, height) = (image.width() as i64, image.height() as i64);
+    image.get(x.rem_euclid(width) as usize, y.rem_euclid(height) as usize)
+}
+
+fn filter(image: &Image, u: Float, v: Float) -> Color {
+    // Continuous pixel coordinates, with pixel centers at integers.
+    let x = u * image.width() as Float - 0.5;
+    let y = (1.0 - v) * image.height() as Float - 0.5;
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as i64, y0 as i64);
+
+    let top = (1.0 - fx) * texel(image, x0, y0) + fx * texel(image, x0 + 1, y0);
+    let bottom = (1.0 - fx) * texel(image, x0, y0 + 1) + fx * texel(image, x0 + 1, y0 + 1);
+    (1.0 - fy) * top + fy * bottom
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: Float, v: Float, _p: &Vec3) -> Color {
+        filter(&self.image, u, v)
+    }
+
+    fn alpha(&self, u: Float, v: Float, p: &Vec3) -> Float {
+        match &self.alpha {
+            Some(alpha) => filter(alpha, u, v).r(),
+            None => self.value(u, v, p).luminance(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: Float = 0.00001;
+
+    fn checker() -> Image {
+        let mut image = Image::new(2, 2);
+        image.set(0, 0, Color::white());
+        image.set(1, 1, Color::white());
+        image
+    }
+
+    #[test]
+    fn samples_pixel_centers() {
+        let texture = ImageTexture::new(checker());
+        let p = Vec3::new(0.0, 0.0, 0.0);
+
+        // Top-left pixel at v near 1, bottom-left at v near 0.
+        assert!((texture.value(0.25, 0.75, &p).r() - 1.0).abs() < EPSILON);
+        assert!(texture.value(0.25, 0.25, &p).r().abs() < EPSILON);
+    }
+
+    #[test]
+    fn filters_and_wraps() {
+        let texture = ImageTexture::new(checker());
+        let p = Vec3::new(0.0, 0.0, 0.0);
+
+        // Halfway between two pixel centers, also across the seam.
+        assert!((texture.value(0.5, 0.75, &p).r() - 0.5).abs() < EPSILON);
+        assert!((texture.value(0.0, 0.75, &p).r() - 0.5).abs() < EPSILON);
+        assert!((texture.value(1.25, 1.75, &p).r() - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn data_undoes_gamma() {
+        let mut image = Image::new(1, 1);
+        image.set(0, 0, Color::new(0.25, 0.25, 0.25));
+        let texture = ImageTexture::data(image);
+
+        let c = texture.value(0.5, 0.5, &Vec3::new(0.0, 0.0, 0.0));
+        assert!((c.g() - 0.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn alpha_comes_from_its_own_channel() {
+        let mut alpha = Image::new(2, 1);
+        alpha.set(1, 0, Color::new(0.2, 0.2, 0.2));
+        let texture = ImageTexture::with_alpha(checker(), alpha);
+        let p = Vec3::new(0.0, 0.0, 0.0);
+
+        // Opacity follows the alpha, not the color, and is not squared.
+        assert!(texture.alpha(0.25, 0.5, &p).abs() < EPSILON);
+        assert!((texture.alpha(0.75, 0.5, &p) - 0.2).abs() < EPSILON);
+        assert!((texture.value(0.25, 0.75, &p).r() - 1.0).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,30 @@
+use super::texture::Texture;
+use crate::utils::color::Color;
+use crate::utils::perlin::Perlin;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// Gray Perlin turbulence over world space, with features about `1 / scale`
+/// units across.
+pub struct NoiseTexture {
+    noise: Perlin,
+    scale: Float,
+    depth: usize,
+}
+
+impl NoiseTexture {
+    pub fn new(scale: Float, depth: usize) -> Self {
+        Self {
+            noise: Perlin::new(),
+            scale,
+            depth,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: Float, _v: Float, p: &Vec3) -> Color {
+        let t = self.noise.turbulence(&(self.scale * *p), self.depth);
+        Color::new(t, t, t)
+    }
+}
@@ -0,0 +1,20 @@
+use super::texture::Texture;
+use crate::utils::color::Color;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+pub struct SolidColor {
+    color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: Float, _v: Float, _p: &Vec3) -> Color {
+        self.color
+    }
+}
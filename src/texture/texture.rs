@@ -0,0 +1,16 @@
+use crate::utils::color::Color;
+use crate::utils::vec3::Vec3;
+use crate::utils::Float;
+
+/// A color that varies over a surface, looked up by the `(u, v)` and world
+/// position of a hit.
+pub trait Texture {
+    fn value(&self, u: Float, v: Float, p: &Vec3) -> Color;
+
+    /// The opacity at `(u, v)`, from 0 for transparent to 1 for opaque, used
+    /// as a cutout mask. Textures without an alpha channel take it to be the
+    /// luminance of their value, so a gray texture serves as a mask.
+    fn alpha(&self, u: Float, v: Float, p: &Vec3) -> Float {
+        self.value(u, v, p).luminance()
+    }
+}
@@ -1,2 +1,20 @@
 pub mod color;
+pub mod image;
+pub mod kd_tree;
+pub mod onb;
+pub mod perlin;
+pub mod random;
+pub mod roots;
 pub mod vec3;
+
+/// Scalar type used for all geometry and color math. Enable the `f32`
+/// feature to trade precision for speed and memory.
+#[cfg(not(feature = "f32"))]
+pub type Float = f64;
+#[cfg(feature = "f32")]
+pub type Float = f32;
+
+#[cfg(feature = "f32")]
+pub use std::f32::consts;
+#[cfg(not(feature = "f32"))]
+pub use std::f64::consts;
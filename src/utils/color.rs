@@ -1,7 +1,192 @@
+use super::random::random_range;
 use super::vec3::Vec3;
+use crate::utils::Float;
 use std::io::{self, Write};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub};
 
-pub type Color = Vec3;
+/// Linear RGB radiance or reflectance.
+///
+/// Kept separate from `Vec3` so positions and directions cannot be mixed up
+/// with colors; all arithmetic is channel-wise.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Color {
+    value: [Float; 3],
+}
+
+impl Color {
+    pub fn new(r: Float, g: Float, b: Float) -> Self {
+        Self { value: [r, g, b] }
+    }
+
+    pub fn black() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+
+    pub fn white() -> Self {
+        Self::new(1.0, 1.0, 1.0)
+    }
+
+    pub fn new_random(min: Float, max: Float) -> Self {
+        Self::new(
+            random_range(min, max),
+            random_range(min, max),
+            random_range(min, max),
+        )
+    }
+
+    /// Parses `#RRGGBB` (the `#` is optional). Hex colors are sRGB-encoded,
+    /// so the result is converted to linear RGB.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        // `from_str_radix` would also take a sign, as in "+f".
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        let (r, g, b) = (channel(0)?, channel(2)?, channel(4)?);
+
+        Some(Self::new(r as Float / 255.0, g as Float / 255.0, b as Float / 255.0).srgb_to_linear())
+    }
+
+    /// Maps a unit vector to a displayable color, e.g. for visualizing normals.
+    pub fn from_normal(n: &Vec3) -> Self {
+        Self::new(
+            0.5 * (n.x() + 1.0),
+            0.5 * (n.y() + 1.0),
+            0.5 * (n.z() + 1.0),
+        )
+    }
+
+    pub fn r(&self) -> Float {
+        self.value[0]
+    }
+
+    pub fn g(&self) -> Float {
+        self.value[1]
+    }
+
+    pub fn b(&self) -> Float {
+        self.value[2]
+    }
+
+    /// Relative luminance using the Rec. 709 primaries.
+    pub fn luminance(&self) -> Float {
+        0.2126 * self.r() + 0.7152 * self.g() + 0.0722 * self.b()
+    }
+
+    pub fn max_component(&self) -> Float {
+        self.r().max(self.g()).max(self.b())
+    }
+
+    pub fn is_black(&self) -> bool {
+        self.value.iter().all(|&v| v == 0.0)
+    }
+
+    pub fn srgb_to_linear(&self) -> Self {
+        self.map(|c| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        })
+    }
+
+    pub fn linear_to_srgb(&self) -> Self {
+        self.map(|c| {
+            if c <= 0.0031308 {
+                12.92 * c
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        })
+    }
+
+    pub fn map<F: Fn(Float) -> Float>(&self, f: F) -> Self {
+        Self::new(f(self.r()), f(self.g()), f(self.b()))
+    }
+
+    fn zip_with<F: Fn(Float, Float) -> Float>(self, other: Self, f: F) -> Self {
+        Self::new(
+            f(self.r(), other.r()),
+            f(self.g(), other.g()),
+            f(self.b(), other.b()),
+        )
+    }
+}
+
+impl Add for Color {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.zip_with(other, |a, b| a + b)
+    }
+}
+
+impl AddAssign for Color {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Color {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.zip_with(other, |a, b| a - b)
+    }
+}
+
+impl Mul<Color> for Color {
+    type Output = Self;
+
+    fn mul(self, rhs: Color) -> Self {
+        self.zip_with(rhs, |a, b| a * b)
+    }
+}
+
+impl MulAssign<Color> for Color {
+    fn mul_assign(&mut self, rhs: Color) {
+        *self = *self * rhs;
+    }
+}
+
+impl Mul<Float> for Color {
+    type Output = Self;
+
+    fn mul(self, rhs: Float) -> Self {
+        self.map(|c| c * rhs)
+    }
+}
+
+impl Mul<Color> for Float {
+    type Output = Color;
+
+    fn mul(self, rhs: Color) -> Color {
+        rhs * self
+    }
+}
+
+impl MulAssign<Float> for Color {
+    fn mul_assign(&mut self, rhs: Float) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div<Float> for Color {
+    type Output = Self;
+
+    fn div(self, rhs: Float) -> Self {
+        self.map(|c| c / rhs)
+    }
+}
+
+impl DivAssign<Float> for Color {
+    fn div_assign(&mut self, rhs: Float) {
+        *self = *self / rhs;
+    }
+}
 
 pub fn write_color<T: Write>(
     writer: &mut T,
@@ -9,10 +194,10 @@ pub fn write_color<T: Write>(
     samples_per_pixels: i32,
 ) -> io::Result<()> {
     // Divide the color by the number of samples and gamma-correct for gamma=2.0
-    let scale = 1.0 / samples_per_pixels as f64;
-    let r = (pixel_color.x() * scale).sqrt();
-    let g = (pixel_color.y() * scale).sqrt();
-    let b = (pixel_color.z() * scale).sqrt();
+    let scale = 1.0 / samples_per_pixels as Float;
+    let r = (pixel_color.r() * scale).sqrt();
+    let g = (pixel_color.g() * scale).sqrt();
+    let b = (pixel_color.b() * scale).sqrt();
 
     let ir = (256.0 * r.clamp(0.0, 0.999)) as i32;
     let ig = (256.0 * g.clamp(0.0, 0.999)) as i32;
@@ -20,3 +205,49 @@ pub fn write_color<T: Write>(
 
     writeln!(writer, "{} {} {}", ir, ig, ib)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn mul() {
+        let c = Color::new(1.0, 0.5, 0.25) * Color::new(0.5, 0.5, 4.0);
+
+        assert!((c.r() - 0.5).abs() < EPSILON);
+        assert!((c.g() - 0.25).abs() < EPSILON);
+        assert!((c.b() - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn luminance() {
+        assert!((Color::white().luminance() - 1.0).abs() < EPSILON);
+        assert!(Color::black().luminance().abs() < EPSILON);
+    }
+
+    #[test]
+    fn srgb_round_trip() {
+        let c = Color::new(0.0, 0.2, 0.8);
+        let round_trip = c.linear_to_srgb().srgb_to_linear();
+
+        assert!((round_trip.r() - c.r()).abs() < EPSILON);
+        assert!((round_trip.g() - c.g()).abs() < EPSILON);
+        assert!((round_trip.b() - c.b()).abs() < EPSILON);
+    }
+
+    #[test]
+    fn from_hex() {
+        let c = Color::from_hex("#ff8000").unwrap();
+
+        assert!((c.r() - 1.0).abs() < EPSILON);
+        assert!((c.g() - 0.21586).abs() < EPSILON);
+        assert!(c.b().abs() < EPSILON);
+
+        assert!(Color::from_hex("ff8000").is_some());
+        assert!(Color::from_hex("#ff80").is_none());
+        assert!(Color::from_hex("#gg8000").is_none());
+        assert!(Color::from_hex("#+f+f+f").is_none());
+    }
+}
@@ -0,0 +1,397 @@
+use super::color::{write_color, Color};
+use crate::utils::Float;
+use std::io::{self, Read, Seek, Write};
+
+/// A linear-RGB image stored row by row, top row first.
+#[derive(Clone, Debug)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color::black(); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// Writes the image as a plain (P3) PPM, gamma-corrected by `write_color`.
+    pub fn write_ppm<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        writeln!(writer, "P3")?;
+        writeln!(writer, "{} {}", self.width, self.height)?;
+        writeln!(writer, "255")?;
+
+        for pixel in self.pixels.iter() {
+            write_color(writer, pixel, 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a plain (P3) or binary (P6) PPM with 8-bit channels, undoing the
+    /// gamma correction applied by `write_ppm`.
+    pub fn read_ppm<T: Read>(reader: &mut T) -> io::Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        let mut pos = 0;
+        let magic = next_token(&data, &mut pos)?;
+        let width = parse_number(next_token(&data, &mut pos)?)?;
+        let height = parse_number(next_token(&data, &mut pos)?)?;
+        let max_value = parse_number(next_token(&data, &mut pos)?)?;
+        if max_value == 0 || max_value > 255 {
+            return Err(invalid_data("only 8-bit PPM files are supported"));
+        }
+
+        let samples: Vec<usize> = match magic {
+            b"P3" => (0..width * height * 3)
+                .map(|_| parse_number(next_token(&data, &mut pos)?))
+                .collect::<io::Result<_>>()?,
+            b"P6" => {
+                // Exactly one whitespace byte separates the header from the raster.
+                let raster = data
+                    .get(pos + 1..pos + 1 + width * height * 3)
+                    .ok_or_else(|| invalid_data("truncated PPM raster"))?;
+                raster.iter().map(|&v| v as usize).collect()
+            }
+            _ => return Err(invalid_data("not a P3 or P6 PPM file")),
+        };
+
+        let decode = |v: usize| {
+            let encoded = (v as Float + 0.5) / (max_value + 1) as Float;
+            encoded * encoded
+        };
+
+        let mut image = Self::new(width, height);
+        for (pixel, rgb) in image.pixels.iter_mut().zip(samples.chunks(3)) {
+            *pixel = Color::new(decode(rgb[0]), decode(rgb[1]), decode(rgb[2]));
+        }
+
+        Ok(image)
+    }
+
+    /// Writes the image as an 8-bit RGB PNG, gamma-corrected the same way as
+    /// `write_ppm`.
+    pub fn write_png<T: Write>(&self, writer: T) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let data: Vec<u8> = self
+            .pixels
+            .iter()
+            .flat_map(|c| [encode(c.r()), encode(c.g()), encode(c.b())])
+            .collect();
+
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&data))
+            .map_err(io::Error::other)
+    }
+
+    /// Writes the image as an OpenEXR file with 32-bit float R, G and B
+    /// channels. Unlike the other formats, this keeps the linear values,
+    /// including those outside [0, 1].
+    pub fn write_exr<T: Write + Seek>(&self, writer: T) -> io::Result<()> {
+        write_exr(writer, &[("", self, &["R", "G", "B"])])
+    }
+
+    /// Reads an 8-bit PNG, undoing the gamma correction applied by
+    /// `write_png`. Gray images are read as gray colors and alpha is ignored.
+    pub fn read_png<T: Read>(reader: T) -> io::Result<Self> {
+        Self::read_png_with_alpha(reader).map(|(image, _)| image)
+    }
+
+    /// Reads an 8-bit PNG like `read_png`, along with its alpha channel if it
+    /// has one. Alpha is a plain value rather than a color, so it is not gamma
+    /// decoded; it is returned as a gray image.
+    pub fn read_png_with_alpha<T: Read>(reader: T) -> io::Result<(Self, Option<Self>)> {
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info().map_err(png_error)?;
+
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).map_err(png_error)?;
+        let channels = info.color_type.samples();
+        let has_alpha = channels == 2 || channels == 4;
+
+        let decode = |v: u8| {
+            let encoded = (v as Float + 0.5) / 256.0;
+            encoded * encoded
+        };
+
+        let (width, height) = (info.width as usize, info.height as usize);
+        let mut image = Self::new(width, height);
+        let mut alpha = Self::new(width, height);
+        for (i, texel) in data.chunks(channels).take(width * height).enumerate() {
+            image.pixels[i] = if channels < 3 {
+                let v = decode(texel[0]);
+                Color::new(v, v, v)
+            } else {
+                Color::new(decode(texel[0]), decode(texel[1]), decode(texel[2]))
+            };
+            if has_alpha {
+                let a = texel[channels - 1] as Float / 255.0;
+                alpha.pixels[i] = Color::new(a, a, a);
+            }
+        }
+
+        Ok((image, if has_alpha { Some(alpha) } else { None }))
+    }
+}
+
+/// Writes images of the same size as the channels of one OpenEXR file. Each
+/// comes with a layer name, which prefixes its channels with a dot unless it
+/// is empty, and the names of the channels its red, green and blue hold; an
+/// image with fewer names leaves the rest out.
+pub fn write_exr<T: Write + Seek>(writer: T, layers: &[(&str, &Image, &[&str])]) -> io::Result<()> {
+    use exr::prelude::{
+        AnyChannel, AnyChannels, Encoding, FlatSamples, Image as ExrImage, Layer, LayerAttributes,
+        SmallVec, WritableImage,
+    };
+
+    let (width, height) = match layers.first() {
+        Some((_, image, _)) => (image.width, image.height),
+        None => return Err(invalid_data("no layers to write")),
+    };
+
+    let mut channels = SmallVec::new();
+    for (layer, image, names) in layers {
+        if (image.width, image.height) != (width, height) {
+            return Err(invalid_data("layers differ in size"));
+        }
+        for (i, name) in names.iter().enumerate().take(3) {
+            // The cast does nothing with the `f32` feature.
+            #[allow(clippy::unnecessary_cast)]
+            let samples = image
+                .pixels
+                .iter()
+                .map(|c| [c.r(), c.g(), c.b()][i] as f32)
+                .collect();
+            let name = if layer.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", layer, name)
+            };
+            channels.push(AnyChannel::new(name.as_str(), FlatSamples::F32(samples)));
+        }
+    }
+
+    let layer = Layer::new(
+        (width, height),
+        LayerAttributes::default(),
+        Encoding::FAST_LOSSLESS,
+        AnyChannels::sort(channels),
+    );
+    ExrImage::from_layer(layer)
+        .write()
+        .to_buffered(writer)
+        .map_err(exr_error)
+}
+
+// Gamma-corrects for gamma=2.0 and quantizes, as `write_color` does.
+fn encode(c: Float) -> u8 {
+    (256.0 * c.sqrt().clamp(0.0, 0.999)) as u8
+}
+
+fn png_error(e: png::DecodingError) -> io::Error {
+    match e {
+        png::DecodingError::IoError(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e),
+    }
+}
+
+fn exr_error(e: exr::error::Error) -> io::Error {
+    match e {
+        exr::error::Error::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e),
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Returns the next whitespace-separated token, skipping `#` comments.
+fn next_token<'a>(data: &'a [u8], pos: &mut usize) -> io::Result<&'a [u8]> {
+    loop {
+        while *pos < data.len() && data[*pos].is_ascii_whitespace() {
+            *pos += 1;
+        }
+        if *pos < data.len() && data[*pos] == b'#' {
+            while *pos < data.len() && data[*pos] != b'\n' {
+                *pos += 1;
+            }
+        } else {
+            break;
+        }
+    }
+
+    let start = *pos;
+    while *pos < data.len() && !data[*pos].is_ascii_whitespace() {
+        *pos += 1;
+    }
+
+    if start == *pos {
+        Err(invalid_data("unexpected end of PPM data"))
+    } else {
+        Ok(&data[start..*pos])
+    }
+}
+
+fn parse_number(token: &[u8]) -> io::Result<usize> {
+    std::str::from_utf8(token)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid_data("invalid number in PPM data"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ppm_round_trip() {
+        let mut image = Image::new(2, 1);
+        image.set(0, 0, Color::new(0.25, 0.0, 1.0));
+
+        let mut buf = Vec::new();
+        image.write_ppm(&mut buf).unwrap();
+        let read = Image::read_ppm(&mut buf.as_slice()).unwrap();
+
+        assert_eq!((read.width(), read.height()), (2, 1));
+        assert!((read.get(0, 0).r() - 0.25).abs() < 0.01);
+        assert!(read.get(0, 0).g() < 0.01);
+        assert!(read.get(1, 0).b() < 0.01);
+    }
+
+    #[test]
+    fn read_binary_ppm() {
+        let mut data = b"P6\n# comment\n1 1\n255\n".to_vec();
+        data.extend_from_slice(&[255, 0, 128]);
+        let image = Image::read_ppm(&mut data.as_slice()).unwrap();
+
+        assert!(image.get(0, 0).r() > 0.99);
+        assert!(image.get(0, 0).g() < 0.01);
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let mut image = Image::new(2, 3);
+        image.set(1, 2, Color::new(0.25, 0.0, 1.0));
+
+        let mut buf = Vec::new();
+        image.write_png(&mut buf).unwrap();
+        let read = Image::read_png(buf.as_slice()).unwrap();
+
+        assert_eq!((read.width(), read.height()), (2, 3));
+        assert!((read.get(1, 2).r() - 0.25).abs() < 0.01);
+        assert!(read.get(1, 2).g() < 0.01);
+        assert!(read.get(1, 2).b() > 0.99);
+        assert!(read.get(0, 0).r() < 0.01);
+
+        assert!(Image::read_png(&buf[..buf.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn png_alpha_is_linear() {
+        let mut buf = Vec::new();
+        let mut encoder = png::Encoder::new(&mut buf, 2, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&[255, 255, 255, 0, 128, 128, 128, 51])
+            .unwrap();
+        writer.finish().unwrap();
+
+        let (image, alpha) = Image::read_png_with_alpha(buf.as_slice()).unwrap();
+        let alpha = alpha.unwrap();
+        assert!(alpha.get(0, 0).r().abs() < 0.01);
+        assert!((alpha.get(1, 0).r() - 0.2).abs() < 0.01);
+        // The color is still gamma decoded.
+        assert!((image.get(1, 0).r() - 0.25).abs() < 0.01);
+
+        let mut opaque = Vec::new();
+        image.write_png(&mut opaque).unwrap();
+        assert!(Image::read_png_with_alpha(opaque.as_slice())
+            .unwrap()
+            .1
+            .is_none());
+    }
+
+    #[test]
+    fn exr_keeps_linear_values() {
+        use exr::prelude::{read, FlatSamples, ReadChannels, ReadLayers};
+
+        let mut color = Image::new(2, 3);
+        color.set(1, 2, Color::new(4.5, -0.25, 1.0));
+        let mut depth = Image::new(2, 3);
+        depth.set(0, 1, Color::new(7.0, 7.0, 7.0));
+
+        let mut buf = io::Cursor::new(Vec::new());
+        write_exr(
+            &mut buf,
+            &[("", &color, &["R", "G", "B"]), ("depth", &depth, &["Z"])],
+        )
+        .unwrap();
+
+        buf.set_position(0);
+        let read = read()
+            .no_deep_data()
+            .largest_resolution_level()
+            .all_channels()
+            .first_valid_layer()
+            .all_attributes()
+            .from_buffered(buf)
+            .unwrap();
+        let channel = |name: &str| {
+            let channel = read
+                .layer_data
+                .channel_data
+                .list
+                .iter()
+                .find(|c| c.name == *name)
+                .unwrap_or_else(|| panic!("no channel {}", name));
+            match &channel.sample_data {
+                FlatSamples::F32(samples) => samples.clone(),
+                _ => panic!("{} is not 32-bit float", name),
+            }
+        };
+
+        assert_eq!(read.layer_data.channel_data.list.len(), 4);
+        assert_eq!(channel("R")[5], 4.5);
+        assert_eq!(channel("G")[5], -0.25);
+        assert_eq!(channel("B")[5], 1.0);
+        assert_eq!(channel("depth.Z")[2], 7.0);
+        assert_eq!(channel("depth.Z")[0], 0.0);
+
+        let small = Image::new(1, 1);
+        let mut buf = io::Cursor::new(Vec::new());
+        assert!(write_exr(&mut buf, &[("", &color, &["R"]), ("a", &small, &["R"])]).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use super::vec3::Vec3;
+use super::Float;
+
+/// Items at points in space, arranged for finding those near a given point.
+/// Built once; the tree is balanced and stored in a flat array, each range
+/// of it split at its middle element along the axis it is widest in.
+pub struct KdTree<T> {
+    items: Vec<(Vec3, T)>,
+    // The axis each element splits its range along.
+    axes: Vec<usize>,
+}
+
+impl<T> KdTree<T> {
+    pub fn new(mut items: Vec<(Vec3, T)>) -> Self {
+        let mut axes = vec![0; items.len()];
+        build(&mut items, &mut axes);
+        Self { items, axes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Calls `f` with every item at most `radius` away from `p`, and its
+    /// point.
+    pub fn for_each_within<F: FnMut(&Vec3, &T)>(&self, p: &Vec3, radius: Float, mut f: F) {
+        self.within(0, self.items.len(), p, radius * radius, &mut f);
+    }
+
+    fn within<F: FnMut(&Vec3, &T)>(
+        &self,
+        start: usize,
+        end: usize,
+        p: &Vec3,
+        radius_squared: Float,
+        f: &mut F,
+    ) {
+        if start >= end {
+            return;
+        }
+
+        let middle = start + (end - start) / 2;
+        let (q, item) = &self.items[middle];
+        if (*q - *p).length_squared() <= radius_squared {
+            f(q, item);
+        }
+
+        // The side of the split that `p` is on first, then the other if the
+        // sphere reaches across.
+        let axis = self.axes[middle];
+        let offset = component(p, axis) - component(q, axis);
+        let (near, far) = if offset < 0.0 {
+            ((start, middle), (middle + 1, end))
+        } else {
+            ((middle + 1, end), (start, middle))
+        };
+        self.within(near.0, near.1, p, radius_squared, f);
+        if offset * offset <= radius_squared {
+            self.within(far.0, far.1, p, radius_squared, f);
+        }
+    }
+}
+
+fn component(p: &Vec3, axis: usize) -> Float {
+    match axis {
+        0 => p.x(),
+        1 => p.y(),
+        _ => p.z(),
+    }
+}
+
+fn build<T>(items: &mut [(Vec3, T)], axes: &mut [usize]) {
+    if items.is_empty() {
+        return;
+    }
+
+    let mut min = items[0].0;
+    let mut max = items[0].0;
+    for (p, _) in items.iter() {
+        min = Vec3::new(min.x().min(p.x()), min.y().min(p.y()), min.z().min(p.z()));
+        max = Vec3::new(max.x().max(p.x()), max.y().max(p.y()), max.z().max(p.z()));
+    }
+    let extent = max - min;
+    let axis = (0..3)
+        .max_by(|&a, &b| component(&extent, a).total_cmp(&component(&extent, b)))
+        .unwrap();
+
+    let middle = items.len() / 2;
+    items.select_nth_unstable_by(middle, |a, b| {
+        component(&a.0, axis).total_cmp(&component(&b.0, axis))
+    });
+    axes[middle] = axis;
+
+    let (left, right) = items.split_at_mut(middle);
+    let (left_axes, right_axes) = axes.split_at_mut(middle);
+    build(left, left_axes);
+    build(&mut right[1..], &mut right_axes[1..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::random::{self, random_float};
+
+    #[test]
+    fn finds_the_same_points_as_a_linear_search() {
+        random::seed(1);
+        let points: Vec<Vec3> = (0..1000)
+            .map(|_| Vec3::new(random_float(), 2.0 * random_float(), 0.5 * random_float()))
+            .collect();
+        let tree = KdTree::new(
+            points
+                .iter()
+                .copied()
+                .enumerate()
+                .map(|(i, p)| (p, i))
+                .collect(),
+        );
+        assert_eq!(tree.len(), points.len());
+
+        for _ in 0..100 {
+            let p = Vec3::new(random_float(), 2.0 * random_float(), 0.5 * random_float());
+            let radius = 0.3 * random_float();
+
+            let mut found = Vec::new();
+            tree.for_each_within(&p, radius, |q, &i| {
+                assert_eq!((*q - points[i]).length_squared(), 0.0);
+                found.push(i);
+            });
+            found.sort_unstable();
+
+            let expected: Vec<usize> = (0..points.len())
+                .filter(|&i| (points[i] - p).length_squared() <= radius * radius)
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn empty_tree_finds_nothing() {
+        let tree: KdTree<()> = KdTree::new(Vec::new());
+        assert!(tree.is_empty());
+        tree.for_each_within(&Vec3::new(0.0, 0.0, 0.0), 1.0, |_, _| panic!());
+    }
+}
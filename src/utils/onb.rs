@@ -0,0 +1,66 @@
+use super::vec3::Vec3;
+
+/// An orthonormal basis, used to move between world space and a local frame
+/// whose `w` axis is a given direction.
+#[derive(Copy, Clone, Debug)]
+pub struct Onb {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Onb {
+    pub fn from_w(n: &Vec3) -> Self {
+        let w = n.unit();
+        let a = if w.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(&a).unit();
+        let u = w.cross(&v);
+
+        Self { u, v, w }
+    }
+
+    pub fn u(&self) -> &Vec3 {
+        &self.u
+    }
+
+    pub fn v(&self) -> &Vec3 {
+        &self.v
+    }
+
+    pub fn w(&self) -> &Vec3 {
+        &self.w
+    }
+
+    /// Converts local coordinates to world space.
+    pub fn local(&self, a: &Vec3) -> Vec3 {
+        a.x() * self.u + a.y() * self.v + a.z() * self.w
+    }
+
+    /// Converts a world-space vector to local coordinates.
+    pub fn to_local(&self, a: &Vec3) -> Vec3 {
+        Vec3::new(a.dot(&self.u), a.dot(&self.v), a.dot(&self.w))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Float;
+
+    const EPSILON: Float = 0.00001;
+
+    #[test]
+    fn round_trip() {
+        let onb = Onb::from_w(&Vec3::new(1.0, 2.0, 3.0));
+        let a = Vec3::new(-0.5, 4.0, 2.0);
+        let b = onb.local(&onb.to_local(&a));
+
+        assert!((onb.u().dot(onb.v())).abs() < EPSILON);
+        assert!((onb.w().length() - 1.0).abs() < EPSILON);
+        assert!((a - b).length() < EPSILON);
+    }
+}
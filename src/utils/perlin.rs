@@ -0,0 +1,89 @@
+use super::random::random_float;
+use super::vec3::Vec3;
+use super::Float;
+
+const POINT_COUNT: usize = 256;
+
+/// Ken Perlin's gradient noise, as built in _Ray Tracing: The Next Week_.
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new() -> Self {
+        Self {
+            gradients: (0..POINT_COUNT)
+                .map(|_| Vec3::new_random(-1.0, 1.0).unit())
+                .collect(),
+            perm_x: generate_perm(),
+            perm_y: generate_perm(),
+            perm_z: generate_perm(),
+        }
+    }
+
+    /// Smooth noise in roughly [-1, 1].
+    pub fn noise(&self, p: &Vec3) -> Float {
+        let floor = [p.x().floor(), p.y().floor(), p.z().floor()];
+        let frac = [p.x() - floor[0], p.y() - floor[1], p.z() - floor[2]];
+        let cell = [floor[0] as i64, floor[1] as i64, floor[2] as i64];
+
+        // Hermite smoothing of the interpolation weights.
+        let smooth = frac.map(|f| f * f * (3.0 - 2.0 * f));
+
+        let mut accum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let index = self.perm_x[((cell[0] + di) & 255) as usize]
+                        ^ self.perm_y[((cell[1] + dj) & 255) as usize]
+                        ^ self.perm_z[((cell[2] + dk) & 255) as usize];
+                    let (fi, fj, fk) = (di as Float, dj as Float, dk as Float);
+                    let weight = Vec3::new(frac[0] - fi, frac[1] - fj, frac[2] - fk);
+
+                    accum += (fi * smooth[0] + (1.0 - fi) * (1.0 - smooth[0]))
+                        * (fj * smooth[1] + (1.0 - fj) * (1.0 - smooth[1]))
+                        * (fk * smooth[2] + (1.0 - fk) * (1.0 - smooth[2]))
+                        * self.gradients[index].dot(&weight);
+                }
+            }
+        }
+
+        accum
+    }
+
+    /// Sum of `depth` octaves of noise, each at double the frequency and
+    /// half the amplitude of the previous one.
+    pub fn turbulence(&self, p: &Vec3, depth: usize) -> Float {
+        let mut accum = 0.0;
+        let mut p = *p;
+        let mut weight = 1.0;
+
+        for _ in 0..depth {
+            accum += weight * self.noise(&p);
+            weight *= 0.5;
+            p *= 2.0;
+        }
+
+        accum.abs()
+    }
+}
+
+impl Default for Perlin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn generate_perm() -> Vec<usize> {
+    let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+
+    for i in (1..POINT_COUNT).rev() {
+        let target = ((random_float() * (i + 1) as Float) as usize).min(i);
+        p.swap(i, target);
+    }
+
+    p
+}
@@ -0,0 +1,134 @@
+use super::Float;
+use crate::sampler::{Dimensions, Sampler};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+
+// All randomness in the renderer goes through this thread-local generator so
+// that a render can be reproduced exactly by seeding it first.
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Reseeds the current thread's generator.
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Returns a uniformly distributed number in `[0, 1)`.
+pub fn random_float() -> Float {
+    RNG.with(|rng| rng.borrow_mut().gen())
+}
+
+/// Like `random_float`, always drawn at double precision, so that what is
+/// built from it, such as the layout of a scene, comes out the same whether
+/// `Float` is `f32` or `f64`.
+pub fn random_f64() -> f64 {
+    RNG.with(|rng| rng.borrow_mut().gen())
+}
+
+/// Returns a uniformly distributed number in `[min, max)`.
+pub fn random_range(min: Float, max: Float) -> Float {
+    min + (max - min) * random_float()
+}
+
+// The sampler installed by `with_sampler`, with the next dimension to hand
+// out and the end of the block that the current stage of the path owns.
+struct Active {
+    sampler: Box<dyn Sampler>,
+    next: usize,
+    end: usize,
+}
+
+thread_local! {
+    static SAMPLER: RefCell<Option<Active>> = const { RefCell::new(None) };
+}
+
+// Puts back the sampler that was installed before, even if the render
+// panics.
+struct Restore(Option<Active>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        SAMPLER.with(|active| *active.borrow_mut() = previous);
+    }
+}
+
+/// Runs `f` with `sampler` supplying `sample_1d` and `sample_2d` on the
+/// current thread. Calls may nest; the sampler installed before is back once
+/// `f` returns.
+pub fn with_sampler<R, F: FnOnce() -> R>(sampler: Box<dyn Sampler>, f: F) -> R {
+    let previous = SAMPLER.with(|active| {
+        active.borrow_mut().replace(Active {
+            sampler,
+            next: 0,
+            end: 0,
+        })
+    });
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Runs `f` with the installed sampler set aside, so that `sample_1d` and
+/// `sample_2d` fall back to `random_float` for work outside any pixel
+/// sample.
+pub fn without_sampler<R, F: FnOnce() -> R>(f: F) -> R {
+    let previous = SAMPLER.with(|active| active.borrow_mut().take());
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Moves the installed sampler to sample `index` of pixel (`x`, `y`).
+pub fn start_pixel_sample(x: usize, y: usize, index: usize) {
+    SAMPLER.with(|active| {
+        if let Some(active) = active.borrow_mut().as_mut() {
+            active.sampler.start_pixel_sample(x, y, index);
+            active.next = 0;
+            active.end = 0;
+        }
+    });
+}
+
+/// Hands the dimensions of `block` to the following `sample_1d` and
+/// `sample_2d` calls.
+pub fn start_dimensions(block: Dimensions) {
+    SAMPLER.with(|active| {
+        if let Some(active) = active.borrow_mut().as_mut() {
+            active.next = block.first();
+            active.end = block.first() + block.count();
+        }
+    });
+}
+
+/// Returns the next dimension of the current sample, in `[0, 1)`. Outside a
+/// render, or once the current block is used up, this is `random_float`.
+pub fn sample_1d() -> Float {
+    let sample = SAMPLER.with(|active| {
+        let mut active = active.borrow_mut();
+        let active = active.as_mut()?;
+        if active.next < active.end {
+            active.next += 1;
+            Some(active.sampler.get_1d(active.next - 1))
+        } else {
+            None
+        }
+    });
+    sample.unwrap_or_else(random_float)
+}
+
+/// Returns the next two dimensions of the current sample, which samplers
+/// stratify together where they can. Falls back like `sample_1d`.
+pub fn sample_2d() -> (Float, Float) {
+    let sample = SAMPLER.with(|active| {
+        let mut active = active.borrow_mut();
+        let active = active.as_mut()?;
+        if active.next + 1 < active.end {
+            active.next += 2;
+            Some(active.sampler.get_2d(active.next - 2))
+        } else {
+            None
+        }
+    });
+    sample.unwrap_or_else(|| (random_float(), random_float()))
+}
@@ -0,0 +1,171 @@
+//! Real roots of low-degree polynomials, returned in ascending order.
+
+use super::Float;
+
+const EPSILON: Float = 1e-9;
+
+fn is_zero(x: Float) -> bool {
+    x.abs() < EPSILON
+}
+
+fn sorted(mut roots: Vec<Float>) -> Vec<Float> {
+    roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    roots
+}
+
+/// Solves `a x^2 + b x + c = 0`.
+pub fn solve_quadratic(a: Float, b: Float, c: Float) -> Vec<Float> {
+    if is_zero(a) {
+        return if is_zero(b) { vec![] } else { vec![-c / b] };
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+
+    // Avoid cancellation by computing the larger-magnitude root first.
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if is_zero(q) {
+        return vec![0.0];
+    }
+    sorted(vec![q / a, c / q])
+}
+
+/// Solves `x^3 + a x^2 + b x + c = 0` with Cardano's method.
+pub fn solve_cubic(a: Float, b: Float, c: Float) -> Vec<Float> {
+    // Substitute x = y - a/3 to get y^3 + 3p y + 2q = 0.
+    let sq_a = a * a;
+    let p = (-sq_a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + c) / 2.0;
+
+    let cb_p = p * p * p;
+    let d = q * q + cb_p;
+
+    let roots = if is_zero(d) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if d < 0.0 {
+        // Three real roots.
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        let third = super::consts::PI / 3.0;
+        vec![
+            t * phi.cos(),
+            -t * (phi + third).cos(),
+            -t * (phi - third).cos(),
+        ]
+    } else {
+        let sqrt_d = d.sqrt();
+        vec![(sqrt_d - q).cbrt() - (sqrt_d + q).cbrt()]
+    };
+
+    sorted(roots.into_iter().map(|y| y - a / 3.0).collect())
+}
+
+/// Solves `x^4 + a x^3 + b x^2 + c x + d = 0` with Ferrari's method, then
+/// polishes each root with Newton's method.
+pub fn solve_quartic(a: Float, b: Float, c: Float, d: Float) -> Vec<Float> {
+    // Substitute x = y - a/4 to get y^4 + p y^2 + q y + r = 0.
+    let sq_a = a * a;
+    let p = -3.0 / 8.0 * sq_a + b;
+    let q = sq_a * a / 8.0 - a * b / 2.0 + c;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * b / 16.0 - a * c / 4.0 + d;
+
+    let ys = if is_zero(r) {
+        // y (y^3 + p y + q) = 0
+        let mut ys = solve_cubic(0.0, p, q);
+        ys.push(0.0);
+        ys
+    } else {
+        // Take one real root of the resolvent cubic...
+        let z = match solve_cubic(-p / 2.0, -r, r * p / 2.0 - q * q / 8.0).last() {
+            Some(&z) => z,
+            None => return vec![],
+        };
+
+        // ...and use it to split the quartic into two quadratics.
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        let u = if is_zero(u) {
+            0.0
+        } else if u > 0.0 {
+            u.sqrt()
+        } else {
+            return vec![];
+        };
+        let v = if is_zero(v) {
+            0.0
+        } else if v > 0.0 {
+            v.sqrt()
+        } else {
+            return vec![];
+        };
+
+        let v = if q < 0.0 { -v } else { v };
+        let mut ys = solve_quadratic(1.0, v, z - u);
+        ys.extend(solve_quadratic(1.0, -v, z + u));
+        ys
+    };
+
+    let f = |x: Float| (((x + a) * x + b) * x + c) * x + d;
+    let df = |x: Float| ((4.0 * x + 3.0 * a) * x + 2.0 * b) * x + c;
+
+    sorted(
+        ys.into_iter()
+            .map(|y| {
+                let mut x = y - a / 4.0;
+                for _ in 0..2 {
+                    let slope = df(x);
+                    if slope != 0.0 {
+                        x -= f(x) / slope;
+                    }
+                }
+                x
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(actual: Vec<Float>, expected: &[Float]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-6, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn quadratic() {
+        assert_roots(solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]);
+        assert_roots(solve_quadratic(1.0, 0.0, 1.0), &[]);
+    }
+
+    #[test]
+    fn cubic() {
+        // (x - 1)(x - 2)(x + 3)
+        assert_roots(solve_cubic(0.0, -7.0, 6.0), &[-3.0, 1.0, 2.0]);
+        // (x - 1)(x^2 + 1)
+        assert_roots(solve_cubic(-1.0, 1.0, -1.0), &[1.0]);
+    }
+
+    #[test]
+    fn quartic() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(
+            solve_quartic(-10.0, 35.0, -50.0, 24.0),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        // (x^2 - 4)(x^2 + 1)
+        assert_roots(solve_quartic(0.0, -3.0, 0.0, -4.0), &[-2.0, 2.0]);
+        // (x^2 + 1)(x^2 + 2)
+        assert_roots(solve_quartic(0.0, 3.0, 0.0, 2.0), &[]);
+    }
+}
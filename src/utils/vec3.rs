@@ -1,28 +1,43 @@
-use rand::{thread_rng, Rng};
+use super::consts::PI;
+use super::random::random_range;
+use super::Float;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub};
 
+// With the `simd` feature the vector is stored as four lanes (the fourth is
+// always zero) so that lane-wise operations compile to packed SIMD
+// instructions. Aligning the lanes as well made no measurable difference, and
+// padded every struct holding a vector.
+#[cfg(not(feature = "simd"))]
+const LANES: usize = 3;
+#[cfg(feature = "simd")]
+const LANES: usize = 4;
+
 #[derive(Copy, Clone, Debug)]
 pub struct Vec3 {
-    value: [f64; 3],
+    value: [Float; LANES],
 }
 
 impl Vec3 {
-    pub fn new(x: f64, y: f64, z: f64) -> Self {
+    #[cfg(not(feature = "simd"))]
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
         Self { value: [x, y, z] }
     }
 
-    pub fn new_random(min: f64, max: f64) -> Self {
-        let mut rng = thread_rng();
-
+    #[cfg(feature = "simd")]
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
         Self {
-            value: [
-                rng.gen_range(min..max),
-                rng.gen_range(min..max),
-                rng.gen_range(min..max),
-            ],
+            value: [x, y, z, 0.0],
         }
     }
 
+    pub fn new_random(min: Float, max: Float) -> Self {
+        Self::new(
+            random_range(min, max),
+            random_range(min, max),
+            random_range(min, max),
+        )
+    }
+
     pub fn new_random_in_unit_sphere() -> Self {
         loop {
             let p = Self::new_random(-1.0, 1.0);
@@ -33,10 +48,8 @@ impl Vec3 {
     }
 
     pub fn new_random_in_unit_disk() -> Self {
-        let mut rng = thread_rng();
-
         loop {
-            let p = Self::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0);
+            let p = Self::new(random_range(-1.0, 1.0), random_range(-1.0, 1.0), 0.0);
             if p.length_squared() < 1.0 {
                 return p;
             }
@@ -57,76 +70,130 @@ impl Vec3 {
         Self::new_random_in_unit_sphere().unit()
     }
 
-    pub fn x(&self) -> f64 {
+    /// Maps a point of the unit square uniformly onto the unit sphere.
+    pub fn uniform_on_sphere(u: (Float, Float)) -> Self {
+        let z = 1.0 - 2.0 * u.0;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * PI * u.1;
+        Self::new(r * phi.cos(), r * phi.sin(), z)
+    }
+
+    /// Maps a point of the unit square and a third number in `[0, 1)`
+    /// uniformly into the unit ball.
+    pub fn uniform_in_ball(u: (Float, Float), w: Float) -> Self {
+        w.cbrt() * Self::uniform_on_sphere(u)
+    }
+
+    /// Maps a point of the unit square uniformly onto the unit disk in the
+    /// xy plane. The concentric mapping keeps nearby points nearby, so
+    /// stratified samples stay stratified.
+    pub fn uniform_in_disk(u: (Float, Float)) -> Self {
+        let (x, y) = (2.0 * u.0 - 1.0, 2.0 * u.1 - 1.0);
+        if x == 0.0 && y == 0.0 {
+            return Self::new(0.0, 0.0, 0.0);
+        }
+
+        let (r, theta) = if x.abs() > y.abs() {
+            (x, PI / 4.0 * (y / x))
+        } else {
+            (y, PI / 2.0 - PI / 4.0 * (x / y))
+        };
+        Self::new(r * theta.cos(), r * theta.sin(), 0.0)
+    }
+
+    pub fn x(&self) -> Float {
         self.value[0]
     }
 
-    pub fn y(&self) -> f64 {
+    pub fn y(&self) -> Float {
         self.value[1]
     }
 
-    pub fn z(&self) -> f64 {
+    pub fn z(&self) -> Float {
         self.value[2]
     }
 
-    pub fn length(&self) -> f64 {
+    pub fn length(&self) -> Float {
         self.length_squared().sqrt()
     }
 
-    pub fn length_squared(&self) -> f64 {
-        self.value.iter().map(|&v| v * v).sum()
+    pub fn length_squared(&self) -> Float {
+        self.dot(self)
     }
 
-    pub fn dot(&self, other: &Self) -> f64 {
-        self.value
-            .iter()
-            .zip(other.value.iter())
-            .map(|(&v1, &v2)| v1 * v2)
-            .sum()
+    pub fn dot(&self, other: &Self) -> Float {
+        (*self * *other).value.iter().sum()
     }
 
     pub fn cross(&self, other: &Self) -> Self {
-        Self {
-            value: [
-                self.y() * other.z() - self.z() * other.y(),
-                self.z() * other.x() - self.x() * other.z(),
-                self.x() * other.y() - self.y() * other.x(),
-            ],
-        }
+        Self::new(
+            self.y() * other.z() - self.z() * other.y(),
+            self.z() * other.x() - self.x() * other.z(),
+            self.x() * other.y() - self.y() * other.x(),
+        )
     }
 
     pub fn unit(&self) -> Self {
-        Self { value: self.value } / self.length()
+        *self / self.length()
     }
 
     pub fn near_zero(&self) -> bool {
-        const EPSILON: f64 = 1e-8;
+        const EPSILON: Float = 1e-8;
         (self.x().abs() < EPSILON) && (self.y().abs() < EPSILON) && (self.z().abs() < EPSILON)
     }
 
     pub fn reflect(&self, n: &Vec3) -> Self {
-        self.clone() - 2.0 * self.dot(n) * n.clone()
+        *self - 2.0 * self.dot(n) * *n
     }
 
-    pub fn refract(&self, n: &Vec3, etai_over_etat: f64) -> Self {
-        let cos_theta = (-self.clone()).dot(n).min(1.0);
+    pub fn refract(&self, n: &Vec3, etai_over_etat: Float) -> Self {
+        let cos_theta = (-*self).dot(n).min(1.0);
 
-        let r_out_perp = etai_over_etat * (self.clone() + cos_theta * n.clone());
-        let r_out_parallel = -((1.0 - r_out_perp.length_squared()).abs().sqrt()) * n.clone();
+        let r_out_perp = etai_over_etat * (*self + cos_theta * *n);
+        let r_out_parallel = -((1.0 - r_out_perp.length_squared()).abs().sqrt()) * *n;
 
         r_out_perp + r_out_parallel
     }
+
+    /// Rotates by `angle` radians about the unit vector `axis`,
+    /// counter-clockwise when looking down the axis (Rodrigues' formula).
+    pub fn rotate(&self, axis: &Vec3, angle: Float) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        cos * *self + sin * axis.cross(self) + (1.0 - cos) * axis.dot(self) * *axis
+    }
+
+    fn map<F: Fn(Float) -> Float>(self, f: F) -> Self {
+        Self::from_lanes(self.value.map(f))
+    }
+
+    fn zip_with<F: Fn(Float, Float) -> Float>(self, other: Self, f: F) -> Self {
+        let mut value = self.value;
+        value
+            .iter_mut()
+            .zip(other.value.iter())
+            .for_each(|(v, &o)| *v = f(*v, o));
+        Self::from_lanes(value)
+    }
+
+    // Lane-wise operations also run on the padding lane, where something
+    // like 0 / 0 would leave a NaN for `dot` to sum, so it is zeroed again.
+    #[cfg(not(feature = "simd"))]
+    fn from_lanes(value: [Float; LANES]) -> Self {
+        Self { value }
+    }
+
+    #[cfg(feature = "simd")]
+    fn from_lanes(mut value: [Float; LANES]) -> Self {
+        value[3] = 0.0;
+        Self { value }
+    }
 }
 
 impl Neg for Vec3 {
     type Output = Self;
 
     fn neg(self) -> Self {
-        let mut value: [f64; 3] = [0.0; 3];
-        for i in 0..3 {
-            value[i] = -self.value[i];
-        }
-        Self { value }
+        self.map(|v| -v)
     }
 }
 
@@ -134,19 +201,13 @@ impl Add for Vec3 {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
-        let mut value = [0.0; 3];
-        for i in 0..3 {
-            value[i] = self.value[i] + other.value[i];
-        }
-        Self { value }
+        self.zip_with(other, |a, b| a + b)
     }
 }
 
 impl AddAssign for Vec3 {
     fn add_assign(&mut self, rhs: Self) {
-        self.value.iter_mut().enumerate().for_each(|(i, v)| {
-            *v += rhs.value[i];
-        });
+        *self = *self + rhs;
     }
 }
 
@@ -154,11 +215,7 @@ impl Sub for Vec3 {
     type Output = Self;
 
     fn sub(self, other: Self) -> Self {
-        let mut value = [0.0; 3];
-        for i in 0..3 {
-            value[i] = self.value[i] - other.value[i];
-        }
-        Self { value }
+        self.zip_with(other, |a, b| a - b)
     }
 }
 
@@ -166,27 +223,19 @@ impl Mul<Vec3> for Vec3 {
     type Output = Self;
 
     fn mul(self, rhs: Vec3) -> Self {
-        let mut value = [0.0; 3];
-        for i in 0..3 {
-            value[i] = self.value[i] * rhs.value[i];
-        }
-        Self { value }
+        self.zip_with(rhs, |a, b| a * b)
     }
 }
 
-impl Mul<f64> for Vec3 {
+impl Mul<Float> for Vec3 {
     type Output = Self;
 
-    fn mul(self, rhs: f64) -> Self {
-        let mut value = [0.0; 3];
-        for i in 0..3 {
-            value[i] = self.value[i] * rhs;
-        }
-        Self { value }
+    fn mul(self, rhs: Float) -> Self {
+        self.map(|v| v * rhs)
     }
 }
 
-impl Mul<Vec3> for f64 {
+impl Mul<Vec3> for Float {
     type Output = Vec3;
 
     fn mul(self, rhs: Vec3) -> Vec3 {
@@ -194,39 +243,32 @@ impl Mul<Vec3> for f64 {
     }
 }
 
-impl MulAssign<f64> for Vec3 {
-    fn mul_assign(&mut self, rhs: f64) {
-        self.value.iter_mut().for_each(|v| {
-            *v *= rhs;
-        });
+impl MulAssign<Float> for Vec3 {
+    fn mul_assign(&mut self, rhs: Float) {
+        *self = *self * rhs;
     }
 }
 
-impl Div<f64> for Vec3 {
+impl Div<Float> for Vec3 {
     type Output = Self;
 
-    fn div(self, rhs: f64) -> Self {
-        let mut value = [0.0; 3];
-        for i in 0..3 {
-            value[i] = self.value[i] / rhs;
-        }
-        Self { value }
+    fn div(self, rhs: Float) -> Self {
+        self.map(|v| v / rhs)
     }
 }
 
-impl DivAssign<f64> for Vec3 {
-    fn div_assign(&mut self, rhs: f64) {
-        self.value.iter_mut().for_each(|v| {
-            *v /= rhs;
-        });
+impl DivAssign<Float> for Vec3 {
+    fn div_assign(&mut self, rhs: Float) {
+        *self = *self / rhs;
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::consts::PI;
 
-    const EPSILON: f64 = 0.00001;
+    const EPSILON: Float = 0.00001;
 
     #[test]
     fn dot() {
@@ -236,6 +278,16 @@ mod tests {
         assert!((v1.dot(&v2) - 6.0).abs() < EPSILON);
     }
 
+    #[test]
+    fn padding_stays_out_of_dot() {
+        // Infinite in x, y and z, and 0 * inf or 0 / 0 in the padding lane
+        // with the `simd` feature, which must not turn the sum into NaN.
+        let ones = Vec3::new(1.0, 1.0, 1.0);
+        assert_eq!((ones * Float::INFINITY).dot(&ones), Float::INFINITY);
+        assert_eq!((ones / 0.0).length_squared(), Float::INFINITY);
+        assert_eq!((-(ones / 0.0)).dot(&ones), Float::NEG_INFINITY);
+    }
+
     #[test]
     fn cross() {
         let v1 = Vec3::new(1.0, 0.0, 0.0);
@@ -315,4 +367,13 @@ mod tests {
         assert!((v.y() - 2.0).abs() < EPSILON);
         assert!((v.z() - 3.0).abs() < EPSILON);
     }
+
+    #[test]
+    fn rotate() {
+        let v = Vec3::new(1.0, 0.0, 1.0).rotate(&Vec3::new(0.0, 0.0, 1.0), PI / 2.0);
+
+        assert!((v.x() - 0.0).abs() < EPSILON);
+        assert!((v.y() - 1.0).abs() < EPSILON);
+        assert!((v.z() - 1.0).abs() < EPSILON);
+    }
 }
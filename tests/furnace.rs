@@ -0,0 +1,131 @@
+//! White-furnace tests: an object lit by a uniform white environment must
+//! never come out brighter than the environment, and a lossless material
+//! must be indistinguishable from it.
+
+use raytracing_in_one_weekend::camera::{Camera, PerspectiveCamera};
+use raytracing_in_one_weekend::geometry::{HittableList, Sphere};
+use raytracing_in_one_weekend::material::{Dielectric, Lambertian, Material, Metal};
+use raytracing_in_one_weekend::render::{ray_color, Background};
+use raytracing_in_one_weekend::utils::color::Color;
+use raytracing_in_one_weekend::utils::random;
+use raytracing_in_one_weekend::utils::random::random_float;
+use raytracing_in_one_weekend::utils::vec3::Vec3;
+use raytracing_in_one_weekend::utils::Float;
+use std::rc::Rc;
+
+const SAMPLES: usize = 20_000;
+const MAX_DEPTH: i32 = 50;
+// Starting Russian roulette at the depth limit turns it off, since it would
+// make the exact results exact only on average.
+const NO_ROULETTE: i32 = MAX_DEPTH;
+
+// Tolerance for results that are exact up to accumulated rounding error.
+const EXACT: Float = 1e-3;
+
+// Average radiance seen through a camera whose view is entirely covered by
+// a unit sphere made of `mat`, inside a furnace of radiance 1.
+fn furnace(mat: Box<dyn Material>) -> Color {
+    furnace_with_roulette(mat, NO_ROULETTE)
+}
+
+fn furnace_with_roulette(mat: Box<dyn Material>, roulette_depth: i32) -> Color {
+    let mat: Rc<Box<dyn Material>> = Rc::new(mat);
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(Vec3::new(0.0, 0.0, 0.0), 1.0, &mat)));
+
+    let camera = PerspectiveCamera::new(
+        Vec3::new(0.0, 0.0, 3.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        (1, 1),
+        0.0,
+        3.0,
+    );
+    let background = Background::Solid(Color::white());
+
+    let mut sum = Color::black();
+    for _ in 0..SAMPLES {
+        let r = camera.get_ray(random_float(), random_float());
+        sum += ray_color(&r, &world, &background, MAX_DEPTH, roulette_depth);
+    }
+    sum / SAMPLES as Float
+}
+
+fn assert_close(name: &str, actual: Color, expected: Color, tolerance: Float) {
+    let d = actual - expected;
+    assert!(
+        d.r().abs() < tolerance && d.g().abs() < tolerance && d.b().abs() < tolerance,
+        "{}: expected {:?}, got {:?}",
+        name,
+        expected,
+        actual
+    );
+}
+
+fn assert_conserves_energy(name: &str, actual: Color) {
+    assert!(
+        actual.max_component() <= 1.0 + EXACT,
+        "{}: reflected more energy than it received: {:?}",
+        name,
+        actual
+    );
+}
+
+#[test]
+fn lambertian_furnace() {
+    random::seed(1);
+    let white = furnace(Box::new(Lambertian::new(Color::white())));
+    assert_close("white lambertian", white, Color::white(), EXACT);
+
+    // A single bounce off a convex object scales by the albedo exactly.
+    let albedo = Color::new(0.2, 0.5, 0.8);
+    let colored = furnace(Box::new(Lambertian::new(albedo)));
+    assert_close("colored lambertian", colored, albedo, EXACT);
+}
+
+#[test]
+fn metal_furnace() {
+    random::seed(2);
+    let mirror = furnace(Box::new(Metal::new(Color::white(), 0.0)));
+    assert_close("mirror", mirror, Color::white(), EXACT);
+
+    // Fuzzed rays that end up below the surface are absorbed.
+    for &fuzz in [0.3, 1.0].iter() {
+        let fuzzy = furnace(Box::new(Metal::new(Color::white(), fuzz)));
+        assert_conserves_energy("fuzzy metal", fuzzy);
+        assert!(fuzzy.luminance() > 0.5, "fuzzy metal: {:?}", fuzzy);
+    }
+}
+
+#[test]
+fn dielectric_furnace() {
+    random::seed(3);
+    // Paths caught by total internal reflection may hit the depth limit, so
+    // allow a small loss.
+    for &ir in [1.0, 1.5, 2.4].iter() {
+        let glass = furnace(Box::new(Dielectric::new(ir)));
+        assert_conserves_energy("dielectric", glass);
+        assert_close("dielectric", glass, Color::white(), 0.01);
+    }
+}
+
+#[test]
+fn russian_roulette_is_unbiased() {
+    random::seed(4);
+    // Paths through a dim material are mostly ended after the first bounce,
+    // and the survivors make up for them.
+    let albedo = Color::new(0.2, 0.5, 0.8);
+    let colored = furnace_with_roulette(Box::new(Lambertian::new(albedo)), 0);
+    assert_close("roulette lambertian", colored, albedo, 0.01);
+
+    // Lossless paths are never ended.
+    let white = furnace_with_roulette(Box::new(Lambertian::new(Color::white())), 0);
+    assert_close("roulette white lambertian", white, Color::white(), EXACT);
+
+    for &ir in [1.5, 2.4].iter() {
+        let glass = furnace_with_roulette(Box::new(Dielectric::new(ir)), 1);
+        assert_conserves_energy("roulette dielectric", glass);
+        assert_close("roulette dielectric", glass, Color::white(), 0.01);
+    }
+}
@@ -0,0 +1,244 @@
+//! Golden-image regression tests.
+//!
+//! Each test renders a small seeded scene and compares it with the reference
+//! in `tests/golden/`. On failure the actual render and a difference image
+//! are written to `target/golden/`. Regenerate the references with
+//!
+//!     UPDATE_GOLDEN=1 cargo test --test golden
+
+use raytracing_in_one_weekend::camera::PerspectiveCamera;
+use raytracing_in_one_weekend::geometry::Hittable;
+use raytracing_in_one_weekend::integrator::IntegratorKind;
+use raytracing_in_one_weekend::render::{render, Background, RenderSettings};
+use raytracing_in_one_weekend::sampler::SamplerKind;
+use raytracing_in_one_weekend::scenes::{cornell_box, random_scene, three_spheres};
+use raytracing_in_one_weekend::utils::color::Color;
+use raytracing_in_one_weekend::utils::image::Image;
+use raytracing_in_one_weekend::utils::random;
+use raytracing_in_one_weekend::utils::vec3::Vec3;
+use raytracing_in_one_weekend::utils::Float;
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+
+// Scenes that place objects randomly are built from `SCENE_SEED`; the render
+// itself uses `RENDER_SEED`.
+const SCENE_SEED: u64 = 42;
+const RENDER_SEED: u64 = 1;
+
+// Errors are measured after averaging blocks of this many pixels squared,
+// so that per-pixel sampling noise does not dominate the comparison.
+const BLOCK: usize = 4;
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn output_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/golden")
+}
+
+fn write_image(path: &PathBuf, image: &Image) {
+    let mut writer = BufWriter::new(File::create(path).unwrap());
+    image.write_ppm(&mut writer).unwrap();
+}
+
+// Round-trips the image through the 8-bit PPM encoding used for references.
+fn quantize(image: &Image) -> Image {
+    let mut buf = Vec::new();
+    image.write_ppm(&mut buf).unwrap();
+    Image::read_ppm(&mut buf.as_slice()).unwrap()
+}
+
+fn downsample(image: &Image, block: usize) -> Image {
+    let mut small = Image::new(image.width() / block, image.height() / block);
+    for y in 0..small.height() {
+        for x in 0..small.width() {
+            let mut sum = Color::black();
+            for dy in 0..block {
+                for dx in 0..block {
+                    sum += image.get(x * block + dx, y * block + dy);
+                }
+            }
+            small.set(x, y, sum / (block * block) as Float);
+        }
+    }
+    small
+}
+
+// Root-mean-square error over all channels of the block-averaged images, in
+// display (gamma-encoded) space.
+fn rmse(a: &Image, b: &Image) -> Float {
+    let a = downsample(a, BLOCK);
+    let b = downsample(b, BLOCK);
+
+    let sum: Float = a
+        .pixels()
+        .iter()
+        .zip(b.pixels().iter())
+        .map(|(pa, pb)| {
+            let d = pa.map(Float::sqrt) - pb.map(Float::sqrt);
+            d.r() * d.r() + d.g() * d.g() + d.b() * d.b()
+        })
+        .sum();
+
+    (sum / (3 * a.pixels().len()) as Float).sqrt()
+}
+
+fn diff_image(a: &Image, b: &Image) -> Image {
+    let mut diff = Image::new(a.width(), a.height());
+    for y in 0..a.height() {
+        for x in 0..a.width() {
+            // Amplify differences so small errors remain visible.
+            let d = (a.get(x, y).map(Float::sqrt) - b.get(x, y).map(Float::sqrt))
+                .map(|c| (4.0 * c.abs()).min(1.0));
+            diff.set(x, y, d * d);
+        }
+    }
+    diff
+}
+
+fn assert_matches_golden(name: &str, image: &Image, tolerance: Float) {
+    let reference_path = golden_dir().join(format!("{}.ppm", name));
+    let actual = quantize(image);
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(golden_dir()).unwrap();
+        write_image(&reference_path, &actual);
+        return;
+    }
+
+    let reference = Image::read_ppm(&mut BufReader::new(
+        File::open(&reference_path)
+            .unwrap_or_else(|e| panic!("cannot open {}: {}", reference_path.display(), e)),
+    ))
+    .unwrap();
+
+    assert_eq!(
+        (reference.width(), reference.height()),
+        (actual.width(), actual.height()),
+        "{}: image size differs from the reference",
+        name
+    );
+
+    let error = rmse(&actual, &reference);
+    if error > tolerance {
+        fs::create_dir_all(output_dir()).unwrap();
+        let actual_path = output_dir().join(format!("{}.actual.ppm", name));
+        let diff_path = output_dir().join(format!("{}.diff.ppm", name));
+        write_image(&actual_path, &actual);
+        write_image(&diff_path, &diff_image(&actual, &reference));
+
+        panic!(
+            "{}: RMSE {:.4} exceeds tolerance {:.4}; see {} and {}",
+            name,
+            error,
+            tolerance,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+fn render_seeded<T: Hittable>(
+    world: &T,
+    camera: &PerspectiveCamera,
+    settings: &RenderSettings,
+) -> Image {
+    random::seed(RENDER_SEED);
+    render(world, camera, settings)
+}
+
+#[test]
+fn three_spheres_scene() {
+    random::seed(SCENE_SEED);
+    let world = three_spheres();
+
+    let settings = RenderSettings {
+        image_width: 80,
+        image_height: 45,
+        samples_per_pixel: 32,
+        max_depth: 20,
+        roulette_depth: 5,
+        background: Background::Sky,
+        crop: None,
+        sampler: SamplerKind::Independent,
+        integrator: IntegratorKind::Path,
+        lights: Vec::new(),
+    };
+    let camera = PerspectiveCamera::new(
+        Vec3::new(-2.0, 2.0, 1.0),
+        Vec3::new(0.0, 0.0, -1.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        40.0,
+        settings.image_size(),
+        0.0,
+        1.0,
+    );
+
+    let image = render_seeded(&world, &camera, &settings);
+    assert_matches_golden("three_spheres", &image, 0.015);
+}
+
+#[test]
+fn random_scene_scene() {
+    random::seed(SCENE_SEED);
+    let world = random_scene();
+
+    let settings = RenderSettings {
+        image_width: 72,
+        image_height: 48,
+        samples_per_pixel: 16,
+        max_depth: 20,
+        roulette_depth: 5,
+        background: Background::Sky,
+        crop: None,
+        sampler: SamplerKind::Independent,
+        integrator: IntegratorKind::Path,
+        lights: Vec::new(),
+    };
+    let camera = PerspectiveCamera::new(
+        Vec3::new(13.0, 2.0, 3.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        settings.image_size(),
+        0.1,
+        10.0,
+    );
+
+    let image = render_seeded(&world, &camera, &settings);
+    assert_matches_golden("random_scene", &image, 0.02);
+}
+
+#[test]
+fn cornell_box_scene() {
+    random::seed(SCENE_SEED);
+    let world = cornell_box();
+
+    let settings = RenderSettings {
+        image_width: 48,
+        image_height: 48,
+        samples_per_pixel: 256,
+        max_depth: 20,
+        roulette_depth: 5,
+        background: Background::Solid(Color::black()),
+        crop: None,
+        sampler: SamplerKind::Independent,
+        integrator: IntegratorKind::Path,
+        lights: Vec::new(),
+    };
+    let camera = PerspectiveCamera::new(
+        Vec3::new(278.0, 278.0, -800.0),
+        Vec3::new(278.0, 278.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        40.0,
+        settings.image_size(),
+        0.0,
+        10.0,
+    );
+
+    let image = render_seeded(&world, &camera, &settings);
+    assert_matches_golden("cornell_box", &image, 0.05);
+}
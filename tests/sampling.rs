@@ -0,0 +1,437 @@
+//! Chi-square tests of the random sampling routines and of the direction
+//! distributions produced by `Material::scatter`, and of how well the
+//! samplers integrate.
+
+use raytracing_in_one_weekend::geometry::{HitRecord, Ray};
+use raytracing_in_one_weekend::material::{Dielectric, Lambertian, Material, Metal};
+use raytracing_in_one_weekend::sampler::{Dimensions, SamplerKind};
+use raytracing_in_one_weekend::utils::color::Color;
+use raytracing_in_one_weekend::utils::consts::PI;
+use raytracing_in_one_weekend::utils::random;
+use raytracing_in_one_weekend::utils::vec3::Vec3;
+use raytracing_in_one_weekend::utils::Float;
+use std::rc::Rc;
+
+const SAMPLES: usize = 200_000;
+const THETA_BINS: usize = 10;
+const PHI_BINS: usize = 16;
+
+// Upper-tail standard normal quantile for a significance level of 0.001.
+const Z_ALPHA: Float = 3.090;
+
+// Critical chi-square value for `df` degrees of freedom (Wilson-Hilferty).
+fn chi_square_critical(df: usize) -> Float {
+    let df = df as Float;
+    let a = 2.0 / (9.0 * df);
+    df * (1.0 - a + Z_ALPHA * a.sqrt()).powi(3)
+}
+
+// Asserts that the observed counts are consistent with the expected
+// probabilities of each bin. Bins with tiny expectations are merged into a
+// single bin so the test stays valid.
+fn assert_chi_square(name: &str, observed: &[usize], probabilities: &[Float]) {
+    let total: usize = observed.iter().sum();
+    let mut statistic = 0.0;
+    let mut df = 0;
+    let mut pooled_observed = 0.0;
+    let mut pooled_expected = 0.0;
+
+    for (&o, &p) in observed.iter().zip(probabilities.iter()) {
+        let expected = p * total as Float;
+        if expected < 5.0 {
+            assert!(
+                p > 0.0 || o == 0,
+                "{}: {} samples fell into a bin of zero probability",
+                name,
+                o
+            );
+            pooled_observed += o as Float;
+            pooled_expected += expected;
+            continue;
+        }
+
+        let d = o as Float - expected;
+        statistic += d * d / expected;
+        df += 1;
+    }
+
+    if pooled_expected > 0.0 {
+        let d = pooled_observed - pooled_expected;
+        statistic += d * d / pooled_expected;
+        df += 1;
+    }
+
+    let critical = chi_square_critical(df - 1);
+    assert!(
+        statistic < critical,
+        "{}: chi-square statistic {:.1} exceeds critical value {:.1} ({} dof)",
+        name,
+        statistic,
+        critical,
+        df - 1
+    );
+}
+
+// An orthonormal frame around `n`, used to express directions as (cos θ, φ).
+fn frame(n: &Vec3) -> (Vec3, Vec3, Vec3) {
+    let w = n.unit();
+    let a = if w.x().abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let v = w.cross(&a).unit();
+    let u = w.cross(&v);
+    (u, v, w)
+}
+
+// Bins directions on the full sphere by (cos θ, φ) around `n`.
+fn bin_directions<I: Iterator<Item = Vec3>>(n: &Vec3, directions: I) -> Vec<usize> {
+    let (u, v, w) = frame(n);
+    let mut counts = vec![0; THETA_BINS * PHI_BINS];
+
+    for d in directions {
+        let d = d.unit();
+        let cos_theta = d.dot(&w).clamp(-1.0, 1.0);
+        let phi = d.dot(&v).atan2(d.dot(&u)) + PI;
+
+        let i = (((cos_theta + 1.0) / 2.0 * THETA_BINS as Float) as usize).min(THETA_BINS - 1);
+        let j = ((phi / (2.0 * PI) * PHI_BINS as Float) as usize).min(PHI_BINS - 1);
+        counts[i * PHI_BINS + j] += 1;
+    }
+
+    counts
+}
+
+// Expected bin probabilities for a distribution whose CDF in cos θ is `cdf`
+// and which is uniform in φ.
+fn bin_probabilities<F: Fn(Float) -> Float>(cdf: F) -> Vec<Float> {
+    let mut probabilities = Vec::with_capacity(THETA_BINS * PHI_BINS);
+    for i in 0..THETA_BINS {
+        let a = -1.0 + 2.0 * i as Float / THETA_BINS as Float;
+        let b = -1.0 + 2.0 * (i + 1) as Float / THETA_BINS as Float;
+        let p = (cdf(b) - cdf(a)) / PHI_BINS as Float;
+        probabilities.extend(std::iter::repeat_n(p, PHI_BINS));
+    }
+    probabilities
+}
+
+fn uniform_sphere_cdf(c: Float) -> Float {
+    (c + 1.0) / 2.0
+}
+
+fn uniform_hemisphere_cdf(c: Float) -> Float {
+    c.max(0.0)
+}
+
+fn cosine_hemisphere_cdf(c: Float) -> Float {
+    c.max(0.0).powi(2)
+}
+
+fn hit_record(normal: Vec3, r_in: &Ray, mat: &Rc<Box<dyn Material>>) -> HitRecord {
+    HitRecord::new(
+        Vec3::new(0.0, 0.0, 0.0),
+        1.0,
+        (0.0, 0.0),
+        r_in,
+        &normal,
+        mat,
+    )
+}
+
+#[test]
+fn random_unit_vector_is_uniform_on_sphere() {
+    random::seed(1);
+    let n = Vec3::new(0.0, 0.0, 1.0);
+    let observed = bin_directions(&n, (0..SAMPLES).map(|_| Vec3::new_random_unit_vector()));
+
+    for v in (0..1000).map(|_| Vec3::new_random_unit_vector()) {
+        assert!((v.length() - 1.0).abs() < 1e-6);
+    }
+    assert_chi_square(
+        "unit vector",
+        &observed,
+        &bin_probabilities(uniform_sphere_cdf),
+    );
+}
+
+#[test]
+fn random_in_unit_sphere_is_uniform_in_volume() {
+    random::seed(2);
+    let points: Vec<Vec3> = (0..SAMPLES)
+        .map(|_| Vec3::new_random_in_unit_sphere())
+        .collect();
+
+    // Directions are uniform on the sphere.
+    let n = Vec3::new(0.3, -0.5, 0.8);
+    let observed = bin_directions(&n, points.iter().copied());
+    assert_chi_square(
+        "unit sphere direction",
+        &observed,
+        &bin_probabilities(uniform_sphere_cdf),
+    );
+
+    // The radius has CDF r^3.
+    const RADIUS_BINS: usize = 20;
+    let mut observed = vec![0; RADIUS_BINS];
+    for p in points.iter() {
+        let r = p.length();
+        assert!(r < 1.0);
+        let i = ((r.powi(3) * RADIUS_BINS as Float) as usize).min(RADIUS_BINS - 1);
+        observed[i] += 1;
+    }
+    let probabilities = vec![1.0 / RADIUS_BINS as Float; RADIUS_BINS];
+    assert_chi_square("unit sphere radius", &observed, &probabilities);
+}
+
+#[test]
+fn random_in_hemisphere_is_uniform_on_hemisphere() {
+    random::seed(3);
+    let n = Vec3::new(0.0, 1.0, 0.0);
+    let observed = bin_directions(&n, (0..SAMPLES).map(|_| Vec3::new_random_in_hemisphere(&n)));
+
+    assert_chi_square(
+        "hemisphere",
+        &observed,
+        &bin_probabilities(uniform_hemisphere_cdf),
+    );
+}
+
+#[test]
+fn random_in_unit_disk_is_uniform() {
+    random::seed(4);
+    const BINS: usize = 16;
+    let mut observed = vec![0; BINS * BINS];
+
+    for _ in 0..SAMPLES {
+        let p = Vec3::new_random_in_unit_disk();
+        assert!(p.length_squared() < 1.0 && p.z() == 0.0);
+
+        // Equal-area bins: r^2 and the angle are both uniform.
+        let i = ((p.length_squared() * BINS as Float) as usize).min(BINS - 1);
+        let phi = p.y().atan2(p.x()) + PI;
+        let j = ((phi / (2.0 * PI) * BINS as Float) as usize).min(BINS - 1);
+        observed[i * BINS + j] += 1;
+    }
+
+    let probabilities = vec![1.0 / (BINS * BINS) as Float; BINS * BINS];
+    assert_chi_square("unit disk", &observed, &probabilities);
+}
+
+#[test]
+fn square_mappings_are_uniform() {
+    random::seed(5);
+    let pair = || (random::random_float(), random::random_float());
+
+    let n = Vec3::new(0.2, 0.9, -0.4);
+    let observed = bin_directions(&n, (0..SAMPLES).map(|_| Vec3::uniform_on_sphere(pair())));
+    assert_chi_square(
+        "square to sphere",
+        &observed,
+        &bin_probabilities(uniform_sphere_cdf),
+    );
+
+    const BINS: usize = 16;
+    let mut observed = vec![0; BINS * BINS];
+    for _ in 0..SAMPLES {
+        let p = Vec3::uniform_in_disk(pair());
+        assert!(p.length_squared() <= 1.0 && p.z() == 0.0);
+
+        let i = ((p.length_squared() * BINS as Float) as usize).min(BINS - 1);
+        let phi = p.y().atan2(p.x()) + PI;
+        let j = ((phi / (2.0 * PI) * BINS as Float) as usize).min(BINS - 1);
+        observed[i * BINS + j] += 1;
+    }
+    let probabilities = vec![1.0 / (BINS * BINS) as Float; BINS * BINS];
+    assert_chi_square("square to disk", &observed, &probabilities);
+
+    const RADIUS_BINS: usize = 20;
+    let mut observed = vec![0; RADIUS_BINS];
+    for _ in 0..SAMPLES {
+        let r = Vec3::uniform_in_ball(pair(), random::random_float()).length();
+        assert!(r <= 1.0);
+        let i = ((r.powi(3) * RADIUS_BINS as Float) as usize).min(RADIUS_BINS - 1);
+        observed[i] += 1;
+    }
+    let probabilities = vec![1.0 / RADIUS_BINS as Float; RADIUS_BINS];
+    assert_chi_square("square to ball radius", &observed, &probabilities);
+}
+
+// Root-mean-square error of per-pixel estimates of the integral of a smooth
+// function over the unit square, using 16 samples per pixel.
+fn sampler_error(kind: SamplerKind, dimension: usize) -> Float {
+    const PIXELS: usize = 256;
+    const SPP: usize = 16;
+    let f = |u: Float, v: Float| u * v + (3.0 * u).sin();
+    let exact = 0.25 + (1.0 - (3.0 as Float).cos()) / 3.0;
+
+    let mut sampler = kind.build(SPP);
+    let mut sum = 0.0;
+    for pixel in 0..PIXELS {
+        let mut estimate = 0.0;
+        for index in 0..SPP {
+            sampler.start_pixel_sample(pixel % 16, pixel / 16, index);
+            let (u, v) = sampler.get_2d(dimension);
+            estimate += f(u, v);
+        }
+        let error = estimate / SPP as Float - exact;
+        sum += error * error;
+    }
+    (sum / PIXELS as Float).sqrt()
+}
+
+#[test]
+fn samplers_beat_independent_sampling() {
+    random::seed(6);
+    for &dimension in &[Dimensions::Pixel.first(), Dimensions::Bounce(3).first()] {
+        let independent = sampler_error(SamplerKind::Independent, dimension);
+        for &kind in &[
+            SamplerKind::Stratified,
+            SamplerKind::Halton,
+            SamplerKind::Sobol,
+            SamplerKind::BlueNoise,
+        ] {
+            // Halton dimensions use ever larger prime bases, which 16 samples
+            // cannot stratify finely; deep down they only avoid being worse.
+            let bound = if kind == SamplerKind::Halton && dimension > Dimensions::Lens.first() {
+                independent
+            } else {
+                0.5 * independent
+            };
+
+            let error = sampler_error(kind, dimension);
+            assert!(
+                error < bound,
+                "{:?} in dimension {}: RMSE {:.5} vs {:.5} independent",
+                kind,
+                dimension,
+                error,
+                independent
+            );
+        }
+    }
+}
+
+#[test]
+fn lambertian_scatter_is_cosine_distributed() {
+    random::seed(5);
+    let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+    let n = Vec3::new(1.0, 2.0, -0.5).unit();
+    let r_in = Ray::new(&(2.0 * n), &(-n));
+    let rec = hit_record(n, &r_in, &mat);
+
+    let observed = bin_directions(
+        &n,
+        (0..SAMPLES).map(|_| {
+            let (attenuation, scattered) = mat.scatter(&r_in, &rec).unwrap();
+            assert_eq!(attenuation, Color::new(0.5, 0.5, 0.5));
+            *scattered.direction()
+        }),
+    );
+
+    assert_chi_square(
+        "lambertian",
+        &observed,
+        &bin_probabilities(cosine_hemisphere_cdf),
+    );
+}
+
+#[test]
+fn smooth_metal_scatter_is_mirror_reflection() {
+    random::seed(6);
+    let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Metal::new(Color::new(0.9, 0.9, 0.9), 0.0)));
+    let n = Vec3::new(0.0, 1.0, 0.0);
+    let r_in = Ray::new(&Vec3::new(-1.0, 1.0, 0.0), &Vec3::new(1.0, -1.0, 0.0));
+    let rec = hit_record(n, &r_in, &mat);
+
+    let (_, scattered) = mat.scatter(&r_in, &rec).unwrap();
+    let expected = Vec3::new(1.0, 1.0, 0.0).unit();
+    assert!((scattered.direction().unit() - expected).length() < 1e-6);
+}
+
+#[test]
+fn fuzzy_metal_scatter_is_uniform_around_reflection() {
+    random::seed(7);
+    let fuzz = 0.5;
+    let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Metal::new(Color::new(0.9, 0.9, 0.9), fuzz)));
+    let n = Vec3::new(0.0, 0.0, 1.0);
+    // At normal incidence the fuzz ball never dips below the surface, so no
+    // samples are absorbed.
+    let r_in = Ray::new(&Vec3::new(0.0, 0.0, 1.0), &Vec3::new(0.0, 0.0, -1.0));
+    let rec = hit_record(n, &r_in, &mat);
+
+    let offsets = (0..SAMPLES).map(|_| {
+        let (_, scattered) = mat.scatter(&r_in, &rec).unwrap();
+        (*scattered.direction() - n) / fuzz
+    });
+
+    let observed = bin_directions(&n, offsets);
+    assert_chi_square(
+        "fuzzy metal",
+        &observed,
+        &bin_probabilities(uniform_sphere_cdf),
+    );
+}
+
+#[test]
+fn dielectric_reflects_with_schlick_probability() {
+    random::seed(8);
+    let ir = 1.5;
+    let mat: Rc<Box<dyn Material>> = Rc::new(Box::new(Dielectric::new(ir)));
+    let n = Vec3::new(0.0, 1.0, 0.0);
+
+    for &cos_theta in [1.0, 0.5, 0.1].iter() {
+        let sin_theta = (1.0 - cos_theta * cos_theta as Float).sqrt();
+        let direction = Vec3::new(sin_theta, -cos_theta, 0.0);
+        let r_in = Ray::new(&(-direction), &direction);
+        let rec = hit_record(n, &r_in, &mat);
+
+        let reflected = (0..SAMPLES)
+            .filter(|_| {
+                let (attenuation, scattered) = mat.scatter(&r_in, &rec).unwrap();
+                assert_eq!(attenuation, Color::white());
+                scattered.direction().dot(&n) > 0.0
+            })
+            .count();
+
+        let r0 = ((1.0 - ir) / (1.0 + ir)).powi(2);
+        let reflectance = r0 + (1.0 - r0) * (1.0 - cos_theta).powi(5);
+        assert_chi_square(
+            &format!("dielectric at cos θ = {}", cos_theta),
+            &[reflected, SAMPLES - reflected],
+            &[reflectance, 1.0 - reflectance],
+        );
+    }
+}
+
+#[test]
+fn tilted_shading_normal_keeps_scatter_on_geometric_side() {
+    random::seed(9);
+    let n = Vec3::new(0.0, 1.0, 0.0);
+    // A grazing ray, with the shading normal tilted along it so that
+    // scattering about the shading normal alone would go through the surface.
+    let direction = Vec3::new(1.0, -0.1, 0.0).unit();
+    let r_in = Ray::new(&(-direction), &direction);
+    let shading_normal = Vec3::new(0.8, 0.6, 0.0);
+
+    let materials: [(Rc<Box<dyn Material>>, bool); 3] = [
+        (Rc::new(Box::new(Lambertian::new(Color::white()))), false),
+        (Rc::new(Box::new(Metal::new(Color::white(), 0.0))), false),
+        (Rc::new(Box::new(Dielectric::new(1.5))), true),
+    ];
+    for (mat, transmits) in materials.iter() {
+        let rec = hit_record(n, &r_in, mat).with_shading_normal(&shading_normal);
+        let mut crossed = 0;
+        for _ in 0..10_000 {
+            let (_, scattered) = mat.scatter(&r_in, &rec).unwrap();
+            let outgoing = scattered.direction().dot(&n);
+            assert!(outgoing != 0.0);
+            if outgoing < 0.0 {
+                crossed += 1;
+            }
+        }
+
+        // Only a refraction may cross the geometric surface.
+        assert_eq!(crossed > 0, *transmits);
+    }
+}